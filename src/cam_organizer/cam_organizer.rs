@@ -1,35 +1,49 @@
 
-use std::{sync::{Arc, atomic::AtomicBool}, time::{Duration, Instant}, cell::RefCell, rc::Rc, pin::Pin};
+use std::{sync::{Arc, atomic::AtomicBool}, time::Duration, cmp::Ordering};
 
 use tokio::{sync::RwLock};
 use bytemuck::{Pod, Zeroable};
-use flume::{Sender, r#async};
-use futures::{join, future::{join_all, BoxFuture}, Future};
+use futures::join;
 
-use crate::{model::model::GameObjectList, rendering::{wgpurenderer::RenderChunk, sprite_instance::SpriteInstance, sprites::vertex_configration::VertexConfigrationTrait}, controller::controller::{SharablePosition, Direction}, game_objects::game_object::{self, DrawableObject}};
+use super::{frame_mailbox::FrameMailbox, interpolation::interpolate};
+use crate::{model::{tick_clock::TickClock, render_snapshot::{SharedRenderSnapshot, RenderSnapshot, RenderItem}}, rendering::{wgpurenderer::RenderChunk, sprite_instance::SpriteInstance, debug_overlay::stats::DebugStats, text::layout::{GlyphInstance, layout_text}}, controller::controller::{SharablePosition, Direction}};
 
 const CAMERA_SPEED: f32 = 15.0;
+
+//what the CamOrganizer reads from the model and hands on to the renderer, grouped so CamOrganizer::new stays short
+pub(crate) struct CamOrganizerChannels{
+    pub(crate) render_snapshot: SharedRenderSnapshot,
+    pub(crate) tick_clock: Arc<TickClock>,
+    pub(crate) frame_mailbox: Arc<FrameMailbox>,
+    pub(crate) debug_stats: Arc<DebugStats>,
+}
+
 pub(crate) struct CamOrganizer{
     state: u32,
-    pub(crate) game_objects: GameObjectList,
+    render_snapshot: SharedRenderSnapshot,     //published by the model after every tick, reading it takes no locks
     cam_pos: SharablePosition,
     cam_proportions: Arc<RwLock<(f32, f32)>>,
     cam_directions: Arc<RwLock<(Direction, Direction)>>,
-    sender: Sender<(Vec<RenderChunk>, CamState)>,
+    frame_mailbox: Arc<FrameMailbox>,
+    tick_clock: Arc<TickClock>,
+    debug_stats: Arc<DebugStats>,
     pub(crate) running: Arc<AtomicBool>,  //<-- this is used to indicate whether the program should exit or not
     
 }
 
 impl CamOrganizer{
 
-    pub(crate) fn new(game_objects : GameObjectList, cam_pos: SharablePosition, sender: Sender<(Vec<RenderChunk>, CamState)>, cam_proportions: Arc<RwLock<(f32, f32)>>, cam_directions: Arc<RwLock<(Direction, Direction)>>, running: Arc<AtomicBool>) -> CamOrganizer{
+    pub(crate) fn new(channels: CamOrganizerChannels, cam_pos: SharablePosition, cam_proportions: Arc<RwLock<(f32, f32)>>, cam_directions: Arc<RwLock<(Direction, Direction)>>, running: Arc<AtomicBool>) -> CamOrganizer{
+        let CamOrganizerChannels{ render_snapshot, tick_clock, frame_mailbox, debug_stats } = channels;
         CamOrganizer{
             state: 0,
-            game_objects: game_objects,
+            render_snapshot,
             cam_pos: cam_pos,
             cam_proportions: cam_proportions,
             cam_directions,
-            sender: sender,
+            frame_mailbox,
+            tick_clock,
+            debug_stats,
             running: running,  //<-- this is used to indicate whether the program should exit or not
 
         }
@@ -44,29 +58,22 @@ impl CamOrganizer{
             loop_helper.loop_sleep();
             let delta = loop_helper.loop_start();
             if let Some(fps) = loop_helper.report_rate() {
-                //println!("FPS: {}", fps);
-            }
-            let render_ops: Vec<RenderChunk> = Vec::with_capacity(10);
-            let cell = Rc::new(RefCell::new(render_ops));
-            let lock = self.game_objects.as_ref().read().await;
-            let mut futures_vec = Vec::new();
-            for obj in lock.iter(){
-            
-                let fut =  Self::process_object(obj, cell.clone()) ;
-                futures_vec.push(fut);
-            }
-
-            let fut = self.compute_camera(delta);
-
-            
-            let vec_join = join_all(futures_vec);
-            let (_ , cam_state) = futures::join!(vec_join, fut);
-            drop(lock);
-
-            let res = self.sender.send((Rc::try_unwrap(cell).unwrap().into_inner(), cam_state));
-            if let Err(e) = res{    //TODO, prepare next frame before awaiting a send for the current one
-                println!("Could not send rendering info to renderer thread: {}", e);
+                self.debug_stats.set_organizer_fps(fps);
             }
+            let (tick, progress) = self.tick_clock.progress();
+            let snapshot = self.render_snapshot.load();
+            //the model ticks the clock right before it publishes the snapshot, so for a moment they can disagree
+            let progress = match snapshot.tick.cmp(&tick){
+                Ordering::Equal => progress,
+                Ordering::Less => 1.0,          //the new snapshot is not published yet, the old one is drawn where it ended
+                Ordering::Greater => 0.0,       //it was published after the clock was read
+            };
+            let render_ops = Self::collect_render_chunks(&snapshot, Some(progress));
+            let glyphs = Self::collect_glyphs(&snapshot, Some(progress));
+            let cam_state = self.compute_camera(delta).await;
+
+            //never waits for the renderer, if it didn't take the last frame yet this one replaces it
+            self.frame_mailbox.publish((render_ops, glyphs, cam_state));
             
         }
 
@@ -77,33 +84,52 @@ impl CamOrganizer{
     }
 
 
-#[inline(always)]
-    async fn process_object(obj: &Arc<RwLock<dyn DrawableObject + Send + Sync>>, render_ops: Rc<RefCell<Vec<RenderChunk>>>){
-        let obj_lock = obj.read().await;
-                let texture_id = *obj_lock.get_texture() as u32;
-                let position = obj_lock.get_position();
-            
-                let vertex_configration = obj_lock.get_vertex_configuration();
-                let mut borrow = render_ops.borrow_mut();
-                let already_queued = borrow.iter_mut().find(|chunk| chunk.vertex_conf as u32 == *vertex_configration as u32);
-                if let Some(queue) = already_queued{
-                    queue.instance_buffer.push(SpriteInstance {
-                        position: [position.x, position.y],
-                        texture_id: texture_id,
-                    });
-                }else{
-                    let instance_buffer = 
-                        vec![SpriteInstance {
-                            position: [position.x, position.y],
-                            texture_id: texture_id,
-                        }];
-                    let render_chunk = RenderChunk{
-                        vertex_conf: *vertex_configration,
-                        instance_buffer: instance_buffer,   //this is because a sprite consists of 2 triangles at the moment. If this changes and can be dynamically set, this should be updated
-                    };
-                    borrow.push(render_chunk);
-                }
+    //groups the sprites of all visible objects by their vertex configuration, also used to render levels without a window (see capture.rs), which don't interpolate
+    pub(crate) fn collect_render_chunks(snapshot: &RenderSnapshot, tick_progress: Option<f32>) -> Vec<RenderChunk>{
+        let mut render_ops: Vec<RenderChunk> = Vec::with_capacity(10);
+        for item in snapshot.items.iter(){
+            Self::process_item(item, &mut render_ops, tick_progress);
+        }
+        //the depth buffer decides between chunks, but within a chunk the instances are sorted back to front, so semi transparent edges blend with what is behind them
+        //the sort is stable, objects with the same depth keep the order in which they were queued
+        for chunk in render_ops.iter_mut(){
+            chunk.instance_buffer.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+        }
+        render_ops
+    }
+
+    //lays out the glyphs of all visible texts, world space texts are interpolated like sprites
+    pub(crate) fn collect_glyphs(snapshot: &RenderSnapshot, tick_progress: Option<f32>) -> Vec<GlyphInstance>{
+        let mut glyphs = Vec::new();
+        for item in snapshot.texts.iter(){
+            let position = match tick_progress{
+                Some(progress) => interpolate(item.previous_position, item.position, progress),
+                None => item.position,
+            };
+            layout_text(&item.text, [position.x, position.y], &mut glyphs);
+        }
+        glyphs
+    }
 
+#[inline(always)]
+    fn process_item(item: &RenderItem, render_ops: &mut Vec<RenderChunk>, tick_progress: Option<f32>){
+        let position = match tick_progress{
+            Some(progress) => interpolate(item.previous_position, item.position, progress),
+            None => item.position,
+        };
+        let depth = item.layer.depth(position.y);
+        let instance = SpriteInstance::new([position.x, position.y], item.texture_id, depth, item.frame, &item.transform);
+
+        let already_queued = render_ops.iter_mut().find(|chunk| chunk.vertex_conf == item.vertex_conf);
+        if let Some(queue) = already_queued{
+            queue.instance_buffer.push(instance);
+        }else{
+            let render_chunk = RenderChunk{
+                vertex_conf: item.vertex_conf,
+                instance_buffer: vec![instance],   //this is because a sprite consists of 2 triangles at the moment. If this changes and can be dynamically set, this should be updated
+            };
+            render_ops.push(render_chunk);
+        }
     }
 
 
@@ -0,0 +1,47 @@
+use std::{sync::{Mutex, Condvar}, time::Duration};
+
+use crate::rendering::{wgpurenderer::RenderChunk, text::layout::GlyphInstance};
+
+use super::cam_organizer::CamState;
+
+pub(crate) type Frame = (Vec<RenderChunk>, Vec<GlyphInstance>, CamState);
+
+//hands the frames from the CamOrganizer to the renderer without making either of them wait for the other. The organizer builds one frame while the renderer draws another
+//and the newest finished one waits in between (triple buffering). Frames the renderer was too slow for are dropped, so it always draws the latest state
+#[derive(Debug)]
+pub(crate) struct FrameMailbox{
+    latest: Mutex<Option<Frame>>,
+    published: Condvar,
+    dropped_frames: Mutex<u64>,
+}
+
+impl FrameMailbox{
+    pub(crate) fn new() -> Self{
+        Self{
+            latest: Mutex::new(None),
+            published: Condvar::new(),
+            dropped_frames: Mutex::new(0),
+        }
+    }
+
+    //replaces the frame that is waiting, if the renderer didn't take it yet
+    pub(crate) fn publish(&self, frame: Frame){
+        let replaced = self.latest.lock().unwrap().replace(frame);
+        if replaced.is_some(){
+            *self.dropped_frames.lock().unwrap() += 1;
+        }
+        self.published.notify_one();
+    }
+
+    //the newest frame that wasn't taken yet. Waits at most timeout for one, so the renderer doesn't hang once the organizer has stopped
+    pub(crate) fn take_latest(&self, timeout: Duration) -> Option<Frame>{
+        let latest = self.latest.lock().unwrap();
+        let (mut latest, _) = self.published.wait_timeout_while(latest, timeout, |latest| latest.is_none()).unwrap();
+        latest.take()
+    }
+
+    //how many frames were replaced before the renderer took them
+    pub(crate) fn dropped_frames(&self) -> u64{
+        *self.dropped_frames.lock().unwrap()
+    }
+}
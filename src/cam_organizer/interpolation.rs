@@ -0,0 +1,37 @@
+use crate::controller::position::Position;
+
+//objects that move further than this in one tick were placed somewhere else (spawned, teleported) and jump instead of sliding across the map
+const MAX_INTERPOLATED_DISTANCE: f32 = 5.0;
+
+//the model moves objects 60 times a second, the frames are built up to 144 times a second. The frames in between two ticks show moving objects on the way
+//from where they were at the previous tick to where they are now, which costs one tick of latency. progress is the one of TickClock::progress
+pub(crate) fn interpolate(previous: Position, current: Position, progress: f32) -> Position{
+    let (dx, dy) = (current.x - previous.x, current.y - previous.y);
+    if dx * dx + dy * dy > MAX_INTERPOLATED_DISTANCE * MAX_INTERPOLATED_DISTANCE{
+        return current;
+    }
+    Position::new(previous.x + dx * progress, previous.y + dy * progress)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn xy(position: Position) -> (f32, f32){
+        (position.x, position.y)
+    }
+
+    #[test]
+    fn moves_from_the_previous_to_the_current_position(){
+        let (previous, current) = (Position::new(1.0, 2.0), Position::new(3.0, 1.0));
+        assert_eq!(xy(interpolate(previous, current, 0.0)), (1.0, 2.0));
+        assert_eq!(xy(interpolate(previous, current, 0.5)), (2.0, 1.5));
+        assert_eq!(xy(interpolate(previous, current, 1.0)), (3.0, 1.0));
+    }
+
+    #[test]
+    fn objects_that_were_placed_somewhere_else_jump(){
+        let (previous, current) = (Position::new(0.0, 0.0), Position::new(MAX_INTERPOLATED_DISTANCE, 1.0));
+        assert_eq!(xy(interpolate(previous, current, 0.25)), (MAX_INTERPOLATED_DISTANCE, 1.0));
+    }
+}
@@ -1 +1,3 @@
-pub(crate) mod cam_organizer;
\ No newline at end of file
+pub(crate) mod cam_organizer;
+pub(crate) mod frame_mailbox;
+pub(crate) mod interpolation;
@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use crate::model::{load_level_functions::{Level, ALL_LEVELS}, level_file::level_path, time_scale::TimeScale};
+
+use super::controller_commands::{ControllerCommand, LevelCommand, Selection};
+
+//every command the console knows, with its arguments. The names are also what tab completes
+pub(crate) const CONSOLE_COMMANDS: [(&str, &str); 13] = [
+    ("help", ""),
+    ("clear", ""),
+    ("spawn", "house <x> <y>"),
+    ("load", "<level or level file>"),
+    ("save", "<file.json>"),
+    ("select", "all|none"),
+    ("script", "<file.rhai>"),
+    ("control", "<faction> <file.rhai>"),
+    ("timescale", "<0.25 to 8>|max"),
+    ("pause", ""),
+    ("resume", ""),
+    ("step", ""),
+    ("quit", ""),
+];
+
+//what a line of the console asks for. Most of it goes straight to the model, the rest changes state the controller keeps track of
+pub(crate) enum ConsoleAction{
+    Model(ControllerCommand),
+    LoadLevel(Level),
+    LoadLevelFile(PathBuf),
+    SetPaused(bool),
+    StepTick,
+    SetTimeScale(TimeScale),
+    Help,
+    Clear,
+    Quit,
+}
+
+//the error is shown in the console as it is
+pub(crate) fn parse_console_command(line: &str) -> Result<ConsoleAction, String>{
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else{
+        return Err("empty command".to_string());
+    };
+    let arguments: Vec<&str> = words.collect();
+    let action = match (name, arguments.as_slice()){
+        ("help", []) => ConsoleAction::Help,
+        ("clear", []) => ConsoleAction::Clear,
+        ("spawn", ["house", x, y]) => ConsoleAction::Model(ControllerCommand::SpawnHouseAtPosition { spawn_position: (parse_number(x)?, parse_number(y)?) }),
+        ("load", [level]) => parse_level(level)?,
+        ("save", [file]) => ConsoleAction::Model(ControllerCommand::SaveLevel(level_path(file, "json"))),
+        ("select", ["all"]) => ConsoleAction::Model(ControllerCommand::Select(Selection::All)),
+        ("select", ["none"]) => ConsoleAction::Model(ControllerCommand::Select(Selection::None)),
+        ("script", [file]) => ConsoleAction::Model(ControllerCommand::RunScript(level_path(file, "rhai"))),
+        ("control", [faction, file]) => {
+            let faction = faction.parse().map_err(|_| format!("\"{}\" is not a faction", faction))?;
+            ConsoleAction::Model(ControllerCommand::Level(LevelCommand::ControlFaction { faction, script: level_path(file, "rhai") }))
+        }
+        ("timescale", ["max"]) => ConsoleAction::SetTimeScale(TimeScale::Unlimited),
+        ("timescale", [scale]) => {
+            let scale = parse_number(scale)?;
+            let time_scale = TimeScale::clamped(scale).ok_or_else(|| "the time scale has to be a number above 0, use pause to stop the simulation or max to run it as fast as possible".to_string())?;
+            ConsoleAction::SetTimeScale(time_scale)
+        }
+        ("pause", []) => ConsoleAction::SetPaused(true),
+        ("resume", []) => ConsoleAction::SetPaused(false),
+        ("step", []) => ConsoleAction::StepTick,
+        ("quit", []) => ConsoleAction::Quit,
+        _ => return Err(match CONSOLE_COMMANDS.iter().find(|(command, _)| *command == name){
+            Some((command, usage)) => format!("usage: {} {}", command, usage),
+            None => format!("unknown command \"{}\", type help for a list of commands", name),
+        }),
+    };
+    Ok(action)
+}
+
+pub(crate) fn console_help() -> Vec<String>{
+    CONSOLE_COMMANDS.iter().map(|(name, usage)| format!("{} {}", name, usage)).collect()
+}
+
+fn parse_number(word: &str) -> Result<f32, String>{
+    word.parse().map_err(|_| format!("\"{}\" is not a number", word))
+}
+
+//the built in levels by their command line name first, then the files in the level directory
+fn parse_level(name: &str) -> Result<ConsoleAction, String>{
+    if let Some(level) = Level::from_name(name){
+        return Ok(ConsoleAction::LoadLevel(level));
+    }
+    let path = level_path(name, "json");
+    if path.exists(){
+        return Ok(ConsoleAction::LoadLevelFile(path));
+    }
+    let levels: Vec<&str> = ALL_LEVELS.iter().map(|level| level.name()).collect();
+    Err(format!("there is no level \"{}\", the built in ones are {}", name, levels.join(", ")))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn time_scale(line: &str) -> Result<TimeScale, String>{
+        match parse_console_command(line)?{
+            ConsoleAction::SetTimeScale(time_scale) => Ok(time_scale),
+            _ => panic!("{} is not a time scale command", line),
+        }
+    }
+
+    #[test]
+    fn parses_commands_with_their_arguments(){
+        assert!(matches!(parse_console_command("  pause "), Ok(ConsoleAction::SetPaused(true))));
+        assert!(matches!(parse_console_command("step"), Ok(ConsoleAction::StepTick)));
+        assert!(matches!(parse_console_command("load maze"), Ok(ConsoleAction::LoadLevel(Level::Maze))));
+        match parse_console_command("spawn house 1.5 -2"){
+            Ok(ConsoleAction::Model(ControllerCommand::SpawnHouseAtPosition { spawn_position })) => assert_eq!(spawn_position, (1.5, -2.0)),
+            _ => panic!("spawn house was not parsed"),
+        }
+    }
+
+    #[test]
+    fn reports_usage_and_unknown_commands(){
+        assert_eq!(parse_console_command("").err(), Some("empty command".to_string()));
+        assert_eq!(parse_console_command("spawn tower 1 2").err(), Some("usage: spawn house <x> <y>".to_string()));
+        assert!(parse_console_command("fly").err().unwrap().starts_with("unknown command \"fly\""));
+        assert!(parse_console_command("spawn house x 2").err().unwrap().contains("is not a number"));
+    }
+
+    #[test]
+    fn time_scales_are_finite_and_clamped(){
+        assert_eq!(time_scale("timescale 2"), Ok(TimeScale::Scaled(2.0)));
+        assert_eq!(time_scale("timescale 0.01"), Ok(TimeScale::Scaled(0.25)));
+        assert_eq!(time_scale("timescale 1e30"), Ok(TimeScale::Scaled(8.0)));
+        assert_eq!(time_scale("timescale max"), Ok(TimeScale::Unlimited));
+        for scale in ["nan", "inf", "-inf", "0", "-1"]{
+            assert!(time_scale(&format!("timescale {}", scale)).is_err(), "{} was accepted", scale);
+        }
+    }
+}
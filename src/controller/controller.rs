@@ -4,9 +4,15 @@ use tokio::sync::RwLock;
 use flume::{Receiver, Sender};
 use winit::event::{VirtualKeyCode, ElementState};
 use winit::window::Window;
-use crate::controller::input::MouseInputType;
+use crate::controller::input::{MouseInputType, BaseProduction};
 use crate::model::load_level_functions::Level;
-use super::controller_commands::ControllerCommand;
+use crate::game_objects::buildings::start_obj::UnitType;
+use crate::model::strategy_test::faction::PLAYER_FACTION;
+use crate::model::time_scale::TimeScale;
+use crate::rendering::ui_layer::{SharedUiFrame, new_shared_ui_frame};
+use crate::ui::{context::Ui, hud::build_hud, menu::{Menu, MenuAction}, console::Console};
+use super::console_commands::{parse_console_command, console_help, ConsoleAction};
+use super::controller_commands::{ControllerCommand, LevelCommand};
 use super::input::ControllerInput;
 use super::position::Position;
 use super::renderer_commands::RendererCommand;
@@ -31,6 +37,15 @@ pub(crate) struct Controller{
                                              //for example: self.model_sender.send(ControllerCommand::SpawnHouseAtPosition { spawn_position: (0.0, 0.0) }).unwrap();
     renderer_sender: Sender<RendererCommand>,
     modifiers: Modifiers,
+    ui: Ui,
+    pub(crate) ui_frame: SharedUiFrame,     //the quads of the last built UI, the renderer draws them
+    menu: Menu,
+    console: Console,
+    current_level: Option<Level>,       //None while a level from a file is loaded
+    base_production: BaseProduction,
+    paused: bool,           //paused by the player, the model is also paused while a menu is open
+    model_paused: bool,     //what was sent to the model last
+    time_scale: TimeScale,
 }
 
 pub(crate) enum Modifier{
@@ -83,12 +98,22 @@ impl Controller{
             cam_directions: Arc::new(RwLock::new((Direction::None, Direction::None))),
             modifiers: Modifiers::new(),
             personal_running_bool: true,
+            ui: Ui::new(),
+            ui_frame: new_shared_ui_frame(),
+            menu: Menu::new(),
+            console: Console::new(),
+            current_level: None,
+            base_production: BaseProduction::default(),
+            paused: false,
+            model_paused: false,
+            time_scale: TimeScale::NORMAL,
         }
     }
 
 
     pub(crate) async fn run(&mut self){
-        self.model_sender.send(ControllerCommand::LoadLevel(Level::StrategyTest)).unwrap();   //here we define what level should start. We use the controller for that, just because it is supposed to later decide what level to load anyways
+        self.load_level(Level::Initial).await;   //the game starts in the main menu, the initial level is only the background until the player picks one
+        self.update_model_pause();
 
 
         while self.personal_running_bool{
@@ -100,13 +125,140 @@ impl Controller{
                 }
                 ControllerInput::MouseInput { action } =>  self.handle_mouse_input(action),
                 ControllerInput::KeyboardInput { key, state } =>  self.handle_keyboard_input(key, state).await,
-                ControllerInput::WindowResized { dimensions } =>    println!("Got window resized!"),
+                ControllerInput::WindowResized { dimensions } =>    self.ui.set_screen_size(dimensions),
+                ControllerInput::Character(character) =>    self.console.type_character(character),
+                ControllerInput::ConsoleOutput(line) =>    self.console.print(line),
+                ControllerInput::BaseProduction(production) =>    self.base_production = production,
             }
+            //the UI only changes through input, so it is rebuilt after every one
+            self.update_ui().await;
         }
     }
 
+    async fn update_ui(&mut self){
+        let screen = self.menu.current();
+        let (commands, actions) = self.build_ui();
+        //a click that switched to another menu has to show it right away, not only after the next input
+        if self.menu.current() != screen{
+            self.build_ui();
+        }
+        for command in commands{
+            self.model_sender.send(command).unwrap();
+        }
+        for action in actions{
+            self.handle_menu_action(action).await;
+        }
+        self.update_model_pause();
+    }
+
+    //the simulation stands still while the player paused it or looks at a menu
+    fn update_model_pause(&mut self){
+        let paused = self.paused || self.menu.is_open();
+        if paused != self.model_paused{
+            self.model_paused = paused;
+            self.model_sender.send(ControllerCommand::SetPaused(paused)).unwrap();
+        }
+    }
+
+    fn set_time_scale(&mut self, time_scale: TimeScale){
+        self.time_scale = time_scale;
+        self.model_sender.send(ControllerCommand::SetTimeScale(time_scale)).unwrap();
+    }
+
+    fn build_ui(&mut self) -> (Vec<ControllerCommand>, Vec<MenuAction>){
+        self.ui.begin_frame();
+        let commands = if self.menu.is_open() { Vec::new() } else { build_hud(&mut self.ui, self.current_level, &self.base_production) };
+        let actions = self.menu.build(&mut self.ui);
+        self.console.build(&mut self.ui);
+        self.ui_frame.store(std::sync::Arc::new(self.ui.end_frame()));
+        (commands, actions)
+    }
+
+    async fn handle_menu_action(&mut self, action: MenuAction){
+        match action{
+            MenuAction::LoadLevel(level) => self.load_level(level).await,
+            MenuAction::LoadLevelFile(path) => {
+                self.current_level = None;
+                *self.cam_position.write().await = Position::new(0.0, 0.0);
+                self.model_sender.send(ControllerCommand::LoadLevelFile(path)).unwrap();
+            }
+            MenuAction::ToggleFullscreen => self.renderer_sender.send(RendererCommand::TOGGLE_FULLSCREEN).unwrap(),
+            MenuAction::ToggleDebugOverlay => self.renderer_sender.send(RendererCommand::TOGGLE_DEBUG_OVERLAY).unwrap(),
+            MenuAction::Quit => {
+                self.model_sender.send(ControllerCommand::Shutdown).unwrap();
+                self.renderer_sender.send(RendererCommand::SHUTDOWN).unwrap();
+            }
+        }
+    }
+
+    async fn run_console_line(&mut self, line: &str){
+        let action = match parse_console_command(line){
+            Ok(action) => action,
+            Err(error) => {
+                self.console.print(error);
+                return;
+            }
+        };
+        match action{
+            ConsoleAction::Model(command) => self.model_sender.send(command).unwrap(),
+            ConsoleAction::LoadLevel(level) => {
+                self.menu.close();
+                self.load_level(level).await;
+            }
+            ConsoleAction::LoadLevelFile(path) => {
+                self.menu.close();
+                self.handle_menu_action(MenuAction::LoadLevelFile(path)).await;
+            }
+            ConsoleAction::SetPaused(paused) => self.paused = paused,
+            ConsoleAction::StepTick => self.step_tick(),
+            ConsoleAction::SetTimeScale(time_scale) => {
+                self.set_time_scale(time_scale);
+                self.console.print(format!("time scale: {}", time_scale));
+            }
+            ConsoleAction::Help => console_help().into_iter().for_each(|line| self.console.print(line)),
+            ConsoleAction::Clear => self.console.clear(),
+            ConsoleAction::Quit => self.handle_menu_action(MenuAction::Quit).await,
+        }
+    }
+
+    //one tick forward, stepping pauses the simulation first
+    fn step_tick(&mut self){
+        self.paused = true;
+        self.update_model_pause();
+        self.model_sender.send(ControllerCommand::StepTick).unwrap();
+    }
+
+    //while the console is open it gets the pressed keys, only releases go on, so nothing stays held down
+    async fn handle_console_key(&mut self, key: VirtualKeyCode){
+        match key{
+            VirtualKeyCode::Grave | VirtualKeyCode::Escape => self.console.close(),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                if let Some(line) = self.console.submit(){
+                    self.run_console_line(&line).await;
+                }
+            }
+            VirtualKeyCode::Back => self.console.delete_character(),
+            VirtualKeyCode::Up => self.console.history_previous(),
+            VirtualKeyCode::Down => self.console.history_next(),
+            VirtualKeyCode::Tab => self.console.complete(),
+            _ => {}
+        }
+    }
+
+    //every level starts with the camera in the middle of the map
+    async fn load_level(&mut self, level: Level){
+        self.current_level = Some(level);
+        self.base_production = BaseProduction::default();
+        *self.cam_position.write().await = Position::new(0.0, 0.0);
+        self.model_sender.send(ControllerCommand::LoadLevel(level)).unwrap();
+    }
+
 
     pub(crate) fn handle_mouse_input(&mut self, buttons: MouseInputType) {
+        //the UI is on top of the world, clicks on it never reach the world picking below
+        if self.ui.handle_mouse_input(&buttons){
+            return;
+        }
         return;
         match buttons {
             MouseInputType::Move(x, y) => todo!(),
@@ -120,6 +272,11 @@ impl Controller{
 
     pub(crate) async fn handle_keyboard_input(&mut self, input: Option<VirtualKeyCode>, state: ElementState) {
         if let Some(key) = input {
+            let passes_console = matches!(key, VirtualKeyCode::LAlt | VirtualKeyCode::RAlt | VirtualKeyCode::LControl | VirtualKeyCode::RControl | VirtualKeyCode::LShift | VirtualKeyCode::RShift | VirtualKeyCode::F3 | VirtualKeyCode::F12);
+            if self.console.is_open() && state == ElementState::Pressed && !passes_console{
+                self.handle_console_key(key).await;
+                return;
+            }
             match key {
                 VirtualKeyCode::Up => {
                     // Verarbeitung für Pfeiltaste nach oben
@@ -194,10 +351,9 @@ impl Controller{
                         },
                     };
                 }
-                VirtualKeyCode::Escape => {
-                    // Verarbeitung für Escape-Taste
-                    self.model_sender.send(ControllerCommand::Shutdown).unwrap();
-                    self.renderer_sender.send(RendererCommand::SHUTDOWN).unwrap();
+                VirtualKeyCode::Escape if state == ElementState::Pressed => {
+                    // opens the pause menu or goes back one menu, quitting is done with the Quit button
+                    self.menu.escape();
                 }
                 VirtualKeyCode::Return => {
                     // Verarbeitung für Enter-Taste
@@ -210,15 +366,48 @@ impl Controller{
                         _ => ()
                     }                 
                 }
+                VirtualKeyCode::Q if state == ElementState::Pressed => {
+                    // queue a worker in the first base of the player faction
+                    self.model_sender.send(ControllerCommand::Level(LevelCommand::QueueUnit { faction: PLAYER_FACTION, base_index: 0, unit: UnitType::Worker })).unwrap();
+                }
+                VirtualKeyCode::X if state == ElementState::Pressed => {
+                    // cancel the last queued unit in the first base of the player faction
+                    self.model_sender.send(ControllerCommand::Level(LevelCommand::CancelUnit { faction: PLAYER_FACTION, base_index: 0 })).unwrap();
+                }
+                VirtualKeyCode::Space if state == ElementState::Pressed => {
+                    self.paused = !self.paused;
+                }
+                VirtualKeyCode::Period if state == ElementState::Pressed => {
+                    self.step_tick();
+                }
+                VirtualKeyCode::Plus | VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd if state == ElementState::Pressed => {
+                    self.set_time_scale(self.time_scale.faster());
+                }
+                VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract if state == ElementState::Pressed => {
+                    self.set_time_scale(self.time_scale.slower());
+                }
+                VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 if state == ElementState::Pressed => {
+                    self.set_time_scale(TimeScale::NORMAL);
+                }
+                VirtualKeyCode::Grave if state == ElementState::Pressed => {
+                    // the developer console, see controller/console_commands.rs for what it understands
+                    self.console.toggle();
+                }
+                VirtualKeyCode::F3 if state == ElementState::Pressed => {
+                    self.renderer_sender.send(RendererCommand::TOGGLE_DEBUG_OVERLAY).unwrap();
+                }
+                VirtualKeyCode::F12 if state == ElementState::Pressed => {
+                    self.renderer_sender.send(RendererCommand::Screenshot).unwrap();
+                }
                 VirtualKeyCode::LAlt => {
                     // Verarbeitung für Alt-Taste
                     self.modifiers.set_modifier(Modifier::Alt, state == ElementState::Pressed);
                 }
-                VirtualKeyCode::LControl => {
+                VirtualKeyCode::LControl | VirtualKeyCode::RControl => {
                     // Verarbeitung für Strg-Taste
                     self.modifiers.set_modifier(Modifier::Ctrl, state == ElementState::Pressed);
                 }
-                VirtualKeyCode::LShift => {
+                VirtualKeyCode::LShift | VirtualKeyCode::RShift => {
                     // Verarbeitung für Shift-Taste
                     self.modifiers.set_modifier(Modifier::Shift, state == ElementState::Pressed);
                 }
@@ -1,4 +1,6 @@
-use crate::model::load_level_functions::Level;
+use std::path::PathBuf;
+
+use crate::{model::{load_level_functions::Level, time_scale::TimeScale, strategy_test::faction::FactionId}, game_objects::buildings::start_obj::UnitType};
 
 use super::position::Position;
 
@@ -9,5 +11,24 @@ pub(crate) enum ControllerCommand{
 
 
     LoadLevel(Level),
+    LoadLevelFile(PathBuf),     //a level defined in a json file, see model/level_file.rs
+    Select(Selection),
+    SaveLevel(PathBuf),     //writes the visible objects into a level file, see model/level_file.rs
+    RunScript(PathBuf),     //adds a ScriptedLogicObject to the current level, see model/scripting
+    SetPaused(bool),
+    StepTick,       //advances the model by one tick while it is paused
+    SetTimeScale(TimeScale),
+    Level(LevelCommand),    //these are handed to every logic object of the current level, the ones that don't know what to do with it just ignore it
     Shutdown,
+}
+
+pub(crate) enum Selection{
+    All,
+    None,
+}
+
+pub(crate) enum LevelCommand{
+    QueueUnit{ faction: FactionId, base_index: usize, unit: UnitType },
+    CancelUnit{ faction: FactionId, base_index: usize },
+    ControlFaction{ faction: FactionId, script: PathBuf },     //the faction is driven by a ScriptedController from now on
 }
\ No newline at end of file
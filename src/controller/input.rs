@@ -1,13 +1,23 @@
 use winit::event::{VirtualKeyCode, ElementState, MouseButton, MouseScrollDelta, TouchPhase};
 //modifiers: ctrl, shift, alt, represented with binary OR -> 00000111
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum ControllerInput{
     MouseInput{  action: MouseInputType},
     KeyboardInput{ key: Option<VirtualKeyCode>, state : ElementState },  
+    Character(char),                //text input, only used while the console is open
+    ConsoleOutput(String),          //what the model answers to commands from the console
+    BaseProduction(BaseProduction), //the production queue of the first base of the player, shown in the HUD
     WindowResized{ dimensions : (u32,u32) },
     Exit
 }
 
+//only sent when it changes, the progress is rounded to whole percents for that
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct BaseProduction{
+    pub(crate) queued: usize,
+    pub(crate) progress: f32,       //of the unit in front of the queue, from 0.0 to 1.0
+}
+
 #[derive(Clone, Copy)]
 pub enum MouseInputType{
     Move(f32, f32),
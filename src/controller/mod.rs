@@ -2,4 +2,5 @@ pub mod input;
 pub mod controller;
 pub mod position;
 pub mod controller_commands;
-pub mod renderer_commands;
\ No newline at end of file
+pub mod renderer_commands;
+pub mod console_commands;
\ No newline at end of file
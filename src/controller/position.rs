@@ -1,4 +1,4 @@
-use std::{sync::{Arc}, collections::HashMap};
+use std::{sync::{Arc}, collections::{HashMap, HashSet}};
 
 
 use tokio::sync::RwLock;
@@ -13,9 +13,12 @@ pub struct Position {
 
 pub struct PathTrack{
     pub path: Vec<(f32, f32)>,
-    pub previously_visited: Vec<(f32, f32)>,
+    pub previously_visited: HashSet<(i32, i32)>,     //dead ends, the search must not walk into them again or it goes back and forth forever
 }
 
+//a path that is not found after this many steps is given up, the search is not bounded by the level and would never end if the goal can't be reached
+const MAX_PATH_SEARCH_STEPS: usize = 20_000;
+
 struct PathBuildingInformation{
     optimal_path_length: f32,
     optimal_path: Vec<(f32, f32)>,
@@ -51,8 +54,13 @@ pub fn find_all_eligible_neighbors(path_track: &PathTrack, blockers: &Vec<Box<dy
     ret
 }
 
+//the key of a tile in previously_visited, floored like the tiles of the path. A plain cast would round negative tiles towards zero
+fn tile_key(tile: &(f32, f32)) -> (i32, i32){
+    (tile.0.floor() as i32, tile.1.floor() as i32)
+}
+
 fn is_eligible(tile: &(f32, f32), blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<RwLock<dyn VisitableStructure>>>, path: &PathTrack) -> bool{
-    if path.path.contains(tile){
+    if path.path.contains(tile) || path.previously_visited.contains(&tile_key(tile)){
         return false;
     }
     for blocker in blockers{
@@ -127,12 +135,12 @@ impl Position {
     pub fn find_path_to(&self, other: &(f32, f32), blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<RwLock<dyn VisitableStructure>>>) -> Option<Vec<(f32, f32)>>{
         let mut path_track = PathTrack{
             path: Vec::new(),
-            previously_visited: Vec::new(),
+            previously_visited: HashSet::new(),
         };
         path_track.path.push(*other);
         path_track.path.push((other.0.floor(), other.1.floor()));
         let mut ret = None;
-        loop  {
+        for _ in 0..MAX_PATH_SEARCH_STEPS{
             if path_track.path.len() == 0{
                 break;
                 
@@ -146,7 +154,8 @@ impl Position {
             
             let eligible_neighbors = find_all_eligible_neighbors(&path_track, blockers, structures);
             if eligible_neighbors.len() == 0{
-                path_track.previously_visited.push(path_track.path.pop().unwrap());
+                let dead_end = path_track.path.pop().unwrap();
+                path_track.previously_visited.insert(tile_key(&dead_end));
                 continue;
             }
             let next_step = find_smallest_dist(&(self.x, self.y), &eligible_neighbors);
@@ -219,4 +228,15 @@ impl Line{
     }
 
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn negative_tiles_have_the_same_key_wherever_they_are_looked_up(){
+        assert_eq!(tile_key(&(-0.5, -1.5)), (-1, -2));
+        assert_eq!(tile_key(&(-1.0, 2.5)), (-1, 2));
+    }
+}
@@ -5,5 +5,7 @@
 pub(crate) enum RendererCommand{
     TOGGLE_FULLSCREEN,
     SHUTDOWN,
+    Screenshot,     //writes the next frame to screenshots/
+    TOGGLE_DEBUG_OVERLAY,
     PLACEHOLDER
 }
\ No newline at end of file
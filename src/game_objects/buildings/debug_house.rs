@@ -1,11 +1,12 @@
 use log::Log;
 
-use crate::{game_objects::game_object::{self, DrawableObject}, controller::position::Position, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, vertex::Vertex}, model::results::LogicResult};
+use crate::{game_objects::game_object::DrawableObject, controller::position::Position, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, depth::RenderLayer}};
 #[derive(Debug)]
 pub(crate) struct DebugHouse{
     pub(crate) texture: Sprite,
     pub position: Position,
     pub vertices: VertexConfigration,
+    pub(crate) layer: RenderLayer,
     id : u64,
 }
 
@@ -16,9 +17,15 @@ impl DebugHouse {
             texture,
             position,
             vertices,
+            layer: RenderLayer::Structures,
             id: 0,
         }
     }
+
+    pub(crate) fn with_layer(mut self, layer: RenderLayer) -> Self{
+        self.layer = layer;
+        self
+    }
 }
 
 impl DrawableObject for DebugHouse{
@@ -53,6 +60,10 @@ impl DrawableObject for DebugHouse{
         self.id = id;
     }
 
+    fn get_layer(&self) -> RenderLayer {
+        self.layer
+    }
+
     
 
 
@@ -1,8 +1,8 @@
-use std::sync::Arc;
+use std::{sync::Arc, collections::VecDeque};
 
 use tokio::sync::RwLock;
 
-use crate::{rendering::sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, controller::position::Position, game_objects::game_object::{DrawableObject, VisitableStructure}, model::{results::LogicResult, strategy_test::{strategy_test::StrategyLogicObject, map_chunk::{MapChunk, ChunkInfo}}}};
+use crate::{rendering::sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, controller::position::Position, game_objects::game_object::{DrawableObject, VisitableStructure}, model::{results::LogicResult, strategy_test::{strategy_test::StrategyLogicObject, map_chunk::{MapChunk, ChunkInfo}, faction::FactionId}}};
 
 #[derive(Debug)]
 pub(crate) struct StartObj{
@@ -12,17 +12,75 @@ pub(crate) struct StartObj{
     pub wares: Vec<WareAmount>,
     size: (f32, f32),
     associated_units: Vec<u64>,
+    production_queue: VecDeque<ProductionItem>,
+    finished_units: Vec<UnitType>,     //the StratLevel picks these up and creates the actual units, since only it knows where to put them
+    faction: FactionId,
+    hidden: bool,
     id : u64,
+    game_object_id: u64,    //the model assigns its own ids to game objects, so we keep them apart from the id inside the StratLevel
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct WareAmount{
     pub(crate) ware_type: u32,
     pub(crate) amount: u32,
 }
 
+//the ware_type of a WareAmount is the WareType cast to u32, similar to how Sprites are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WareType{
+    Food,
+    Wood,
+    Stone,
+}
+
+pub(crate) const ALL_WARE_TYPES: [WareType; 3] = [WareType::Food, WareType::Wood, WareType::Stone];
+
+impl WareType{
+    //the name scripts use for the ware
+    pub(crate) fn name(&self) -> &'static str{
+        match self{
+            WareType::Food => "food",
+            WareType::Wood => "wood",
+            WareType::Stone => "stone",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<WareType>{
+        ALL_WARE_TYPES.iter().copied().find(|ware_type| ware_type.name() == name)
+    }
+}
+
+pub(crate) const MAX_PRODUCTION_QUEUE_LENGTH: usize = 10;
+const SIGHT_RADIUS: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnitType{
+    Worker,
+}
+
+#[derive(Debug)]
+struct ProductionItem{
+    unit: UnitType,
+    remaining_secs: f32,
+}
+
+impl UnitType{
+    pub(crate) fn costs(&self) -> &'static [WareAmount]{
+        match self{
+            UnitType::Worker => &[WareAmount{ ware_type: WareType::Food as u32, amount: 25 }],
+        }
+    }
+
+    pub(crate) fn build_time_secs(&self) -> f32{
+        match self{
+            UnitType::Worker => 8.0,
+        }
+    }
+}
+
 impl StartObj{
-    pub(crate) fn new(position: Position, id: u64) -> Self{
+    pub(crate) fn new(position: Position, faction: FactionId, id: u64) -> Self{
         Self{
             texture: Sprite::BaseBaseLarge,
             position,
@@ -30,12 +88,87 @@ impl StartObj{
             wares: Vec::new(),
             size: (4.2, 2.2),
             associated_units: Vec::new(),
+            production_queue: VecDeque::new(),
+            finished_units: Vec::new(),
+            faction,
+            hidden: false,
             id,
+            game_object_id: 0,
+        }
+    }
+
+    pub(crate) fn get_faction(&self) -> FactionId{
+        self.faction
+    }
+
+    pub(crate) fn get_sight_radius(&self) -> f32{
+        SIGHT_RADIUS
+    }
+
+    pub(crate) fn set_hidden(&mut self, hidden: bool){
+        self.hidden = hidden;
+    }
+
+    //the costs are paid when the unit is queued, returns false if the queue is full or the wares are missing
+    pub(crate) fn queue_unit(&mut self, unit: UnitType) -> bool{
+        if self.production_queue.len() >= MAX_PRODUCTION_QUEUE_LENGTH{
+            return false;
+        }
+        if !self.take_wares(unit.costs()){
+            return false;
+        }
+        self.production_queue.push_back(ProductionItem{ unit, remaining_secs: unit.build_time_secs() });
+        true
+    }
+
+    //removes the last queued unit and refunds its costs
+    pub(crate) fn cancel_last_unit(&mut self) -> Option<UnitType>{
+        let item = self.production_queue.pop_back()?;
+        for cost in item.unit.costs(){
+            if let Some(ware) = self.wares.iter_mut().find(|w| w.ware_type == cost.ware_type){
+                ware.amount += cost.amount;
+            }else{
+                self.wares.push(*cost);
+            }
+        }
+        Some(item.unit)
+    }
+
+    pub(crate) fn get_production_queue_length(&self) -> usize{
+        self.production_queue.len()
+    }
+
+    //how far the unit in front of the queue is, 0.0 if nothing is queued
+    pub(crate) fn get_production_progress(&self) -> f32{
+        self.production_queue.front().map_or(0.0, |item| 1.0 - item.remaining_secs / item.unit.build_time_secs())
+    }
+
+    pub(crate) fn take_finished_units(&mut self) -> Vec<UnitType>{
+        std::mem::take(&mut self.finished_units)
+    }
+
+    pub(crate) fn add_wares(&mut self, ware_type: WareType, amount: u32){
+        if let Some(ware) = self.wares.iter_mut().find(|w| w.ware_type == ware_type as u32){
+            ware.amount += amount;
+            return;
         }
+        self.wares.push(WareAmount{ ware_type: ware_type as u32, amount });
     }
 
-    pub(crate) fn get_entry_coords(&self) -> (f32, f32){
-        (self.position.x, self.position.y - self.size.1 / 2.0 )
+    //only takes the wares if all of them are available, returns whether it did
+    pub(crate) fn take_wares(&mut self, costs: &[WareAmount]) -> bool{
+        for cost in costs{
+            let available = self.wares.iter().find(|w| w.ware_type == cost.ware_type).map(|w| w.amount).unwrap_or(0);
+            if available < cost.amount{
+                return false;
+            }
+        }
+        for cost in costs{
+            if let Some(ware) = self.wares.iter_mut().find(|w| w.ware_type == cost.ware_type){
+                ware.amount -= cost.amount;
+            }
+        }
+        true
     }
 
     pub(crate) fn add_associated_unit(&mut self, id: u64){
@@ -48,8 +181,9 @@ impl StartObj{
 }
 
 impl VisitableStructure for StartObj{
+    //the middle of the tile right below the blocking chunk, units that stand inside of it could never find a way out again
     fn get_entry_point(&self) -> Position {
-        Position::new(self.position.x, self.position.y - self.size.1 / 2.0)
+        Position::new(self.position.x, self.get_blocking_chunk().bottom_left.y as f32 - 0.5)
     }
 
     fn get_blocking_chunk(&self) -> ChunkInfo {
@@ -60,6 +194,15 @@ impl VisitableStructure for StartObj{
 
 impl StrategyLogicObject for StartObj{
     fn process_logic(&mut self, delta_time: std::time::Duration, _blockers: &mut Vec<Box<dyn MapChunk>>, _structures: &mut Vec<Arc<RwLock<dyn VisitableStructure>>>) -> LogicResult {        
+        //only the first unit in the queue is being worked on
+        if let Some(item) = self.production_queue.front_mut(){
+            item.remaining_secs -= delta_time.as_secs_f32();
+            if item.remaining_secs <= 0.0{
+                let unit = item.unit;
+                self.production_queue.pop_front();
+                self.finished_units.push(unit);
+            }
+        }
         LogicResult::None
     }
 
@@ -73,7 +216,9 @@ impl StrategyLogicObject for StartObj{
     }
 
     fn initialize_behavior(&mut self, blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<tokio::sync::RwLock<dyn VisitableStructure>>>) {
-        println!("I don't know what to do yet!");
+        //a base doesn't move, everything it does is driven by its production queue, which starts out empty
+        self.production_queue.clear();
+        self.finished_units.clear();
     }
 
 }
@@ -104,14 +249,96 @@ impl DrawableObject for StartObj{
     }
 
     fn get_id(&self) -> u64 {
-        self.id
+        self.game_object_id
     }
 
     fn set_id(&mut self, id: u64) {
-        self.id = id;
+        self.game_object_id = id;
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.hidden
     }
 
     
 
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests{
+    use std::time::Duration;
+
+    use super::*;
+
+    fn base_with_food(amount: u32) -> StartObj{
+        let mut base = StartObj::new(Position::new(0.0, 0.0), 0, 0);
+        base.add_wares(WareType::Food, amount);
+        base
+    }
+
+    fn food(base: &StartObj) -> u32{
+        base.wares.iter().find(|w| w.ware_type == WareType::Food as u32).map(|w| w.amount).unwrap_or(0)
+    }
+
+    #[test]
+    fn queueing_pays_the_costs(){
+        let mut base = base_with_food(60);
+        assert!(base.queue_unit(UnitType::Worker));
+        assert!(base.queue_unit(UnitType::Worker));
+        assert_eq!(food(&base), 10);
+        assert!(!base.queue_unit(UnitType::Worker));
+        assert_eq!(food(&base), 10);
+        assert_eq!(base.get_production_queue_length(), 2);
+    }
+
+    #[test]
+    fn cancelling_refunds_the_last_unit(){
+        let mut base = base_with_food(50);
+        base.queue_unit(UnitType::Worker);
+        base.queue_unit(UnitType::Worker);
+        assert_eq!(food(&base), 0);
+        assert_eq!(base.cancel_last_unit(), Some(UnitType::Worker));
+        assert_eq!(food(&base), 25);
+        assert_eq!(base.cancel_last_unit(), Some(UnitType::Worker));
+        assert_eq!(food(&base), 50);
+        assert_eq!(base.cancel_last_unit(), None);
+        assert_eq!(food(&base), 50);
+    }
+
+    #[test]
+    fn the_queue_is_limited(){
+        let mut base = base_with_food(25 * (MAX_PRODUCTION_QUEUE_LENGTH as u32 + 1));
+        for _ in 0..MAX_PRODUCTION_QUEUE_LENGTH{
+            assert!(base.queue_unit(UnitType::Worker));
+        }
+        assert!(!base.queue_unit(UnitType::Worker));
+        assert_eq!(food(&base), 25);
+    }
+
+    #[test]
+    fn units_are_finished_one_after_another(){
+        let mut base = base_with_food(50);
+        base.queue_unit(UnitType::Worker);
+        base.queue_unit(UnitType::Worker);
+        let build_time = Duration::from_secs_f32(UnitType::Worker.build_time_secs());
+        base.process_logic(build_time, &mut Vec::new(), &mut Vec::new());
+        assert_eq!(base.take_finished_units(), vec![UnitType::Worker]);
+        assert_eq!(base.get_production_queue_length(), 1);
+        assert!(base.take_finished_units().is_empty());
+        base.process_logic(build_time, &mut Vec::new(), &mut Vec::new());
+        assert_eq!(base.take_finished_units(), vec![UnitType::Worker]);
+    }
+
+    #[test]
+    fn the_progress_is_the_one_of_the_first_unit(){
+        let mut base = base_with_food(50);
+        assert_eq!(base.get_production_progress(), 0.0);
+        base.queue_unit(UnitType::Worker);
+        base.queue_unit(UnitType::Worker);
+        let quarter = Duration::from_secs_f32(UnitType::Worker.build_time_secs() / 4.0);
+        base.process_logic(quarter, &mut Vec::new(), &mut Vec::new());
+        assert!((base.get_production_progress() - 0.25).abs() < 1e-4);
+        base.cancel_last_unit();
+        assert!((base.get_production_progress() - 0.25).abs() < 1e-4);
+    }
+}
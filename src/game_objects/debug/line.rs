@@ -1,4 +1,4 @@
-use crate::{game_objects::game_object::DrawableObject, controller::position::Position, rendering::sprites::sprite_mapping::Sprite, model::results::LogicResult};
+use crate::{game_objects::game_object::DrawableObject, controller::position::Position, rendering::{sprites::sprite_mapping::Sprite, depth::RenderLayer}};
 
 
 #[derive(Debug)]
@@ -55,4 +55,8 @@ impl DrawableObject for Line{
             Line::Vertical{position: _, id: i} => *i = id,
         }
         }
+
+    fn get_layer(&self) -> RenderLayer {
+        RenderLayer::Overlay
+    }
 }
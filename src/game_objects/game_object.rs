@@ -1,6 +1,6 @@
-use std::{fmt::Debug, time::Duration};
+use std::{fmt::Debug, time::Duration, ops::Range};
 
-use crate::{rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}}, controller::position::Position, model::{results::LogicResult, strategy_test::map_chunk::{MapChunk, ChunkInfo}}};
+use crate::{rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, depth::RenderLayer, sprite_instance::SpriteTransform, text::layout::TextContent}, controller::{position::Position, controller_commands::LevelCommand}, model::{results::LogicResult, strategy_test::map_chunk::ChunkInfo}};
 
 
 ///!!!This is the trait that all drawable objects have to implement, the implementations here are defaults and should be overridden if necessary !!!
@@ -8,6 +8,12 @@ pub(crate) trait DrawableObject: Debug + Send + Sync {
     
 
     fn get_position(&self) -> Position;
+    //where the object was at the end of the previous tick, the CamOrganizer draws moving objects between this and get_position. Objects that never move can keep the default
+    fn get_previous_position(&self) -> Position{
+        self.get_position()
+    }
+    //called by the model for every game object at the start of each tick, before anything is moved
+    fn store_previous_position(&mut self){}
     fn get_x_y_values(&self) -> (f32, f32);
     fn get_size(&self) -> f32;
     fn get_texture(&self) -> &Sprite;
@@ -15,12 +21,38 @@ pub(crate) trait DrawableObject: Debug + Send + Sync {
 
 
     //Consider making this interior mutable, in order to speed up access to these 
+    //called by the model once per tick for every game object, delta_time is in seconds
     fn process_animation(&mut self, delta_time: f64);
     fn get_vertex_configuration(&self) -> &VertexConfigration;
 
 
     fn get_id(&self) -> u64;
     fn set_id(&mut self, id: u64);
+
+    //hidden objects are skipped by the CamOrganizer, for example units that are covered by the fog of war
+    fn is_hidden(&self) -> bool{
+        false
+    }
+
+    //the animation frame of the texture that is currently shown, see AnimationClip
+    fn get_frame(&self) -> u32{
+        0
+    }
+
+    //scale, rotation, tint and flip of the sprite, applied on top of the vertex configuration
+    fn get_transform(&self) -> SpriteTransform{
+        SpriteTransform::default()
+    }
+
+    //decides what is drawn in front of what, see RenderLayer
+    fn get_layer(&self) -> RenderLayer{
+        RenderLayer::Structures
+    }
+
+    //objects that return a text are drawn with the glyph atlas instead of their texture, see Text
+    fn get_text(&self) -> Option<TextContent>{
+        None
+    }
 }
 
 pub (crate) trait VisitableStructure: Debug + Send + Sync {
@@ -33,4 +65,12 @@ pub(crate) trait LogicObject: Debug{
     fn process_logic(&mut self, delta_time: Duration) -> LogicResult;
     fn set_id(&mut self, id: u32);
     fn get_id(&self) -> u32;
+
+    //most logic objects don't react to commands, so they don't need to implement this
+    fn process_command(&mut self, _command: &LevelCommand){
+    }
+
+    //the ids the model gave the game objects this logic object created in its last tick, in the order they were created. Logic objects that destroy their own objects keep them from here
+    fn game_objects_added(&mut self, _ids: Range<u64>){
+    }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 pub mod game_object;
 pub mod static_object;
 pub mod buildings;
-pub mod debug;
\ No newline at end of file
+pub mod debug;
+pub mod resource_deposit;
+pub mod text;
\ No newline at end of file
@@ -0,0 +1,80 @@
+use crate::{controller::position::Position, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, depth::RenderLayer, sprite_instance::SpriteTransform}, game_objects::{game_object::DrawableObject, buildings::start_obj::WareType}};
+
+//a spot on the map where workers can gather a ware
+#[derive(Debug)]
+pub(crate) struct ResourceDeposit{
+    pub(crate) ware_type: WareType,
+    pub(crate) position: Position,
+    texture: Sprite,
+    hidden: bool,
+    id: u64,
+}
+
+impl ResourceDeposit{
+    pub(crate) fn new(ware_type: WareType, position: Position) -> Self{
+        let texture = match ware_type{
+            WareType::Food => Sprite::Green,
+            WareType::Wood => Sprite::DarkerGreen,
+            WareType::Stone => Sprite::Black,
+        };
+        Self{
+            ware_type,
+            position,
+            texture,
+            hidden: false,
+            id: 0,
+        }
+    }
+
+    pub(crate) fn set_hidden(&mut self, hidden: bool){
+        self.hidden = hidden;
+    }
+}
+
+impl DrawableObject for ResourceDeposit{
+    fn get_position(&self) -> Position {
+        self.position
+    }
+
+    fn get_x_y_values(&self) -> (f32, f32) {
+        (self.position.x, self.position.y)
+    }
+
+    fn get_size(&self) -> f32 {
+        0.48
+    }
+
+    fn get_texture(&self) -> &Sprite {
+        &self.texture
+    }
+
+    fn process_animation(&mut self, _delta_time: f64) {
+    }
+
+    fn get_vertex_configuration(&self) -> &VertexConfigration {
+        &VertexConfigration::UNIT_SQUARE
+    }
+
+    fn get_transform(&self) -> SpriteTransform {
+        SpriteTransform{
+            scale: [self.get_size(), self.get_size()],
+            ..Default::default()
+        }
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    fn get_layer(&self) -> RenderLayer {
+        RenderLayer::Ground
+    }
+}
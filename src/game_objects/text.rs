@@ -0,0 +1,84 @@
+use crate::{game_objects::game_object::DrawableObject, controller::position::Position, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, depth::RenderLayer, text::layout::{TextContent, TextSpace}}};
+
+const DEFAULT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+//a string that is drawn with the glyph atlas instead of a sprite. The position is the top left corner of the first line, in world units or in pixels of the window (see TextSpace)
+#[derive(Debug)]
+pub(crate) struct Text{
+    position: Position,
+    previous_position: Position,
+    text: TextContent,
+    id: u64,
+}
+
+impl Text{
+    pub(crate) fn new(content: &str, position: Position, size: f32, space: TextSpace) -> Self{
+        Self{
+            position,
+            previous_position: position,
+            text: TextContent{
+                content: content.into(),
+                size,
+                color: DEFAULT_COLOR,
+                space,
+            },
+            id: 0,
+        }
+    }
+
+    pub(crate) fn with_color(mut self, color: [f32; 4]) -> Self{
+        self.text.color = color;
+        self
+    }
+}
+
+impl DrawableObject for Text{
+    fn get_position(&self) -> Position {
+        self.position
+    }
+
+    fn get_previous_position(&self) -> Position {
+        self.previous_position
+    }
+
+    fn store_previous_position(&mut self) {
+        self.previous_position = self.position;
+    }
+
+    fn get_x_y_values(&self) -> (f32, f32) {
+        (self.position.x, self.position.y)
+    }
+
+    fn get_size(&self) -> f32 {
+        self.text.size
+    }
+
+    //not used, texts are drawn by the TextLayer
+    fn get_texture(&self) -> &Sprite {
+        &Sprite::Black
+    }
+
+    fn process_animation(&mut self, _delta_time: f64) {
+    }
+
+    //not used either
+    fn get_vertex_configuration(&self) -> &VertexConfigration {
+        &VertexConfigration::UNIT_SQUARE
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_layer(&self) -> RenderLayer {
+        RenderLayer::Overlay
+    }
+
+    fn get_text(&self) -> Option<TextContent> {
+        Some(self.text.clone())
+    }
+}
@@ -2,10 +2,10 @@ use std::{sync::{atomic::AtomicBool, Arc, RwLock}, thread, time::{Duration, Syst
 
 use controller::{controller::Controller, position::Position, controller_commands::ControllerCommand};
 use flume::Receiver;
-use model::model::Model;
+use model::model::{Model, ModelChannels};
 use rendering::wgpurenderer::RenderChunk;
 use serde::Deserialize;
-use crate::{rendering::wgpurenderer::Renderer, controller::controller::SharablePosition, cam_organizer::cam_organizer::CamOrganizer, };
+use crate::{rendering::{wgpurenderer::Renderer, window_event_loop::RendererInputs, capture::render_level_to_png, capabilities::GraphicsOptions, debug_overlay::stats::DebugStats}, controller::controller::{SharablePosition, CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT}, cam_organizer::cam_organizer::{CamOrganizer, CamOrganizerChannels, CamState}, model::{load_level_functions::{Level, ALL_LEVELS}, model::MODEL_TICK_INTERVAL, tick_clock::TickClock, render_snapshot::new_shared_render_snapshot}, cam_organizer::frame_mailbox::FrameMailbox};
 
 use async_std::task::block_on;
 mod game_objects;
@@ -13,16 +13,56 @@ mod rendering;
 mod model;
 mod controller;
 mod cam_organizer;
+mod ui;
 
 #[async_std::main]
 pub async fn main() {
 
     env_logger::init();     //wgpu logs per default to the env_logger. If we don't initialize it, we only get very basic and not very helpful errors
 
+    //--headless [ticks] runs an AI vs AI match of the strategy level without opening a window, --seed <seed> makes the generated map reproducible
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--headless"){
+        let ticks = args.get(index + 1).and_then(|ticks| ticks.parse().ok()).unwrap_or(36000);
+        let seed = args.iter().position(|arg| arg == "--seed").and_then(|index| args.get(index + 1)).and_then(|seed| seed.parse().ok());
+        model::strategy_test::headless::run_headless_match(ticks, seed);
+        return;
+    }
+
+    //--render-level <level> <file.png> renders the first frame of a level in software without opening a window, e.g. to create golden images
+    if let Some(index) = args.iter().position(|arg| arg == "--render-level"){
+        let level = args.get(index + 1).and_then(|name| Level::from_name(name));
+        let (Some(level), Some(path)) = (level, args.get(index + 2)) else{
+            eprintln!("usage: --render-level <{}> <file.png>", ALL_LEVELS.map(|level| level.name()).join("|"));
+            return;
+        };
+        let camera = CamState{ cam_size: [CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT], cam_pos: [0.0, 0.0] };
+        match render_level_to_png(level, camera, (1400, 787)).and_then(|png| std::fs::write(path, png).map_err(|error| error.to_string())){
+            Ok(_) => println!("Rendered {} to {}", level.name(), path),
+            Err(error) => eprintln!("Could not render {}: {}", level.name(), error),
+        }
+        return;
+    }
+
+    //--dev reloads the sprite shader and textures whenever they change on disk
+    let dev_mode = args.iter().any(|arg| arg == "--dev");
+    //--backend <vulkan,metal,dx12,dx11,gl> only uses these graphics apis, --fallback-adapter renders with the software adapter of wgpu
+    let graphics_options = match GraphicsOptions::from_args(&args){
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
     let (controller_sender, controller_receiver) = flume::unbounded();  //this channel is used to send messages from the event loop to the controller 
     let (controller_to_model_sender, controller_to_model_receiver) = flume::unbounded();  //this channel is used to send messages from the controller to the model
     let (controller_to_renderer_sender, controller_to_renderer_receiver) = flume::unbounded();  //this channel is used to send messages from the controller to the renderer
-    let (renderer_sender, renderer_receiver) = flume::bounded(0);  //this channel is used to send messages from the renderer to the event loop (for example, when the window is resized
+    let frame_mailbox = Arc::new(FrameMailbox::new());  //the CamOrganizer leaves the newest frame here for the renderer, neither of them waits for the other
+    let tick_clock = Arc::new(TickClock::new(MODEL_TICK_INTERVAL));  //the model marks its ticks here, so the CamOrganizer can interpolate between them
+    let (tile_map_sender, tile_map_receiver) = flume::unbounded();  //this channel is used to send the tile map of a newly loaded level from the model to the renderer
+    let (fog_sender, fog_receiver) = flume::unbounded();  //this channel is used to send the fog of war of the player from the model to the renderer
+    let (sprite_set_sender, sprite_set_receiver) = flume::unbounded();  //this channel is used to tell the renderer which sprites the newly loaded level is drawn with
     let mut join_handles_vec = Vec::new();     //this vector will be used to store all the join handles of the threads that are spawned
 
     let running = AtomicBool::new(true);  //<-- this is used to indicate whether the program should exit or not
@@ -30,11 +70,16 @@ pub async fn main() {
 
 
     //model is not completely Send, which means we cannot send it between threads and thus we need to create the shared states beforehand and create the model in the thread
-    let game_objects = Arc::new(tokio::sync::RwLock::new(Vec::new()));
-    let game_objects_clone = game_objects.clone();
+    let render_snapshot = new_shared_render_snapshot();  //the model publishes the visible game objects here after every tick, the CamOrganizer draws them without locking anything
+    let model_render_snapshot = render_snapshot.clone();
+    let model_tick_clock = tick_clock.clone();
+    let debug_stats = Arc::new(DebugStats::default());  //the model and the CamOrganizer report their rates here, the renderer shows them in the debug overlay (F3)
+    let model_debug_stats = debug_stats.clone();
+    let console_sender = controller_sender.clone();    //the model answers console commands, like save, through the inputs of the controller
     //spawn the model thread
     let model_thread = thread::spawn(move || { 
-        let mut model = Model::new( controller_to_model_receiver, game_objects_clone);
+        let channels = ModelChannels{ controller_receiver: controller_to_model_receiver, tile_map_sender, fog_sender, sprite_set_sender, console_sender };
+        let mut model = Model::new(channels, model_render_snapshot, model_tick_clock, model_debug_stats);
         block_on(model.run());
     });
     join_handles_vec.push(model_thread);
@@ -42,12 +87,14 @@ pub async fn main() {
     let cam_pos: SharablePosition = controller.cam_position.clone();
     let cam_proportions = controller.cam_proportions.clone();
     let cam_directions = controller.cam_directions.clone();
+    let ui_frame = controller.ui_frame.clone();
     let controller_thread = thread::spawn(move || { 
         block_on(controller.run());
     });
     join_handles_vec.push(controller_thread);
 
-    let sprite_organizer = CamOrganizer::new(game_objects, cam_pos.clone(), renderer_sender, cam_proportions, cam_directions, running.clone());
+    let channels = CamOrganizerChannels{ render_snapshot, tick_clock, frame_mailbox: frame_mailbox.clone(), debug_stats: debug_stats.clone() };
+    let sprite_organizer = CamOrganizer::new(channels, cam_pos.clone(), cam_proportions, cam_directions, running.clone());
     let sprite_organizer_thread = thread::spawn(move || { 
         block_on(sprite_organizer.run());
     });
@@ -55,5 +102,14 @@ pub async fn main() {
     join_handles_vec.push(sprite_organizer_thread);
 
 
-    Renderer::run(running, join_handles_vec, controller_sender, controller_to_renderer_receiver, cam_pos, renderer_receiver).await;
+    let renderer_inputs = RendererInputs{
+        controller_receiver: controller_to_renderer_receiver,
+        frame_mailbox,
+        tile_map_receiver,
+        fog_receiver,
+        sprite_set_receiver,
+        debug_stats,
+        ui_frame,
+    };
+    Renderer::run(running, join_handles_vec, controller_sender, renderer_inputs, dev_mode, graphics_options).await;
 }
\ No newline at end of file
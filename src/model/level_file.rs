@@ -0,0 +1,209 @@
+use std::{path::{Path, PathBuf}, fmt::Display, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{game_objects::{buildings::debug_house::DebugHouse, game_object::DrawableObject, text::Text}, rendering::{sprites::{sprite_mapping::{Sprite, ALL_SPRITES}, dynamic_vertex_configuration::{register_rectangle, MAX_DYNAMIC_VERTEX_CONFIGURATIONS}, vertex_configration::VertexConfigrationTrait}, text::layout::TextSpace, depth::RenderLayer}, controller::position::Position};
+
+use super::{model::Model, render_snapshot::{RenderSnapshot, RenderItem}};
+
+//the level files are looked up relative to the working directory first and then relative to the crate, like the sprite manifests
+const LEVEL_DIRECTORY: &str = "levels";
+
+//a level that is defined in a json file instead of code, see levels/village.json. It only places objects, there is no logic in it
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct LevelFile{
+    #[serde(default)]
+    pub(crate) sprite_set: u32,     //the sprite manifest the level is drawn with, textures/manifests/level_<sprite_set>.json
+    pub(crate) objects: Vec<LevelObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) scripts: Vec<PathBuf>,      //run as ScriptedLogicObjects once the objects are placed, relative to the level file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum LevelObject{
+    Sprite{
+        sprite: String,     //the name of the Sprite, as in the manifests
+        position: [f32; 2],
+        #[serde(default = "default_sprite_size")]
+        size: [f32; 2],     //in world units
+        #[serde(default = "default_sprite_layer")]
+        layer: RenderLayer,
+    },
+    Text{
+        content: String,
+        position: [f32; 2],
+        size: f32,
+        #[serde(default)]
+        screen: bool,       //true places the text in pixels of the window instead of the world
+        #[serde(default = "default_text_color")]
+        color: [f32; 4],
+    },
+}
+
+fn default_sprite_size() -> [f32; 2]{
+    [1.0, 1.0]
+}
+
+fn default_sprite_layer() -> RenderLayer{
+    RenderLayer::Structures
+}
+
+fn default_text_color() -> [f32; 4]{
+    [1.0, 1.0, 1.0, 1.0]
+}
+
+#[derive(Debug)]
+pub(crate) enum LevelFileError{
+    Io{ path: PathBuf, error: std::io::Error },
+    Parse{ path: PathBuf, error: serde_json::Error },
+    UnknownSprite(String),
+    TooManyShapes,
+    Write{ path: PathBuf, error: std::io::Error },
+}
+
+impl Display for LevelFileError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            LevelFileError::Io{ path, error } => write!(f, "could not read {}: {}", path.display(), error),
+            LevelFileError::Parse{ path, error } => write!(f, "{} is not a valid level file: {}", path.display(), error),
+            LevelFileError::UnknownSprite(name) => write!(f, "the level uses the sprite \"{}\", but there is no such Sprite", name),
+            LevelFileError::TooManyShapes => write!(f, "the level uses more than {} different sprite sizes", MAX_DYNAMIC_VERTEX_CONFIGURATIONS),
+            LevelFileError::Write{ path, error } => write!(f, "could not write {}: {}", path.display(), error),
+        }
+    }
+}
+
+pub(crate) fn level_directory() -> PathBuf{
+    let relative = Path::new(LEVEL_DIRECTORY);
+    if relative.exists(){
+        return relative.to_path_buf();
+    }
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(LEVEL_DIRECTORY)
+}
+
+//plain file names end up in the level directory, so saved levels show up in the level select. The extension is added if it is missing
+pub(crate) fn level_path(name: &str, extension: &str) -> PathBuf{
+    let mut path = PathBuf::from(name);
+    if path.extension().is_none(){
+        path.set_extension(extension);
+    }
+    if path.is_absolute() || path.parent().is_some_and(|parent| parent != Path::new("")){
+        return path;
+    }
+    level_directory().join(path)
+}
+
+//all json files in the level directory, sorted by name. A missing directory just means there are no file levels
+pub(crate) fn list_level_files() -> Vec<PathBuf>{
+    let Ok(entries) = std::fs::read_dir(level_directory()) else{
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|extension| extension == "json")).collect();
+    paths.sort();
+    paths
+}
+
+//what the level select shows for a level file
+pub(crate) fn level_file_title(path: &Path) -> String{
+    path.file_stem().map_or_else(|| path.display().to_string(), |stem| stem.to_string_lossy().replace('_', " "))
+}
+
+pub(crate) fn read_level_file(path: &Path) -> Result<LevelFile, LevelFileError>{
+    let content = std::fs::read_to_string(path).map_err(|error| LevelFileError::Io{ path: path.to_path_buf(), error })?;
+    serde_json::from_str(&content).map_err(|error| LevelFileError::Parse{ path: path.to_path_buf(), error })
+}
+
+pub(crate) fn write_level_file(path: &Path, level_file: &LevelFile) -> Result<(), LevelFileError>{
+    let content = serde_json::to_string_pretty(level_file).expect("a level file only contains plain data");
+    if let Some(directory) = path.parent(){
+        std::fs::create_dir_all(directory).map_err(|error| LevelFileError::Write{ path: path.to_path_buf(), error })?;
+    }
+    std::fs::write(path, content).map_err(|error| LevelFileError::Write{ path: path.to_path_buf(), error })
+}
+
+impl LevelFile{
+    //everything that is visible in the snapshot and the scripts. Other logic objects, like the maze or the factions of the strategy level, can't be stored in a level file
+    pub(crate) fn from_snapshot(snapshot: &RenderSnapshot, sprite_set: u32, scripts: Vec<PathBuf>) -> Self{
+        let mut objects: Vec<LevelObject> = snapshot.items.iter().filter_map(sprite_object).collect();
+        objects.extend(snapshot.texts.iter().map(|item| LevelObject::Text{
+            content: item.text.content.to_string(),
+            position: [item.position.x, item.position.y],
+            size: item.text.size,
+            screen: item.text.space == TextSpace::Screen,
+            color: item.text.color,
+        }));
+        Self{ sprite_set, objects, scripts }
+    }
+
+    pub(crate) fn create_game_objects(&self) -> Result<Vec<Arc<RwLock<dyn DrawableObject + Send + Sync>>>, LevelFileError>{
+        let mut game_objects: Vec<Arc<RwLock<dyn DrawableObject + Send + Sync>>> = Vec::with_capacity(self.objects.len());
+        for object in self.objects.iter(){
+            match object{
+                LevelObject::Sprite{ sprite, position, size, layer } => {
+                    let texture = Sprite::from_name(sprite).ok_or_else(|| LevelFileError::UnknownSprite(sprite.clone()))?;
+                    let house = DebugHouse::new(texture, Position::new(position[0], position[1]), register_rectangle(size[0], size[1]).ok_or(LevelFileError::TooManyShapes)?).with_layer(*layer);
+                    game_objects.push(Arc::new(RwLock::new(house)));
+                }
+                LevelObject::Text{ content, position, size, screen, color } => {
+                    let space = if *screen { TextSpace::Screen } else { TextSpace::World };
+                    game_objects.push(Arc::new(RwLock::new(Text::new(content, Position::new(position[0], position[1]), *size, space).with_color(*color))));
+                }
+            }
+        }
+        Ok(game_objects)
+    }
+}
+
+//level files only know centered rectangles, so the shape is stored as its bounding box and the position is moved to the center of it
+fn sprite_object(item: &RenderItem) -> Option<LevelObject>{
+    let sprite = ALL_SPRITES.get(item.texture_id as usize)?;
+    let vertices = item.vertex_conf.to_configuration().get_vertices();
+    let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+    for vertex in vertices.iter(){
+        for axis in 0..2{
+            min[axis] = min[axis].min(vertex.position[axis]);
+            max[axis] = max[axis].max(vertex.position[axis]);
+        }
+    }
+    let scale = item.transform.scale;
+    Some(LevelObject::Sprite{
+        sprite: sprite.name().to_string(),
+        position: [item.position.x + (min[0] + max[0]) / 2.0 * scale[0], item.position.y + (min[1] + max[1]) / 2.0 * scale[1]],
+        size: [(max[0] - min[0]) * scale[0], (max[1] - min[1]) * scale[1]],
+        layer: item.layer,
+    })
+}
+
+impl Model{
+    //a broken file is reported and the current level keeps running
+    pub(crate) async fn load_level_file(&mut self, path: &Path){
+        let loaded = read_level_file(path).and_then(|level_file| Ok((level_file.create_game_objects()?, level_file.sprite_set, level_file.scripts)));
+        let (game_objects, sprite_set, scripts) = match loaded{
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.console_output(format!("Could not load the level {}: {}", path.display(), error));
+                return;
+            }
+        };
+        self.reset_counters();
+        self.clear_objects().await;
+        self.add_game_objects(game_objects).await;
+        self.use_sprite_set(sprite_set);
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for script in scripts{
+            self.run_script(&directory.join(script));
+        }
+    }
+
+    pub(crate) fn save_level_file(&self, path: &Path){
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let scripts = self.scripts.iter().map(|script| script.strip_prefix(directory).unwrap_or(script).to_path_buf()).collect();
+        let level_file = LevelFile::from_snapshot(&self.render_snapshot.load(), self.sprite_set, scripts);
+        match write_level_file(path, &level_file){
+            Ok(()) => self.console_output(format!("Saved {} objects to {}", level_file.objects.len(), path.display())),
+            Err(error) => self.console_output(format!("Could not save the level: {}", error)),
+        }
+    }
+}
@@ -3,10 +3,11 @@ use std::{sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 use futures::join;
 
-use crate::{game_objects::{buildings::debug_house::DebugHouse, debug::line::Line}, rendering::sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, controller::position::Position};
+use crate::{game_objects::{buildings::debug_house::DebugHouse, text::Text, game_object::LogicObject}, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, depth::RenderLayer, text::layout::TextSpace}, controller::position::Position};
 
 use super::{model::Model, maze::maze::Maze, logic_test::logic_test::LogicTest, strategy_test::{strategy_test::StratLevel, map_chunk::{ChunkInfo, IntEdge}}};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Level{
     Initial,
     Maze,
@@ -14,6 +15,41 @@ pub(crate) enum Level{
     StrategyTest,
 }
 
+pub(crate) const ALL_LEVELS: [Level; 4] = [Level::Initial, Level::Maze, Level::LogicTests, Level::StrategyTest];
+
+impl Level{
+    //the name used on the command line
+    pub(crate) fn name(&self) -> &'static str{
+        match self{
+            Level::Initial => "initial",
+            Level::Maze => "maze",
+            Level::LogicTests => "logic_tests",
+            Level::StrategyTest => "strategy_test",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Level>{
+        ALL_LEVELS.iter().copied().find(|level| level.name() == name)
+    }
+
+    //the name shown in the level select
+    pub(crate) fn title(&self) -> &'static str{
+        match self{
+            Level::Initial => "Initial",
+            Level::Maze => "Maze",
+            Level::LogicTests => "Logic tests",
+            Level::StrategyTest => "Strategy test",
+        }
+    }
+
+    //the sprite manifest the level is drawn with, textures/manifests/level_<sprite_set>.json
+    pub(crate) fn sprite_set(&self) -> u32{
+        match self{
+            Level::Initial | Level::Maze | Level::LogicTests | Level::StrategyTest => 0,
+        }
+    }
+}
+
 impl Model{
 
     pub(crate) async fn load_level(&mut self, level: Level){
@@ -26,13 +62,18 @@ impl Model{
             Level::LogicTests => self.load_logic_tests().await,
             Level::StrategyTest => self.load_strategy_test().await,
         } 
+        self.use_sprite_set(level.sprite_set());
     }
 
     pub(crate) async fn load_strategy_test(&mut self){
         async_std::task::sleep(Duration::from_millis(500)).await;
+        self.load_strategy_test_with_seed(None).await;
+    }
 
+    //the same seed always generates the same map, None picks a random one
+    pub(crate) async fn load_strategy_test_with_seed(&mut self, seed: Option<u64>){
         self.clear_objects().await;
-        let mut level = StratLevel::new(ChunkInfo{
+        let borders = ChunkInfo{
             bottom_left: IntEdge{
                 x: -100,
                 y: -100,
@@ -41,8 +82,15 @@ impl Model{
                 x: 100,
                 y: 100,
             }
-        });
+        };
+        let mut level = match seed{
+            Some(seed) => StratLevel::with_seed(borders, seed),
+            None => StratLevel::new(borders),
+        };
+        level.fog_sender = Some(self.fog_sender.clone());
+        level.console_sender = Some(self.console_sender.clone());
         let game_objects = level.initialize().await;
+        let _ = self.tile_map_sender.send(level.tile_map_data());
         self.add_logic_object(Box::new(level));
         self.add_game_objects(game_objects).await;
 
@@ -53,12 +101,12 @@ impl Model{
 
        self.clear_objects().await;
         //create a Maze
-        let (maze, to_add_objects ) = Maze::new(43, 24, (-10.0, -5.0));
-        let background_square = DebugHouse::new(Sprite::DarkBlue, Position { x: 1241.2, y: 1231.1 }, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0);
-        self.add_game_object(Arc::new(RwLock::new(background_square))).await;  //this is just a workaround. The cam_organizer start at game_objects[0] and goes through all, constructing the necessary commands, which means that the vertex_configurations that are seen first are in the background and the ones later are in the foreground
-        //this can be avoided by implementing a depth buffer to use in the wgpu render-pipeline
+        let (mut maze, to_add_objects ) = Maze::new(43, 24, (-10.0, -5.0));
+        let background_square = DebugHouse::new(Sprite::DarkBlue, Position { x: 1241.2, y: 1231.1 }, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0).with_layer(RenderLayer::Background);
+        self.add_game_object(Arc::new(RwLock::new(background_square))).await;
+        let ids = self.add_game_objects(to_add_objects).await;
+        maze.game_objects_added(ids);
         self.add_logic_object(Box::new(maze));
-        self.add_game_objects(to_add_objects).await;
 
 
     }
@@ -67,14 +115,16 @@ impl Model{
 
         self.clear_objects().await;
 
-        let background_square = DebugHouse::new(Sprite::DarkBlue, Position { x: 1241.2, y: 1231.1 }, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0);
+        let background_square = DebugHouse::new(Sprite::DarkBlue, Position { x: 1241.2, y: 1231.1 }, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0).with_layer(RenderLayer::Background);
         self.add_game_object(Arc::new(RwLock::new(background_square))).await;
         let test = LogicTest::new(10000);
         self.add_logic_object(Box::new(test));
 
     }
 
-    async fn clear_objects(&mut self){
+    pub(super) async fn clear_objects(&mut self){
+        let _ = self.tile_map_sender.send(None);
+        let _ = self.fog_sender.send(None);
         self.clear_logic_objects();
         self.clear_game_objects().await;
         self.clear_static_objects().await;
@@ -82,7 +132,10 @@ impl Model{
 
 
     pub(crate) async fn load_initial_level(&mut self){
-        
-    
+        self.clear_objects().await;
+        let title = Text::new("spawg", Position::new(-3.0, 2.0), 2.5, TextSpace::World);
+        let hint = Text::new("Esc: menu\nSpace: pause, .: single tick, +/-: speed\nF3: debug overlay\nF12: screenshot", Position::new(16.0, 16.0), 18.0, TextSpace::Screen).with_color([0.8, 0.8, 0.8, 1.0]);
+        self.add_game_object(Arc::new(RwLock::new(title))).await;
+        self.add_game_object(Arc::new(RwLock::new(hint))).await;
     }
 }
\ No newline at end of file
@@ -77,7 +77,7 @@ impl LogicTest {
         const LEN : usize = 20;
         const WID : usize = 20;
         //if a json file with the results exists, load it
-        let path_str = "results".to_owned()+&WID.to_string() +"_"+&LEN.to_string()+".json";
+        let path_str = "results".to_owned()+&*WID.to_string() +"_"+&*LEN.to_string()+".json";
         if std::path::Path::new(&path_str).exists(){
             let file = std::fs::File::open(path_str).unwrap();
             let reader = std::io::BufReader::new(file);
@@ -198,7 +198,7 @@ impl LogicTest {
         if self.exec_count == 200_000{
             //Serialize to json and write to file
             self.ser_vec.result_vec.push((start_pos_1, start_pos_2, self.result_vec[0] as f64 / self.exec_count as f64, self.result_vec[1] as f64 / self.exec_count as f64));
-            let path_str = "results".to_owned()+&self.width.to_string() +"_"+&self.length.to_string()+".json";
+            let path_str = "results".to_owned()+&*self.width.to_string() +"_"+&*self.length.to_string()+".json";
 
             let file = std::fs::File::create(path_str).unwrap();
             let writer = std::io::BufWriter::new(file);
@@ -1,9 +1,9 @@
-use std::{ time::Duration, cell::{RefCell, Ref, RefMut}, rc::{Rc, Weak}, sync::{ Arc}, io::Read};
+use std::{ time::Duration, cell::{RefCell, RefMut}, rc::{Rc, Weak}, sync::{ Arc}, ops::Range};
 
 use tokio::sync::RwLock;
 use rand::Rng;
 
-use crate::{game_objects::{game_object::{DrawableObject, LogicObject}, debug::line::Line, buildings::debug_house::DebugHouse}, model::{results::{LogicResult, GameObjects}, strategy_test::worker::Worker}, controller::{controller::Direction, position::Position}, rendering::sprites::{vertex_configration::VertexConfigration, sprite_mapping::Sprite}};
+use crate::{game_objects::{game_object::{DrawableObject, LogicObject}, debug::line::Line, buildings::debug_house::DebugHouse}, model::results::{LogicResult, GameObjects}, controller::position::Position, rendering::{sprites::{dynamic_vertex_configuration::register_rectangle, sprite_mapping::Sprite}, depth::RenderLayer}};
 
 
 const DISTANCE_BETWEEN_TILES: f32 = 0.48;
@@ -21,6 +21,7 @@ pub(crate) struct Maze{
     current_path: Option<Vec<Weak<RefCell<MazeTile>>>>,
     id: u32,
     blocked: Vec<Vec<(usize, usize)>>,
+    pending_objects: Vec<Option<(Weak<RefCell<MazeTile>>, usize)>>,      //the tile and side of every object of the last result, the model tells their ids in game_objects_added
 
 }
 
@@ -52,6 +53,14 @@ impl LogicObject for Maze{
     fn set_id(&mut self, id: u32) {
         self.id = id;
     }
+
+    fn game_objects_added(&mut self, ids: Range<u64>) {
+        for (id, pending) in ids.zip(self.pending_objects.drain(..)){
+            if let Some((tile, side)) = pending{
+                tile.upgrade().unwrap().borrow_mut().underlying_ids[side] = Some(id);
+            }
+        }
+    }
 }
 
 impl Maze{
@@ -68,7 +77,7 @@ impl Maze{
             let column = Vec::with_capacity(height);
             maze.push(column);
             for j in 0..height{
-                let tile = MazeTile{position_offset: position, position: (i, j),connected:(true,true,true,true),visited:false, underlying_objects: [None, None, None, None]};
+                let tile = MazeTile{position_offset: position, position: (i, j),connected:(true,true,true,true),visited:false, underlying_objects: [None, None, None, None], underlying_ids: [None; 4]};
                 let refr = Rc::new(RefCell::new(tile));
                 maze[i].push(refr);
             }
@@ -94,6 +103,7 @@ impl Maze{
             start_tile: start_tile,
             end_tile: end_tile,     
             blocked: bl,       
+            pending_objects: Vec::new(),
          };
        
         maze.set_outside_walls();
@@ -132,19 +142,30 @@ impl Maze{
 
     }
 
-    fn generate_maze_objects(&self) -> GameObjects{
+    fn generate_maze_objects(&mut self) -> GameObjects{
         let mut objects = GameObjects::new();
+        self.pending_objects.clear();
         for i in 0..self.width{
             for j in 0..self.height{
                 let tile = self.maze[i][j].clone();
-                let mut tile = tile.borrow_mut();
-                let tile_objects = tile.update_underlying_objects();
+                let tile_objects = tile.borrow_mut().update_underlying_objects();
+                self.remember_sides(&Rc::downgrade(&tile), &tile_objects.0);
 
                 objects.extend(tile_objects.0);
             }
         }
 
         objects
+    }
+
+    //the objects are matched with the side of the tile they were put on, so the ids the model hands out later end up at the right side
+    fn remember_sides(&mut self, tile: &Weak<RefCell<MazeTile>>, objects: &GameObjects){
+        let upgrade = tile.upgrade().unwrap();
+        let tile_ref = upgrade.borrow();
+        for object in objects.iter(){
+            let side = tile_ref.underlying_objects.iter().position(|side_object| side_object.as_ref().is_some_and(|side_object| Arc::ptr_eq(side_object, object)));
+            self.pending_objects.push(side.map(|side| (tile.clone(), side)));
+        }
     }       
    
    
@@ -170,6 +191,8 @@ impl Maze{
                 path.push(weak.clone());
                 let possible_neighbors = self.visit_tile(&weak);
                 let (to_add, to_remove) = weak.upgrade().unwrap().borrow_mut().update_underlying_objects_with_prev_ref(possible_neighbors);
+                self.pending_objects.clear();
+                self.remember_sides(&weak, &to_add);
                 return LogicResult::CreateAndDestroyGameObjects { game_objects_to_create: to_add, game_objects_to_destroy: to_remove };
             }
             else{
@@ -178,6 +201,8 @@ impl Maze{
                     if let Some(tile) = path.last(){
                         let possible_neighbors =  self.visit_tile(tile);
                         let (to_add, to_remove) = tile.upgrade().unwrap().borrow_mut().update_underlying_objects_with_prev_ref(possible_neighbors);
+                        self.pending_objects.clear();
+                        self.remember_sides(tile, &to_add);
                         return LogicResult::CreateAndDestroyGameObjects { game_objects_to_create: to_add, game_objects_to_destroy: to_remove };
                     }else{
                         unreachable!("Path is empty, but current_path is not");
@@ -382,6 +407,7 @@ struct MazeTile{
     connected: (bool, bool, bool, bool),
     visited: bool,
     underlying_objects: [Option<Arc<RwLock<dyn DrawableObject + Send + Sync>>>; 4],
+    underlying_ids: [Option<u64>; 4],       //the ids of the underlying objects, None until the model added them
 }
 
 impl MazeTile{
@@ -393,10 +419,9 @@ impl MazeTile{
         let actual_x = self.position.0 as f32 * DISTANCE_BETWEEN_TILES + self.position_offset.0;
         let actual_y = self.position.1 as f32 * DISTANCE_BETWEEN_TILES + self.position_offset.1;
 
-        if let Some(object) = current_object{
+        if current_object.is_some(){
             if self.connected.0{
-                let obj_id = object.try_read().unwrap().get_id();
-                to_remove.push(obj_id);
+                to_remove.extend(self.underlying_ids[0].take());
                 self.underlying_objects[0] = None;
             }
         }else{
@@ -411,10 +436,10 @@ impl MazeTile{
 
         let current_object = self.underlying_objects[1].clone();
        //process right
-        if let Some(object) = current_object{
+        if current_object.is_some(){
             if self.connected.1{
-                let obj_id = object.try_read().unwrap().get_id();
-                to_remove.push(obj_id);                self.underlying_objects[1] = None;
+                to_remove.extend(self.underlying_ids[1].take());
+                self.underlying_objects[1] = None;
             }
         }else{
             if !self.connected.1{
@@ -423,12 +448,12 @@ impl MazeTile{
                 self.underlying_objects[1] = Some(object);
             }
         }
-        let current_object = self.underlying_objects[3].clone();
+        let current_object = self.underlying_objects[2].clone();
         //process bottom
-        if let Some(object) = current_object{
+        if current_object.is_some(){
             if self.connected.2{
-                let obj_id = object.try_read().unwrap().get_id();
-                to_remove.push(obj_id);                self.underlying_objects[2] = None;
+                to_remove.extend(self.underlying_ids[2].take());
+                self.underlying_objects[2] = None;
             }
         }else{
             if !self.connected.2{
@@ -439,10 +464,10 @@ impl MazeTile{
         }
         //process left
         let current_object = self.underlying_objects[3].clone();
-        if let Some(object) = current_object{
+        if current_object.is_some(){
             if self.connected.3{
-                let obj_id = object.try_read().unwrap().get_id();
-                to_remove.push(obj_id);                self.underlying_objects[3] = None;
+                to_remove.extend(self.underlying_ids[3].take());
+                self.underlying_objects[3] = None;
             }
         }else{
             if !self.connected.3{
@@ -466,7 +491,8 @@ impl MazeTile{
         let actual_y = self.position.1 as f32 * DISTANCE_BETWEEN_TILES + self.position_offset.1;
 
         
-        let background_square = DebugHouse::new(Sprite::Green, Position { x: actual_x, y: actual_y}, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0);
+        //the cells are exactly as large as the distance between them, whatever that is set to
+        let background_square = DebugHouse::new(Sprite::Green, Position { x: actual_x, y: actual_y}, register_rectangle(DISTANCE_BETWEEN_TILES, DISTANCE_BETWEEN_TILES).expect("all cells share one shape")).with_layer(RenderLayer::Ground);
         to_add.push(Arc::new(RwLock::new(background_square)));
         if let Some(object) = current_object{
 
@@ -579,11 +605,30 @@ impl MazeTile{
 
 
     fn get_side_object_id(&self, direction: usize) -> Option<u64>{
-        let objects: [Option<Arc<RwLock<dyn DrawableObject + Send + Sync>>>; 4] = self.underlying_objects.clone();
-        let dum: Option<&Arc<RwLock<dyn DrawableObject + Send + Sync>>> = objects[direction].as_ref();
-        if let Some(obj) = dum{
-            return Some(obj.blocking_read().get_id());
+        self.underlying_ids[direction]
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn the_walls_get_the_ids_of_their_objects(){
+        let (mut maze, objects) = Maze::new(4, 3, (0.0, 0.0));
+        maze.game_objects_added(10..10 + objects.len() as u64);
+        let mut walls = 0;
+        for tile in maze.maze.iter().flatten(){
+            let tile = tile.borrow();
+            for side in 0..4{
+                if let Some(object) = &tile.underlying_objects[side]{
+                    let index = objects.iter().position(|created| Arc::ptr_eq(created, object)).unwrap();
+                    assert_eq!(tile.get_side_object_id(side), Some(10 + index as u64));
+                    walls += 1;
+                }
+            }
         }
-        return None
+        assert!(walls > 0);
+        assert!(maze.pending_objects.is_empty());
     }
-}
\ No newline at end of file
+}
@@ -2,7 +2,12 @@ pub mod model;
 pub mod spawn_functions;
 pub mod load_level_functions;
 pub mod level;
+pub mod level_file;
 pub mod maze;
 pub mod results;
+pub mod tick_clock;
+pub mod time_scale;
+pub mod render_snapshot;
 pub mod logic_test;
-pub mod strategy_test;
\ No newline at end of file
+pub mod strategy_test;
+pub mod scripting;
\ No newline at end of file
@@ -1,26 +1,43 @@
-use std::{sync::{atomic::{AtomicBool, Ordering}, RwLock}, os::windows::thread, time::Duration, future::Future, ops::Index};
+use std::{collections::HashSet, path::PathBuf, time::Duration, ops::Range};
 
-use crate::{game_objects::{game_object::{DrawableObject, LogicObject}, static_object::StaticObject, buildings::debug_house::DebugHouse}, controller::{position::Position, controller_commands::ControllerCommand}, rendering::sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}};
-use async_std::{sync::{Arc}, task::block_on};
+use crate::{game_objects::{game_object::{DrawableObject, LogicObject}, static_object::StaticObject}, controller::{controller_commands::{ControllerCommand, Selection}, input::ControllerInput}, rendering::{tilemap::TileMapData, fog_of_war::FogData, debug_overlay::stats::DebugStats}};
+use async_std::sync::Arc;
 use tokio::sync::RwLock as AsyncRwLock;
-use flume::{Receiver, r#async};
+use flume::{Receiver, Sender};
 
-use super::results::LogicResult;
+use super::{results::LogicResult, tick_clock::TickClock, time_scale::TimeScale, render_snapshot::{SharedRenderSnapshot, RenderSnapshot, RenderItem, TextItem}};
 
 //these types are just shorthand for the long type names, making it more easy to assess them
 pub(crate) type GameObjectList = Arc<AsyncRwLock<Vec<Arc<AsyncRwLock<dyn DrawableObject + Send + Sync >>>>>;
 pub(crate) type StaticObjectList = Arc<AsyncRwLock<Vec<StaticObject>>>;
 pub(crate) type LogicObjects = Vec<Box<dyn LogicObject>>;
 
+pub(crate) const MODEL_TICK_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 60);
+//multiplied with the tint of selected objects
+const SELECTED_TINT: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
 pub(crate) struct Model{
     pub(crate) running: bool,  //<-- this is used to indicate whether the program should exit or not
-    game_objects: GameObjectList,           //we need to make sure to increment the counters and thus we can't let the controller directly access these, instead we have to use the commands
+    pub(super) game_objects: GameObjectList,           //we need to make sure to increment the counters and thus we can't let the controller directly access these, instead we have to use the commands
+    pub(super) render_snapshot: SharedRenderSnapshot,   //what the CamOrganizer draws, so it doesn't have to lock the game objects
     pub static_objects: StaticObjectList,
     pub(crate) logic_objects: LogicObjects,
 
 
     controller_receiver: Receiver<ControllerCommand>,
+    pub(super) tile_map_sender: Sender<Option<TileMapData>>,    //the ground of a level is static, so it is sent to the renderer once when the level is loaded
+    pub(super) fog_sender: Sender<Option<FogData>>,     //levels with vision send the fog of war of the player through this
+    sprite_set_sender: Sender<u32>,      //the renderer reloads the sprites when a level with another sprite set is loaded
+    pub(super) sprite_set: u32,
+    pub(super) console_sender: Sender<ControllerInput>,      //answers to the commands of the console go back to the controller
+    selection: HashSet<u64>,        //ids of the selected game objects, they are drawn with SELECTED_TINT
+    pub(super) scripts: Vec<PathBuf>,      //the scripts running in the current level, they are written into saved level files
     pub state: i32,
+    tick_clock: Arc<TickClock>,    //lets the CamOrganizer interpolate between two ticks
+    debug_stats: Arc<DebugStats>,
+    paused: bool,
+    pending_steps: u32,     //ticks that still have to be run while paused
+    time_scale: TimeScale,
     
 
     static_object_id_counter: u64,
@@ -29,6 +46,16 @@ pub(crate) struct Model{
 }
 
 
+//the channels of the model to and from the other threads, grouped so Model::new stays short
+pub(crate) struct ModelChannels{
+    pub(crate) controller_receiver: Receiver<ControllerCommand>,
+    pub(crate) tile_map_sender: Sender<Option<TileMapData>>,
+    pub(crate) fog_sender: Sender<Option<FogData>>,
+    pub(crate) sprite_set_sender: Sender<u32>,
+    pub(crate) console_sender: Sender<ControllerInput>,
+}
+
+
 //this struct is to group all the actions that the model has to do after the logic has been processed in order to deal with newly created objects and objects that need to be destroyed
 
 struct LogicActions{
@@ -44,12 +71,26 @@ struct LogicActions{
 
 
 impl Model{
-    pub(crate) fn new(controller_to_model_receiver: Receiver<ControllerCommand>, game_objects: GameObjectList) -> Self{
+    pub(crate) fn new(channels: ModelChannels, render_snapshot: SharedRenderSnapshot, tick_clock: Arc<TickClock>, debug_stats: Arc<DebugStats>) -> Self{
+        let ModelChannels{ controller_receiver, tile_map_sender, fog_sender, sprite_set_sender, console_sender } = channels;
         Self{
             running: true,
-            game_objects: game_objects,
+            game_objects: Arc::new(AsyncRwLock::new(Vec::new())),
+            render_snapshot,
             static_objects: Arc::new(AsyncRwLock::new(Vec::new())),
-            controller_receiver: controller_to_model_receiver,
+            controller_receiver,
+            tile_map_sender,
+            fog_sender,
+            sprite_set_sender,
+            sprite_set: 0,
+            console_sender,
+            selection: HashSet::new(),
+            scripts: Vec::new(),
+            tick_clock,
+            debug_stats,
+            paused: false,
+            pending_steps: 0,
+            time_scale: TimeScale::NORMAL,
             state: 0,
             logic_objects: Vec::new(),
             static_object_id_counter: 0,
@@ -67,7 +108,7 @@ impl Model{
 
         let mut loop_helper = spin_sleep::LoopHelper::builder()
         .report_interval_s(0.5) // report every half a second
-        .build_with_target_rate(60.0);
+        .build_with_target_rate(1.0 / MODEL_TICK_INTERVAL.as_secs_f64());
         let mut loop_reset_bool = false;
         while self.running{
 
@@ -79,9 +120,21 @@ impl Model{
                 loop_helper.loop_start();
             }
             //do stuff
-            self.update(loop_helper.loop_start()).await;    //what this does is that it calls whatever function we have stored in logic_function. This means that we can easily change what the model is supposed to do.
+            let elapsed = loop_helper.loop_start();
+            match self.simulated_delta(elapsed){
+                Some(delta_time) => self.update(delta_time).await,    //what this does is that it calls whatever function we have stored in logic_function. This means that we can easily change what the model is supposed to do.
+                None => self.store_previous_positions().await,     //paused: the CamOrganizer must not keep interpolating between the last two ticks
+            }
+            let tick = self.tick_clock.tick();
+            self.publish_render_snapshot(tick).await;
 
-            loop_helper.loop_sleep();
+            if let Some(tps) = loop_helper.report_rate(){
+                self.report_debug_stats(tps).await;
+            }
+
+            if self.paused || self.time_scale != TimeScale::Unlimited{
+                loop_helper.loop_sleep();
+            }
 
         }   
 
@@ -93,10 +146,110 @@ impl Model{
 
 
 
+    //the time that passes in the simulation during this tick, None while paused and there is no step to do
+    fn simulated_delta(&mut self, elapsed: Duration) -> Option<Duration>{
+        if !self.paused{
+            return Some(self.time_scale.scale(elapsed));
+        }
+        if self.pending_steps == 0{
+            return None;
+        }
+        self.pending_steps -= 1;
+        Some(MODEL_TICK_INTERVAL)
+    }
+
     #[inline(always)]
      async fn update(&mut self, delta_time: Duration){
         //only logic objects actually do something, so there is no need to call anything else than compute_logic_objects
+        self.store_previous_positions().await;
         self.compute_logic_objects(delta_time).await;
+        self.compute_animations(delta_time).await;
+    }
+
+    //the positions of the last tick, the CamOrganizer interpolates from there to the positions after this tick
+    #[inline(always)]
+    async fn store_previous_positions(&mut self){
+        let game_objects = self.game_objects.read().await;
+        for game_object in game_objects.iter(){
+            game_object.write().await.store_previous_position();
+        }
+    }
+
+    //the renderer reloads the sprites if the set changed
+    pub(super) fn use_sprite_set(&mut self, sprite_set: u32){
+        self.sprite_set = sprite_set;
+        let _ = self.sprite_set_sender.send(sprite_set);
+    }
+
+    //shown in the console and printed, so it also ends up in the log
+    pub(super) fn console_output(&self, message: String){
+        println!("{}", message);
+        let _ = self.console_sender.send(ControllerInput::ConsoleOutput(message));
+    }
+
+    async fn select(&mut self, selection: Selection){
+        match selection{
+            Selection::All => {
+                let game_objects = self.game_objects.read().await;
+                let mut ids = Vec::with_capacity(game_objects.len());
+                for game_object in game_objects.iter(){
+                    ids.push(game_object.read().await.get_id());
+                }
+                drop(game_objects);
+                self.selection = ids.into_iter().collect();
+            }
+            Selection::None => self.selection.clear(),
+        }
+        self.console_output(format!("{} objects selected", self.selection.len()));
+    }
+
+    //copies what the CamOrganizer needs out of the visible game objects and replaces the last snapshot with it. Only the model locks the game objects, so this never waits
+    pub(crate) async fn publish_render_snapshot(&self, tick: u64){
+        let game_objects = self.game_objects.read().await;
+        let mut items = Vec::with_capacity(game_objects.len());
+        let mut texts = Vec::new();
+        for game_object in game_objects.iter(){
+            let object = game_object.read().await;
+            if object.is_hidden(){
+                continue;
+            }
+            if let Some(text) = object.get_text(){
+                texts.push(TextItem{ position: object.get_position(), previous_position: object.get_previous_position(), text });
+                continue;
+            }
+            let mut transform = object.get_transform();
+            if self.selection.contains(&object.get_id()){
+                transform.tint = std::array::from_fn(|index| transform.tint[index] * SELECTED_TINT[index]);
+            }
+            items.push(RenderItem{
+                position: object.get_position(),
+                previous_position: object.get_previous_position(),
+                texture_id: *object.get_texture() as u32,
+                frame: object.get_frame(),
+                transform,
+                layer: object.get_layer(),
+                vertex_conf: object.get_vertex_configuration().handle(),
+            });
+        }
+        self.render_snapshot.store(Arc::new(RenderSnapshot{ tick, items, texts }));
+    }
+
+    async fn report_debug_stats(&self, tps: f64){
+        self.debug_stats.set_model_tps(tps);
+        self.debug_stats.set_simulation_speed(self.paused, self.time_scale);
+        let game_objects = self.game_objects.read().await.len();
+        let static_objects = self.static_objects.read().await.len();
+        self.debug_stats.set_object_counts(game_objects, self.logic_objects.len(), static_objects);
+    }
+
+    //animations are ticked here for all game objects, so they advance with the model and not with the frame rate of the renderer
+    #[inline(always)]
+    async fn compute_animations(&mut self, delta_time: Duration){
+        let delta_secs = delta_time.as_secs_f64();
+        let game_objects = self.game_objects.read().await;
+        for game_object in game_objects.iter(){
+            game_object.write().await.process_animation(delta_secs);
+        }
     }
 
 
@@ -104,16 +257,26 @@ impl Model{
     #[inline(always)]
     async fn compute_logic_objects(&mut self, delta_time: Duration){
         let mut logic_actions = LogicActions::new();
+        let mut created_counts = Vec::with_capacity(self.logic_objects.len());
         for id in 0..self.logic_objects.len(){
             let logic_object = self.logic_objects[id].as_mut();
             let logic_result = logic_object.process_logic(delta_time);
+            let created_before = logic_actions.create_game_objects.len();
             process_logic_result(logic_result, &mut logic_actions);
+            created_counts.push((logic_actions.create_game_objects.len() - created_before) as u64);
         }
         if logic_actions.destroy_game_objects.len() > 0 {
             self.destroy_game_objects(logic_actions.destroy_game_objects).await;
         }
         if logic_actions.create_game_objects.len() > 0{
-            self.add_game_objects(logic_actions.create_game_objects).await;
+            let mut next_id = self.add_game_objects(logic_actions.create_game_objects).await.start;
+            //the ids are handed out in the order the logic objects returned their game objects
+            for (logic_object, count) in self.logic_objects.iter_mut().zip(created_counts){
+                if count > 0{
+                    logic_object.game_objects_added(next_id..next_id + count);
+                    next_id += count;
+                }
+            }
         }
         if logic_actions.create_logic_objects.len() > 0 {
             self.add_logic_objects(logic_actions.create_logic_objects);
@@ -172,6 +335,33 @@ impl Model{
                 }
             ControllerCommand::SpawnHouseAtPositionPixelated { spawn_position: _ } => {false},
             ControllerCommand::LoadLevel(level) => {self.load_level(level).await; true },
+            ControllerCommand::LoadLevelFile(path) => {self.load_level_file(&path).await; true },
+            ControllerCommand::Select(selection) => {self.select(selection).await; false },
+            ControllerCommand::SaveLevel(path) => {self.save_level_file(&path); false },
+            ControllerCommand::RunScript(path) => {self.run_script(&path); false },
+            ControllerCommand::SetPaused(paused) => {
+                self.paused = paused;
+                self.pending_steps = 0;
+                self.debug_stats.set_simulation_speed(self.paused, self.time_scale);
+                false
+            },
+            ControllerCommand::StepTick => {
+                if self.paused{
+                    self.pending_steps += 1;
+                }
+                false
+            },
+            ControllerCommand::SetTimeScale(time_scale) => {
+                self.time_scale = time_scale;
+                self.debug_stats.set_simulation_speed(self.paused, self.time_scale);
+                true    //the time the model slept at the old speed must not be scaled with the new one
+            },
+            ControllerCommand::Level(level_command) => {
+                for logic_object in self.logic_objects.iter_mut(){
+                    logic_object.process_command(&level_command);
+                }
+                false
+            },
             ControllerCommand::Shutdown => {self.running = false; false },
          }
     }
@@ -190,12 +380,15 @@ impl Model{
         self.game_object_id_counter += 1;
     }
 
-    pub(super) async fn add_game_objects(&mut self, game_objects: Vec<Arc<AsyncRwLock<dyn DrawableObject + Send + Sync>>>){
+    //returns the ids the game objects got, they are consecutive
+    pub(super) async fn add_game_objects(&mut self, game_objects: Vec<Arc<AsyncRwLock<dyn DrawableObject + Send + Sync>>>) -> Range<u64>{
+        let first_id = self.game_object_id_counter;
         for game_object in &game_objects{
             game_object.write().await.set_id(self.game_object_id_counter);
             self.game_object_id_counter += 1;
         }
         self.game_objects.write().await.extend(game_objects);       
+        first_id..self.game_object_id_counter
     }
 
     pub(super)  fn add_logic_object(&mut self, mut logic_object: Box<dyn LogicObject>){
@@ -214,11 +407,13 @@ impl Model{
 
     pub(super) async fn clear_game_objects(&mut self){
         self.game_objects.write().await.clear();
+        self.selection.clear();     //the ids are handed out again from 0
         self.game_object_id_counter = 0;
     }
 
     pub(super) fn clear_logic_objects(&mut self){
         self.logic_objects.clear();
+        self.scripts.clear();
         self.logic_object_id_counter = 0;
     }
 
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::{controller::position::Position, rendering::{sprite_instance::SpriteTransform, depth::RenderLayer, sprites::vertex_configration::VertexConfigHandle, text::layout::TextContent}};
+
+//shared between the model, which replaces the snapshot after every tick, and the CamOrganizer, which draws the newest one. Neither of them ever waits for the other
+pub(crate) type SharedRenderSnapshot = Arc<ArcSwap<RenderSnapshot>>;
+
+//everything the CamOrganizer needs to draw one visible game object, copied out of the object at the end of a tick
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderItem{
+    pub(crate) position: Position,
+    pub(crate) previous_position: Position,     //at the end of the tick before, for interpolation
+    pub(crate) texture_id: u32,
+    pub(crate) frame: u32,
+    pub(crate) transform: SpriteTransform,
+    pub(crate) layer: RenderLayer,
+    pub(crate) vertex_conf: VertexConfigHandle,
+}
+
+//a visible Text, the CamOrganizer lays out its glyphs
+#[derive(Debug, Clone)]
+pub(crate) struct TextItem{
+    pub(crate) position: Position,
+    pub(crate) previous_position: Position,
+    pub(crate) text: TextContent,
+}
+
+//the visible game objects after one tick, it is never changed after it was published
+#[derive(Debug, Default)]
+pub(crate) struct RenderSnapshot{
+    pub(crate) tick: u64,   //the tick of the TickClock this snapshot belongs to
+    pub(crate) items: Vec<RenderItem>,
+    pub(crate) texts: Vec<TextItem>,
+}
+
+pub(crate) fn new_shared_render_snapshot() -> SharedRenderSnapshot{
+    Arc::new(ArcSwap::from_pointee(RenderSnapshot::default()))
+}
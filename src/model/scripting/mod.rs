@@ -0,0 +1,3 @@
+pub mod script;
+pub mod scripted_logic_object;
+pub mod scripted_controller;
\ No newline at end of file
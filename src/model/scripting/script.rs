@@ -0,0 +1,130 @@
+use std::{path::{Path, PathBuf}, fmt::{Debug, Display}};
+
+use flume::Sender;
+use rhai::{Engine, AST, Scope, Dynamic, Map, FuncArgs, CallFnOptions, EvalAltResult, ParseError};
+
+use crate::controller::input::ControllerInput;
+
+//a script that loops forever would stop the whole model, so every call is aborted after this many operations
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_EXPRESSION_DEPTH: usize = 256;
+
+#[derive(Debug)]
+pub(crate) enum ScriptError{
+    Io{ path: PathBuf, error: std::io::Error },
+    Compile{ path: PathBuf, error: ParseError },
+    Run{ path: PathBuf, error: Box<EvalAltResult> },
+    MissingFunction{ path: PathBuf, function: &'static str },
+}
+
+impl Display for ScriptError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            ScriptError::Io{ path, error } => write!(f, "could not read {}: {}", path.display(), error),
+            ScriptError::Compile{ path, error } => write!(f, "{} does not compile: {}", path.display(), error),
+            ScriptError::Run{ path, error } => write!(f, "{} failed: {}", path.display(), error),
+            ScriptError::MissingFunction{ path, function } => write!(f, "{} has no {}", path.display(), function),
+        }
+    }
+}
+
+//where scripts report to: always the log and the console, if there is one
+#[derive(Clone)]
+pub(crate) struct ScriptLog{
+    console_sender: Option<Sender<ControllerInput>>,
+}
+
+impl ScriptLog{
+    pub(crate) fn new(console_sender: Option<Sender<ControllerInput>>) -> Self{
+        Self{ console_sender }
+    }
+
+    pub(crate) fn print(&self, message: String){
+        println!("{}", message);
+        if let Some(sender) = &self.console_sender{
+            let _ = sender.send(ControllerInput::ConsoleOutput(message));
+        }
+    }
+}
+
+//the bindings that every script gets, the scripted objects register their own ones on top of it
+pub(crate) fn new_engine(log: &ScriptLog) -> Engine{
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_expr_depths(MAX_EXPRESSION_DEPTH, MAX_EXPRESSION_DEPTH);     //the defaults of debug builds already refuse a loop in an if in a function
+    let print_log = log.clone();
+    engine.on_print(move |text| print_log.print(text.to_string()));
+    engine
+}
+
+//the number arguments of the bindings, so scripts can write 3 as well as 3.0
+pub(crate) fn number(value: &Dynamic) -> Result<f32, Box<EvalAltResult>>{
+    if let Ok(float) = value.as_float(){
+        return Ok(float as f32);
+    }
+    if let Ok(int) = value.as_int(){
+        return Ok(int as f32);
+    }
+    Err(format!("expected a number, but got {}", value.type_name()).into())
+}
+
+//a compiled script file. Its top level statements run once when it is loaded, after that only its callbacks are called
+pub(crate) struct Script{
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    state: Dynamic,     //`this` inside of the callbacks, scripts keep everything there that has to survive until the next call
+    log: ScriptLog,
+    failed: bool,       //a script that ran into an error is not called anymore, otherwise it would report the same error every tick
+}
+
+impl Debug for Script{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Script").field("path", &self.path).field("failed", &self.failed).finish()
+    }
+}
+
+impl Script{
+    //on_start() is called right after the top level statements, if the script has one
+    pub(crate) fn load(path: &Path, engine: Engine, log: ScriptLog) -> Result<Self, ScriptError>{
+        let source = std::fs::read_to_string(path).map_err(|error| ScriptError::Io{ path: path.to_path_buf(), error })?;
+        let ast = engine.compile(source).map_err(|error| ScriptError::Compile{ path: path.to_path_buf(), error })?;
+        engine.run_ast_with_scope(&mut Scope::new(), &ast).map_err(|error| ScriptError::Run{ path: path.to_path_buf(), error })?;
+        let mut script = Self{
+            path: path.to_path_buf(),
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            log,
+            failed: false,
+        };
+        if script.has_function("on_start", 0){
+            script.call("on_start", ());
+        }
+        Ok(script)
+    }
+
+    pub(crate) fn path(&self) -> &Path{
+        &self.path
+    }
+
+    pub(crate) fn has_function(&self, name: &str, parameters: usize) -> bool{
+        self.ast.iter_functions().any(|function| function.name == name && function.params.len() == parameters)
+    }
+
+    //returns None once the script failed, the error is only reported the first time
+    pub(crate) fn call(&mut self, name: &str, arguments: impl FuncArgs) -> Option<Dynamic>{
+        if self.failed{
+            return None;
+        }
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.state);
+        match self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, arguments){
+            Ok(result) => Some(result),
+            Err(error) => {
+                self.failed = true;
+                self.log.print(format!("{} failed in {}: {}, the script is stopped", self.path.display(), name, error));
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::{path::Path, sync::{Arc, Mutex}, time::Duration};
+
+use flume::Sender;
+use rhai::{Engine, Dynamic, Map, Array, EvalAltResult, INT, FLOAT};
+
+use crate::{game_objects::buildings::start_obj::{WareAmount, WareType, UnitType, ALL_WARE_TYPES}, model::strategy_test::{faction::{FactionController, FactionSnapshot, FactionOrder}, worker::WorkerJob}, controller::{position::Position, input::ControllerInput}};
+
+use super::script::{Script, ScriptLog, ScriptError, new_engine, number};
+
+const DECISION_INTERVAL_SECS: f32 = 1.0;
+
+//a faction controller whose decisions are made by a script. Its on_control(faction) is called once per decision interval with the snapshot of the faction as a map
+//and gives its orders through gather(worker_id, ware, x, y), queue_worker(base_index) and place_base(base_index, x, y)
+#[derive(Debug)]
+pub(crate) struct ScriptedController{
+    script: Script,
+    orders: Arc<Mutex<Vec<FactionOrder>>>,     //shared with the bindings of the script
+    next_decision: f32,
+}
+
+impl ScriptedController{
+    pub(crate) fn load(path: &Path, console_sender: Option<Sender<ControllerInput>>) -> Result<Self, ScriptError>{
+        let log = ScriptLog::new(console_sender);
+        let orders = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = new_engine(&log);
+        register_orders(&mut engine, orders.clone());
+        let script = Script::load(path, engine, log)?;
+        if !script.has_function("on_control", 1){
+            return Err(ScriptError::MissingFunction{ path: path.to_path_buf(), function: "on_control(faction)" });
+        }
+        Ok(Self{
+            script,
+            orders,
+            next_decision: 0.0,
+        })
+    }
+}
+
+impl FactionController for ScriptedController{
+    fn tick(&mut self, delta_time: Duration) -> bool {
+        self.next_decision -= delta_time.as_secs_f32();
+        if self.next_decision > 0.0{
+            return false;
+        }
+        self.next_decision = DECISION_INTERVAL_SECS;
+        true
+    }
+
+    fn control(&mut self, snapshot: &FactionSnapshot) -> Vec<FactionOrder> {
+        self.script.call("on_control", (snapshot_map(snapshot),));
+        std::mem::take(&mut *self.orders.lock().unwrap())
+    }
+}
+
+fn register_orders(engine: &mut Engine, orders: Arc<Mutex<Vec<FactionOrder>>>){
+    let gather_orders = orders.clone();
+    engine.register_fn("gather", move |worker_id: INT, ware: &str, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let ware_type = WareType::from_name(ware).ok_or_else(|| format!("there is no ware \"{}\"", ware))?;
+        let job = WorkerJob::Gather { ware_type, site: (number(&x)?, number(&y)?) };
+        gather_orders.lock().unwrap().push(FactionOrder::AssignJob { worker_id: worker_id as u64, job });
+        Ok(())
+    });
+    let queue_orders = orders.clone();
+    engine.register_fn("queue_worker", move |base_index: INT| {
+        queue_orders.lock().unwrap().push(FactionOrder::QueueUnit { base_index: base_index as usize, unit: UnitType::Worker });
+    });
+    engine.register_fn("place_base", move |base_index: INT, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let position = Position::new(number(&x)?, number(&y)?);
+        orders.lock().unwrap().push(FactionOrder::PlaceBuilding { base_index: base_index as usize, position });
+        Ok(())
+    });
+}
+
+//#{ id, wares, bases: [#{ x, y, wares, queued_units }], workers: [#{ id, x, y, state, job }], resources: [#{ ware, x, y }] }, wares are maps like #{ food: 25, wood: 0, stone: 0 }
+fn snapshot_map(snapshot: &FactionSnapshot) -> Map{
+    let bases: Array = snapshot.bases.iter().map(|base| {
+        let mut map = position_map(&base.position);
+        map.insert("wares".into(), Dynamic::from_map(wares_map(&base.wares)));
+        map.insert("queued_units".into(), Dynamic::from_int(base.queued_units as INT));
+        Dynamic::from_map(map)
+    }).collect();
+    let workers: Array = snapshot.workers.iter().map(|worker| {
+        let mut map = position_map(&worker.position);
+        map.insert("id".into(), Dynamic::from_int(worker.id as INT));
+        map.insert("state".into(), format!("{:?}", worker.state).to_lowercase().into());
+        let job = match worker.job{
+            Some(WorkerJob::Gather { ware_type, .. }) => ware_type.name().into(),
+            None => Dynamic::UNIT,
+        };
+        map.insert("job".into(), job);
+        Dynamic::from_map(map)
+    }).collect();
+    let resources: Array = snapshot.resources.iter().map(|resource| {
+        let mut map = position_map(&resource.position);
+        map.insert("ware".into(), resource.ware_type.name().into());
+        Dynamic::from_map(map)
+    }).collect();
+    let all_wares: Vec<WareAmount> = snapshot.bases.iter().flat_map(|base| base.wares.iter().copied()).collect();
+
+    let mut map = Map::new();
+    map.insert("id".into(), Dynamic::from_int(snapshot.faction as INT));
+    map.insert("wares".into(), Dynamic::from_map(wares_map(&all_wares)));
+    map.insert("bases".into(), Dynamic::from_array(bases));
+    map.insert("workers".into(), Dynamic::from_array(workers));
+    map.insert("resources".into(), Dynamic::from_array(resources));
+    map
+}
+
+fn position_map(position: &Position) -> Map{
+    let mut map = Map::new();
+    map.insert("x".into(), Dynamic::from_float(position.x as FLOAT));
+    map.insert("y".into(), Dynamic::from_float(position.y as FLOAT));
+    map
+}
+
+//every ware type is in the map, the ones the faction has none of with 0. Amounts of the same ware type are added up
+fn wares_map(wares: &[WareAmount]) -> Map{
+    ALL_WARE_TYPES.iter().map(|ware_type| {
+        let amount: u32 = wares.iter().filter(|ware| ware.ware_type == *ware_type as u32).map(|ware| ware.amount).sum();
+        (ware_type.name().into(), Dynamic::from_int(amount as INT))
+    }).collect()
+}
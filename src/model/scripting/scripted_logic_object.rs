@@ -0,0 +1,151 @@
+use std::{path::Path, sync::{Arc, Mutex}, time::Duration};
+
+use flume::Sender;
+use rhai::{Engine, Dynamic, Array, EvalAltResult, INT, FLOAT};
+use tokio::sync::RwLock;
+
+use crate::{game_objects::{game_object::{LogicObject, DrawableObject}, buildings::debug_house::DebugHouse, text::Text}, model::{model::{Model, GameObjectList}, results::{LogicResult, GameObjects}}, rendering::{sprites::{sprite_mapping::Sprite, dynamic_vertex_configuration::{register_rectangle, MAX_DYNAMIC_VERTEX_CONFIGURATIONS}}, text::layout::TextSpace}, controller::{position::Position, input::ControllerInput}};
+
+use super::script::{Script, ScriptLog, ScriptError, new_engine, number};
+
+//what the bindings did during a call, it is handed to the model as the result of the tick
+#[derive(Debug, Default)]
+struct ScriptChanges{
+    created: GameObjects,
+    destroyed: Vec<u64>,
+}
+
+//a game object as scripts see it. The model only gives it an id once it is added, which happens at the end of the tick it was spawned in
+#[derive(Clone)]
+struct ObjectHandle(Arc<RwLock<dyn DrawableObject + Send + Sync>>);
+
+//a logic object whose behavior is a script, so levels can be prototyped without recompiling. The script's on_tick(delta_time) is called every tick, delta_time is in seconds
+#[derive(Debug)]
+pub(crate) struct ScriptedLogicObject{
+    script: Script,
+    changes: Arc<Mutex<ScriptChanges>>,     //shared with the bindings of the script
+    has_on_tick: bool,
+    id: u32,
+}
+
+impl ScriptedLogicObject{
+    //game_objects is the list of the model, scripts can look up every object in it
+    pub(crate) fn load(path: &Path, game_objects: GameObjectList, console_sender: Sender<ControllerInput>) -> Result<Self, ScriptError>{
+        let log = ScriptLog::new(Some(console_sender));
+        let changes = Arc::new(Mutex::new(ScriptChanges::default()));
+        let mut engine = new_engine(&log);
+        register_world(&mut engine, game_objects, changes.clone());
+        let script = Script::load(path, engine, log)?;
+        Ok(Self{
+            has_on_tick: script.has_function("on_tick", 1),
+            script,
+            changes,
+            id: 0,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path{
+        self.script.path()
+    }
+}
+
+impl LogicObject for ScriptedLogicObject{
+    fn process_logic(&mut self, delta_time: Duration) -> LogicResult {
+        if self.has_on_tick{
+            self.script.call("on_tick", (delta_time.as_secs_f64() as FLOAT,));
+        }
+        //the objects of the top level statements are picked up in the first tick as well
+        let changes = std::mem::take(&mut *self.changes.lock().unwrap());
+        if changes.created.is_empty() && changes.destroyed.is_empty(){
+            return LogicResult::None;
+        }
+        LogicResult::CreateAndDestroyGameObjects { game_objects_to_create: changes.created, game_objects_to_destroy: changes.destroyed }
+    }
+
+    fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+//spawn_sprite(sprite, x, y), spawn_sprite(sprite, x, y, width, height), spawn_text(content, x, y, size), destroy(object or id), position(id), objects() and the id, x and y of objects
+fn register_world(engine: &mut Engine, game_objects: GameObjectList, changes: Arc<Mutex<ScriptChanges>>){
+    engine.register_type_with_name::<ObjectHandle>("Object")
+        .register_get("id", |object: &mut ObjectHandle| object.0.blocking_read().get_id() as INT)
+        .register_get("x", |object: &mut ObjectHandle| object.0.blocking_read().get_position().x as FLOAT)
+        .register_get("y", |object: &mut ObjectHandle| object.0.blocking_read().get_position().y as FLOAT);
+
+    let spawn_changes = changes.clone();
+    engine.register_fn("spawn_sprite", move |sprite: &str, x: Dynamic, y: Dynamic| {
+        create_sprite(&spawn_changes, sprite, [x, y], [Dynamic::from_float(1.0), Dynamic::from_float(1.0)])
+    });
+    let spawn_changes = changes.clone();
+    engine.register_fn("spawn_sprite", move |sprite: &str, x: Dynamic, y: Dynamic, width: Dynamic, height: Dynamic| {
+        create_sprite(&spawn_changes, sprite, [x, y], [width, height])
+    });
+    let text_changes = changes.clone();
+    //spawn is a keyword of rhai, so the names say what is spawned
+    engine.register_fn("spawn_text", move |content: &str, x: Dynamic, y: Dynamic, size: Dynamic| -> Result<ObjectHandle, Box<EvalAltResult>> {
+        let text = Text::new(content, Position::new(number(&x)?, number(&y)?), number(&size)?, TextSpace::World);
+        Ok(add_created(&text_changes, Arc::new(RwLock::new(text))))
+    });
+
+    //an object that was spawned in the same tick never reaches the model, it has no id yet
+    let destroy_changes = changes.clone();
+    engine.register_fn("destroy", move |object: ObjectHandle| {
+        let mut changes = destroy_changes.lock().unwrap();
+        let pending = changes.created.len();
+        changes.created.retain(|created| !Arc::ptr_eq(created, &object.0));
+        if changes.created.len() == pending{
+            let id = object.0.blocking_read().get_id();
+            changes.destroyed.push(id);
+        }
+    });
+    let destroy_changes = changes;
+    engine.register_fn("destroy", move |id: INT| destroy_changes.lock().unwrap().destroyed.push(id as u64));
+
+    let position_objects = game_objects.clone();
+    engine.register_fn("position", move |id: INT| -> Dynamic {
+        let game_objects = position_objects.blocking_read();
+        for game_object in game_objects.iter(){
+            let lock = game_object.blocking_read();
+            if lock.get_id() == id as u64{
+                let position = lock.get_position();
+                return Dynamic::from_array(vec![Dynamic::from_float(position.x as FLOAT), Dynamic::from_float(position.y as FLOAT)]);
+            }
+        }
+        Dynamic::UNIT
+    });
+    engine.register_fn("objects", move || -> Array {
+        game_objects.blocking_read().iter().map(|game_object| Dynamic::from_int(game_object.blocking_read().get_id() as INT)).collect()
+    });
+}
+
+impl Model{
+    //a script that doesn't load is reported and the level keeps running without it
+    pub(crate) fn run_script(&mut self, path: &Path){
+        match ScriptedLogicObject::load(path, self.game_objects.clone(), self.console_sender.clone()){
+            Ok(scripted) => {
+                self.console_output(format!("Running {}", scripted.path().display()));
+                self.scripts.push(path.to_path_buf());
+                self.add_logic_object(Box::new(scripted));
+            }
+            Err(error) => self.console_output(format!("Could not run the script: {}", error)),
+        }
+    }
+}
+
+fn create_sprite(changes: &Mutex<ScriptChanges>, sprite: &str, position: [Dynamic; 2], size: [Dynamic; 2]) -> Result<ObjectHandle, Box<EvalAltResult>>{
+    let texture = Sprite::from_name(sprite).ok_or_else(|| format!("there is no sprite \"{}\"", sprite))?;
+    let vertices = register_rectangle(number(&size[0])?, number(&size[1])?).ok_or_else(|| format!("there are already {} different sprite sizes", MAX_DYNAMIC_VERTEX_CONFIGURATIONS))?;
+    let house = DebugHouse::new(texture, Position::new(number(&position[0])?, number(&position[1])?), vertices);
+    Ok(add_created(changes, Arc::new(RwLock::new(house))))
+}
+
+fn add_created(changes: &Mutex<ScriptChanges>, game_object: Arc<RwLock<dyn DrawableObject + Send + Sync>>) -> ObjectHandle{
+    changes.lock().unwrap().created.push(game_object.clone());
+    ObjectHandle(game_object)
+}
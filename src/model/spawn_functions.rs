@@ -10,6 +10,6 @@ impl Model{
 
     pub(crate) async fn spawn_house_at_position(&mut self, pos: (f32, f32)){
         let house = DebugHouse::new(Sprite::DwarfBaseHouse, Position::new(pos.0, pos.1), VertexConfigration::SQUARE_SMALL_1);
-        self.add_game_object(Arc::new(RwLock::new(house)));
+        self.add_game_object(Arc::new(RwLock::new(house))).await;
     }
 }
\ No newline at end of file
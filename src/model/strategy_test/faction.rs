@@ -0,0 +1,165 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use tokio::sync::RwLock;
+
+use crate::{game_objects::{buildings::start_obj::{StartObj, WareAmount, WareType, UnitType}, game_object::DrawableObject, resource_deposit::ResourceDeposit}, controller::position::Position};
+
+use super::{worker::{Worker, WorkerJob, WorkerState}, strategy_test::StrategyLogicObject, visibility::VisibilityGrid, map_chunk::ChunkInfo};
+
+pub(crate) type FactionId = u32;
+
+//the faction that is controlled from the keyboard and whose vision is shown on screen
+pub(crate) const PLAYER_FACTION: FactionId = 0;
+
+//the units of a faction are tinted with its color, factions without an own color use the last one
+const FACTION_TINTS: [[f32; 4]; 3] = [
+    [1.0, 1.0, 1.0, 1.0],
+    [1.0, 0.45, 0.45, 1.0],
+    [1.0, 0.9, 0.4, 1.0],
+];
+
+pub(crate) fn faction_tint(faction: FactionId) -> [f32; 4]{
+    FACTION_TINTS[(faction as usize).min(FACTION_TINTS.len() - 1)]
+}
+
+//a faction groups everything that belongs to one side of a match. The controller decides what the faction does, a faction without a controller stays idle
+#[derive(Debug)]
+pub(crate) struct Faction{
+    pub(crate) id: FactionId,
+    pub(crate) bases: Vec<Arc<RwLock<StartObj>>>,
+    pub(crate) workers: Vec<Arc<RwLock<Worker>>>,
+    pub(crate) controller: Option<Box<dyn FactionController>>,
+    pub(crate) visibility: VisibilityGrid,
+}
+
+//this is everything a FactionController gets to see of its own faction. It is rebuilt every time the controller is asked, so it never holds any locks
+#[derive(Debug)]
+pub(crate) struct FactionSnapshot{
+    pub(crate) faction: FactionId,
+    pub(crate) bases: Vec<BaseInfo>,
+    pub(crate) workers: Vec<WorkerInfo>,
+    pub(crate) resources: Vec<ResourceInfo>,
+}
+
+#[derive(Debug)]
+pub(crate) struct BaseInfo{
+    pub(crate) position: Position,
+    pub(crate) wares: Vec<WareAmount>,
+    pub(crate) queued_units: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct ResourceInfo{
+    pub(crate) ware_type: WareType,
+    pub(crate) position: Position,
+}
+
+#[derive(Debug)]
+pub(crate) struct WorkerInfo{
+    pub(crate) id: u64,
+    pub(crate) position: Position,
+    pub(crate) state: WorkerState,
+    pub(crate) job: Option<WorkerJob>,
+    pub(crate) home_base: Option<usize>,     //the index of the base the worker brings its wares to
+}
+
+//orders are only suggestions, the StratLevel checks them and ignores the ones that are not possible (unknown worker, blocked or unexplored position, missing wares, ...)
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FactionOrder{
+    AssignJob{ worker_id: u64, job: WorkerJob },
+    PlaceBuilding{ base_index: usize, position: Position },
+    QueueUnit{ base_index: usize, unit: UnitType },
+}
+
+//this is what drives a faction. StratLevel calls tick() once per tick and only builds a snapshot and asks for orders if tick() returns true, since building the snapshot has to lock every unit of the faction
+pub(crate) trait FactionController: Debug{
+    fn tick(&mut self, delta_time: Duration) -> bool;
+    fn control(&mut self, snapshot: &FactionSnapshot) -> Vec<FactionOrder>;
+}
+
+pub(crate) const BUILDING_COSTS: [WareAmount; 2] = [
+    WareAmount{ ware_type: WareType::Wood as u32, amount: 60 },
+    WareAmount{ ware_type: WareType::Stone as u32, amount: 40 },
+];
+
+impl Faction{
+    pub(crate) fn new(id: FactionId, controller: Option<Box<dyn FactionController>>, borders: &ChunkInfo) -> Self{
+        Self{
+            id,
+            bases: Vec::new(),
+            workers: Vec::new(),
+            controller,
+            visibility: VisibilityGrid::new(borders),
+        }
+    }
+
+    //moves every viewer of the faction to its current position, only viewers that changed their cell actually touch the grid
+    pub(crate) fn update_visibility(&mut self){
+        for base in &self.bases{
+            let lock = base.blocking_read();
+            self.visibility.update_viewer(StrategyLogicObject::get_id(&*lock), &lock.position, lock.get_sight_radius());
+        }
+        for worker in &self.workers{
+            let lock = worker.blocking_read();
+            self.visibility.update_viewer(StrategyLogicObject::get_id(&*lock), &lock.get_position(), lock.get_sight_radius());
+        }
+    }
+
+    pub(crate) fn snapshot(&self, resources: &[Arc<RwLock<ResourceDeposit>>]) -> FactionSnapshot{
+        let bases = self.bases.iter().map(|base| {
+            let lock = base.blocking_read();
+            BaseInfo{
+                position: lock.position,
+                wares: lock.wares.clone(),
+                queued_units: lock.get_production_queue_length(),
+            }
+        }).collect();
+        let workers = self.workers.iter().map(|worker| {
+            let lock = worker.blocking_read();
+            WorkerInfo{
+                id: StrategyLogicObject::get_id(&*lock),
+                position: lock.get_position(),
+                state: lock.get_state(),
+                job: lock.get_job(),
+                home_base: lock.get_home().and_then(|home| self.bases.iter().position(|base| Arc::ptr_eq(base, home))),
+            }
+        }).collect();
+        //a controller only gets to know about the resources its faction has already explored
+        let resources = resources.iter().filter_map(|deposit| {
+            let lock = deposit.blocking_read();
+            if !self.visibility.is_explored(&lock.position){
+                return None;
+            }
+            Some(ResourceInfo{
+                ware_type: lock.ware_type,
+                position: lock.position,
+            })
+        }).collect();
+        FactionSnapshot{
+            faction: self.id,
+            bases,
+            workers,
+            resources,
+        }
+    }
+
+    pub(crate) fn total_wares(&self) -> Vec<WareAmount>{
+        let mut ret: Vec<WareAmount> = Vec::new();
+        for base in &self.bases{
+            for ware in &base.blocking_read().wares{
+                if let Some(existing) = ret.iter_mut().find(|w| w.ware_type == ware.ware_type){
+                    existing.amount += ware.amount;
+                }else{
+                    ret.push(*ware);
+                }
+            }
+        }
+        ret
+    }
+}
+
+impl BaseInfo{
+    pub(crate) fn get_ware_amount(&self, ware_type: WareType) -> u32{
+        self.wares.iter().find(|w| w.ware_type == ware_type as u32).map(|w| w.amount).unwrap_or(0)
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use async_std::task::block_on;
+
+use crate::game_objects::game_object::LogicObject;
+
+use super::{strategy_test::StratLevel, map_chunk::{ChunkInfo, IntEdge}};
+
+const TICK_RATE: f64 = 60.0;
+const SUMMARY_INTERVAL_TICKS: u32 = 600;
+
+//runs the strategy level without any window, renderer or model thread, so AI controlled factions can play against each other as fast as possible
+//the level is advanced with a fixed delta time, so a match is as long as it would be in real time, no matter how fast the machine is
+pub(crate) fn run_headless_match(ticks: u32, seed: Option<u64>){
+    let borders = ChunkInfo{
+        bottom_left: IntEdge::new(-100, -100),
+        top_right: IntEdge::new(100, 100),
+    };
+    let mut level = match seed{
+        Some(seed) => StratLevel::with_seed(borders, seed),
+        None => StratLevel::new(borders),
+    };
+    let _ = block_on(level.initialize());   //there is nothing to draw, so the game objects can be dropped right away
+
+    let delta_time = Duration::from_secs_f64(1.0 / TICK_RATE);
+    for tick in 0..ticks{
+        let _ = level.process_logic(delta_time);
+        if tick % SUMMARY_INTERVAL_TICKS == 0{
+            println!("--- tick {} ---", tick);
+            level.print_faction_summary();
+        }
+    }
+    println!("--- finished after {} ticks ({:.1}s of game time) ---", ticks, ticks as f64 / TICK_RATE);
+    level.print_faction_summary();
+}
@@ -2,12 +2,12 @@ use std::fmt;
 
 use crate::controller::position::Position;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct IntEdge{
     pub(crate) x: i32,
     pub(crate) y: i32,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct ChunkInfo{
     pub(crate) bottom_left: IntEdge,
     pub(crate) top_right: IntEdge,
@@ -29,8 +29,9 @@ impl IntEdge{
 
 impl ChunkInfo{
     pub(crate) fn new(x: f32, y: f32, width: f32, height: f32) -> Self{
-        let bottom_left = IntEdge::new((x - width / 2.0) as i32, (y - height / 2.0) as i32);
-        let top_right = IntEdge::new((x + width / 2.0) as i32, (y + height / 2.0) as i32);
+        //floored like the tiles of the pathfinding, truncating would make the chunks around 0 a tile larger than everywhere else
+        let bottom_left = IntEdge::new((x - width / 2.0).floor() as i32, (y - height / 2.0).floor() as i32);
+        let top_right = IntEdge::new((x + width / 2.0).floor() as i32, (y + height / 2.0).floor() as i32);
         Self{
             bottom_left,
             top_right,
@@ -38,9 +39,17 @@ impl ChunkInfo{
     }
 
     pub(crate) fn contains(&self, position: &Position) -> bool{
-        let x = position.x as i32;
-        let y = position.y as i32;
+        let x = position.x.floor() as i32;
+        let y = position.y.floor() as i32;
         x >= self.bottom_left.x && x <= self.top_right.x && y >= self.bottom_left.y && y <= self.top_right.y
     }
+
+    pub(crate) fn intersects(&self, other: &ChunkInfo) -> bool{
+        self.bottom_left.x <= other.top_right.x && self.top_right.x >= other.bottom_left.x && self.bottom_left.y <= other.top_right.y && self.top_right.y >= other.bottom_left.y
+    }
+
+    pub(crate) fn contains_chunk(&self, other: &ChunkInfo) -> bool{
+        other.bottom_left.x >= self.bottom_left.x && other.top_right.x <= self.top_right.x && other.bottom_left.y >= self.bottom_left.y && other.top_right.y <= self.top_right.y
+    }
 }
 
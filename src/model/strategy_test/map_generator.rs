@@ -0,0 +1,387 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, SeedableRng, Rng};
+
+use crate::{controller::position::Position, game_objects::{buildings::start_obj::WareType, resource_deposit::ResourceDeposit}, rendering::tilemap::{TileMapData, TileTexture}};
+
+use super::map_chunk::{MapChunk, ChunkInfo, IntEdge};
+
+pub(crate) const TERRAIN_CELL_SIZE: i32 = 4;        //size of a single terrain cell in world units, neighboring cells of the same terrain in a row are merged into one chunk
+const NOISE_LATTICE_CELLS: usize = 6;              //distance between the random values of the noise, the larger, the larger the lakes, forests, etc. get
+const BASE_CLEARING_RADIUS: f32 = 14.0;            //the area around a base is always grass, so there is room for the initial workers
+const RESOURCES_PER_WARE_TYPE: usize = 12;
+const RESOURCE_DISTANCE_TO_BASES: (f32, f32) = (8.0, 60.0);
+const RESOURCE_PLACEMENT_ATTEMPTS: usize = 2000;
+
+//the blocking and the non blocking chunks of a map
+type ChunkLists = (Vec<Box<dyn MapChunk>>, Vec<Box<dyn MapChunk>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TerrainType{
+    Grass,
+    Forest,
+    Water,
+    Rock,
+}
+
+impl TerrainType{
+    pub(crate) fn is_blocking(&self) -> bool{
+        match self{
+            TerrainType::Water | TerrainType::Rock => true,
+            TerrainType::Grass | TerrainType::Forest => false,
+        }
+    }
+
+    pub(crate) fn get_tile_texture(&self) -> TileTexture{
+        match self{
+            TerrainType::Grass => TileTexture::Grass,
+            TerrainType::Forest => TileTexture::Forest,
+            TerrainType::Water => TileTexture::Water,
+            TerrainType::Rock => TileTexture::Rock,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TerrainChunk{
+    pub(crate) info: ChunkInfo,      //the terrain type is only needed to decide whether the chunk is blocking or not, which list it ends up in says that already
+}
+
+impl MapChunk for TerrainChunk{
+    fn identify(&self) -> String {
+        format!("terrain {:?}", self.info)
+    }
+
+    fn inf(&self) -> ChunkInfo {
+        self.info
+    }
+}
+
+//the terrain of the whole level, cell (0, 0) is the bottom left corner of the level borders
+#[derive(Debug)]
+pub(crate) struct TerrainGrid{
+    pub(crate) origin: IntEdge,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    cells: Vec<TerrainType>,
+}
+
+#[derive(Debug)]
+pub(crate) struct GeneratedMap{
+    pub(crate) terrain: TerrainGrid,
+    pub(crate) blocking_chunks: Vec<Box<dyn MapChunk>>,
+    pub(crate) non_blocking_chunks: Vec<Box<dyn MapChunk>>,
+    pub(crate) resources: Vec<ResourceDeposit>,
+}
+
+//generates the same map for the same seed, borders and bases
+pub(crate) struct MapGenerator{
+    seed: u64,
+}
+
+impl TerrainGrid{
+    fn new(borders: &ChunkInfo) -> Self{
+        let width = ((borders.top_right.x - borders.bottom_left.x) / TERRAIN_CELL_SIZE).max(1) as usize;
+        let height = ((borders.top_right.y - borders.bottom_left.y) / TERRAIN_CELL_SIZE).max(1) as usize;
+        Self{
+            origin: borders.bottom_left,
+            width,
+            height,
+            cells: vec![TerrainType::Grass; width * height],
+        }
+    }
+
+    pub(crate) fn get(&self, x: usize, y: usize) -> TerrainType{
+        self.cells[y * self.width + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, terrain: TerrainType){
+        self.cells[y * self.width + x] = terrain;
+    }
+
+    pub(crate) fn cell_of(&self, position: &Position) -> Option<(usize, usize)>{
+        let x = ((position.x - self.origin.x as f32) / TERRAIN_CELL_SIZE as f32).floor();
+        let y = ((position.y - self.origin.y as f32) / TERRAIN_CELL_SIZE as f32).floor();
+        if x < 0.0 || y < 0.0 || x as usize >= self.width || y as usize >= self.height{
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    pub(crate) fn cell_center(&self, x: usize, y: usize) -> Position{
+        Position::new(
+            self.origin.x as f32 + (x as f32 + 0.5) * TERRAIN_CELL_SIZE as f32,
+            self.origin.y as f32 + (y as f32 + 0.5) * TERRAIN_CELL_SIZE as f32,
+        )
+    }
+
+    //the chunk covering the cells from_x..=to_x in row y. The top right edge is inclusive, so it is one less than the start of the next cell, otherwise neighboring chunks would overlap
+    pub(crate) fn cells_chunk(&self, from_x: usize, to_x: usize, y: usize) -> ChunkInfo{
+        ChunkInfo{
+            bottom_left: IntEdge::new(self.origin.x + from_x as i32 * TERRAIN_CELL_SIZE, self.origin.y + y as i32 * TERRAIN_CELL_SIZE),
+            top_right: IntEdge::new(self.origin.x + (to_x as i32 + 1) * TERRAIN_CELL_SIZE - 1, self.origin.y + (y as i32 + 1) * TERRAIN_CELL_SIZE - 1),
+        }
+    }
+
+    pub(crate) fn to_tile_map_data(&self) -> TileMapData{
+        TileMapData{
+            origin: [self.origin.x as f32, self.origin.y as f32],
+            cell_size: TERRAIN_CELL_SIZE as f32,
+            width: self.width,
+            height: self.height,
+            tiles: self.cells.iter().map(|cell| cell.get_tile_texture() as u32).collect(),
+        }
+    }
+
+    fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)>{
+        let mut ret = Vec::with_capacity(4);
+        if x > 0 { ret.push((x - 1, y)); }
+        if y > 0 { ret.push((x, y - 1)); }
+        if x + 1 < self.width { ret.push((x + 1, y)); }
+        if y + 1 < self.height { ret.push((x, y + 1)); }
+        ret
+    }
+
+    //flood fill over all non blocking cells, starting at the given cell
+    pub(crate) fn reachable_from(&self, start: (usize, usize)) -> Vec<bool>{
+        let mut reached = vec![false; self.width * self.height];
+        if self.get(start.0, start.1).is_blocking(){
+            return reached;
+        }
+        let mut queue = VecDeque::new();
+        reached[start.1 * self.width + start.0] = true;
+        queue.push_back(start);
+        while let Some((x, y)) = queue.pop_front(){
+            for (nx, ny) in self.neighbors(x, y){
+                let index = ny * self.width + nx;
+                if !reached[index] && !self.get(nx, ny).is_blocking(){
+                    reached[index] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        reached
+    }
+
+    //merges neighboring cells of the same terrain in each row, so pathfinding has to check far less chunks
+    fn to_chunks(&self) -> ChunkLists{
+        let mut blocking: Vec<Box<dyn MapChunk>> = Vec::new();
+        let mut non_blocking: Vec<Box<dyn MapChunk>> = Vec::new();
+        for y in 0..self.height{
+            let mut run_start = 0;
+            for x in 1..=self.width{
+                if x < self.width && self.get(x, y) == self.get(run_start, y){
+                    continue;
+                }
+                let terrain = self.get(run_start, y);
+                let chunk = Box::new(TerrainChunk{ info: self.cells_chunk(run_start, x - 1, y) });
+                if terrain.is_blocking(){
+                    blocking.push(chunk);
+                }else{
+                    non_blocking.push(chunk);
+                }
+                run_start = x;
+            }
+        }
+        (blocking, non_blocking)
+    }
+}
+
+impl MapGenerator{
+    pub(crate) fn new(seed: u64) -> Self{
+        Self{
+            seed,
+        }
+    }
+
+    pub(crate) fn generate(&self, borders: &ChunkInfo, bases: &[Position]) -> GeneratedMap{
+        let mut rand = StdRng::seed_from_u64(self.seed);
+        let mut terrain = TerrainGrid::new(borders);
+        let elevation = value_noise(&mut rand, terrain.width, terrain.height);
+        let moisture = value_noise(&mut rand, terrain.width, terrain.height);
+
+        for y in 0..terrain.height{
+            for x in 0..terrain.width{
+                let index = y * terrain.width + x;
+                let cell = if elevation[index] < 0.25{
+                    TerrainType::Water
+                }else if elevation[index] > 0.78{
+                    TerrainType::Rock
+                }else if moisture[index] > 0.6{
+                    TerrainType::Forest
+                }else{
+                    TerrainType::Grass
+                };
+                terrain.set(x, y, cell);
+            }
+        }
+
+        clear_around_bases(&mut terrain, bases);
+        connect_bases(&mut terrain, bases);
+        let resources = place_resources(&mut rand, &terrain, bases);
+        let (blocking_chunks, non_blocking_chunks) = terrain.to_chunks();
+        println!("Generated map with seed {}: {} blocking and {} non blocking chunks, {} resources", self.seed, blocking_chunks.len(), non_blocking_chunks.len(), resources.len());
+
+        GeneratedMap{
+            terrain,
+            blocking_chunks,
+            non_blocking_chunks,
+            resources,
+        }
+    }
+}
+
+//random values on a coarse lattice, smoothly interpolated for every cell. Returns values between 0 and 1
+fn value_noise(rand: &mut StdRng, width: usize, height: usize) -> Vec<f32>{
+    let lattice_width = width / NOISE_LATTICE_CELLS + 2;
+    let lattice_height = height / NOISE_LATTICE_CELLS + 2;
+    let lattice: Vec<f32> = (0..lattice_width * lattice_height).map(|_| rand.gen_range(0.0..1.0)).collect();
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+
+    let mut ret = Vec::with_capacity(width * height);
+    for y in 0..height{
+        for x in 0..width{
+            let lx = x / NOISE_LATTICE_CELLS;
+            let ly = y / NOISE_LATTICE_CELLS;
+            let tx = smooth((x % NOISE_LATTICE_CELLS) as f32 / NOISE_LATTICE_CELLS as f32);
+            let ty = smooth((y % NOISE_LATTICE_CELLS) as f32 / NOISE_LATTICE_CELLS as f32);
+            let bottom = lattice[ly * lattice_width + lx] * (1.0 - tx) + lattice[ly * lattice_width + lx + 1] * tx;
+            let top = lattice[(ly + 1) * lattice_width + lx] * (1.0 - tx) + lattice[(ly + 1) * lattice_width + lx + 1] * tx;
+            ret.push(bottom * (1.0 - ty) + top * ty);
+        }
+    }
+    ret
+}
+
+fn clear_around_bases(terrain: &mut TerrainGrid, bases: &[Position]){
+    for y in 0..terrain.height{
+        for x in 0..terrain.width{
+            let center = terrain.cell_center(x, y);
+            if bases.iter().any(|base| base.distance_to_position(&center) < BASE_CLEARING_RADIUS){
+                terrain.set(x, y, TerrainType::Grass);
+            }
+        }
+    }
+}
+
+//every base that can't be reached from the first one gets a grass corridor to it. The corridor only moves in straight steps, so it is connected for the 4-neighborhood as well
+fn connect_bases(terrain: &mut TerrainGrid, bases: &[Position]){
+    let Some(first) = bases.first().and_then(|base| terrain.cell_of(base)) else{
+        return;
+    };
+    for base in bases.iter().skip(1){
+        let Some(mut current) = terrain.cell_of(base) else{
+            continue;
+        };
+        if terrain.reachable_from(first)[current.1 * terrain.width + current.0]{
+            continue;
+        }
+        while current != first{
+            if current.0 != first.0{
+                current.0 = if current.0 < first.0 { current.0 + 1 } else { current.0 - 1 };
+            }else{
+                current.1 = if current.1 < first.1 { current.1 + 1 } else { current.1 - 1 };
+            }
+            if terrain.get(current.0, current.1).is_blocking(){
+                terrain.set(current.0, current.1, TerrainType::Grass);
+            }
+        }
+    }
+}
+
+//food grows on grass, wood in forests and stone can be found on grass next to rocks. Only cells reachable from the bases are used
+fn place_resources(rand: &mut StdRng, terrain: &TerrainGrid, bases: &[Position]) -> Vec<ResourceDeposit>{
+    let mut ret = Vec::new();
+    let Some(first) = bases.first().and_then(|base| terrain.cell_of(base)) else{
+        return ret;
+    };
+    let reachable = terrain.reachable_from(first);
+    for ware_type in [WareType::Food, WareType::Wood, WareType::Stone]{
+        let mut placed = 0;
+        for _ in 0..RESOURCE_PLACEMENT_ATTEMPTS{
+            if placed >= RESOURCES_PER_WARE_TYPE{
+                break;
+            }
+            let x = rand.gen_range(0..terrain.width);
+            let y = rand.gen_range(0..terrain.height);
+            if !reachable[y * terrain.width + x]{
+                continue;
+            }
+            let center = terrain.cell_center(x, y);
+            let distance = bases.iter().map(|base| base.distance_to_position(&center)).fold(f32::MAX, f32::min);
+            if distance < RESOURCE_DISTANCE_TO_BASES.0 || distance > RESOURCE_DISTANCE_TO_BASES.1{
+                continue;
+            }
+            let suitable = match ware_type{
+                WareType::Food => terrain.get(x, y) == TerrainType::Grass,
+                WareType::Wood => terrain.get(x, y) == TerrainType::Forest,
+                WareType::Stone => terrain.get(x, y) == TerrainType::Grass && terrain.neighbors(x, y).iter().any(|(nx, ny)| terrain.get(*nx, *ny) == TerrainType::Rock),
+            };
+            if !suitable || ret.iter().any(|deposit: &ResourceDeposit| deposit.position.distance_to_position(&center) < 1.0){
+                continue;
+            }
+            ret.push(ResourceDeposit::new(ware_type, center));
+            placed += 1;
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn borders() -> ChunkInfo{
+        ChunkInfo{ bottom_left: IntEdge::new(-100, -100), top_right: IntEdge::new(100, 100) }
+    }
+
+    fn bases() -> [Position; 3]{
+        [Position::new(0.0, 0.0), Position::new(30.0, -20.0), Position::new(-25.0, 25.0)]
+    }
+
+    #[test]
+    fn the_same_seed_generates_the_same_map(){
+        let first = MapGenerator::new(7).generate(&borders(), &bases());
+        let second = MapGenerator::new(7).generate(&borders(), &bases());
+        assert_eq!(first.terrain.cells, second.terrain.cells);
+        let positions = |map: &GeneratedMap| map.resources.iter().map(|deposit| (deposit.ware_type, deposit.position.x, deposit.position.y)).collect::<Vec<_>>();
+        assert_eq!(positions(&first), positions(&second));
+
+        let other = MapGenerator::new(8).generate(&borders(), &bases());
+        assert_ne!(first.terrain.cells, other.terrain.cells);
+    }
+
+    #[test]
+    fn every_base_can_be_reached_from_the_first_one(){
+        for seed in 0..32{
+            let map = MapGenerator::new(seed).generate(&borders(), &bases());
+            let first = map.terrain.cell_of(&bases()[0]).unwrap();
+            let reachable = map.terrain.reachable_from(first);
+            for base in bases(){
+                let (x, y) = map.terrain.cell_of(&base).unwrap();
+                assert_eq!(map.terrain.get(x, y), TerrainType::Grass, "seed {}", seed);
+                assert!(reachable[y * map.terrain.width + x], "base at {:?} can't be reached with seed {}", base, seed);
+            }
+            for deposit in &map.resources{
+                let (x, y) = map.terrain.cell_of(&deposit.position).unwrap();
+                assert!(reachable[y * map.terrain.width + x], "deposit at {:?} can't be reached with seed {}", deposit.position, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn blocking_chunks_cover_exactly_the_blocking_cells(){
+        let map = MapGenerator::new(3).generate(&borders(), &bases());
+        for y in 0..map.terrain.height{
+            for x in 0..map.terrain.width{
+                //just inside of the bottom left and the top right corner, truncating instead of flooring would move the ones below 0 into the next chunk
+                let inset = TERRAIN_CELL_SIZE as f32 / 2.0 - 0.25;
+                let center = map.terrain.cell_center(x, y);
+                for position in [Position::new(center.x - inset, center.y - inset), Position::new(center.x + inset, center.y + inset)]{
+                    let blocked = map.blocking_chunks.iter().any(|chunk| chunk.inf().contains(&position));
+                    assert_eq!(blocked, map.terrain.get(x, y).is_blocking(), "cell ({}, {}) at {:?}", x, y, position);
+                }
+            }
+        }
+    }
+}
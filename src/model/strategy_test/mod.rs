@@ -1,3 +1,8 @@
 pub mod strategy_test;
 pub mod map_chunk;
-pub mod worker;
\ No newline at end of file
+pub mod worker;
+pub mod faction;
+pub mod rule_based_ai;
+pub mod headless;
+pub mod map_generator;
+pub mod visibility;
@@ -0,0 +1,125 @@
+use std::{time::Duration, f32::consts::PI};
+
+use rand::Rng;
+
+use crate::{game_objects::buildings::start_obj::{WareType, ALL_WARE_TYPES, UnitType}, controller::position::Position};
+
+use super::{faction::{FactionController, FactionSnapshot, FactionOrder, BUILDING_COSTS, ResourceInfo}, worker::{WorkerJob, WorkerState}};
+
+const DECISION_INTERVAL_SECS: f32 = 1.0;
+const MAX_ASSIGNMENTS_PER_DECISION: usize = 25;     //pathfinding is not cheap, so we don't want to send hundreds of workers at once
+const MAX_BASES: usize = 3;
+const NEAREST_DEPOSITS_TO_USE: usize = 3;
+const MAX_QUEUED_WORKERS_PER_BASE: usize = 2;
+const FOOD_RESERVE: u32 = 50;      //food above this is spent on new workers
+const GATHER_DISTANCE: (f32, f32) = (6.0, 14.0);
+const EXPANSION_DISTANCE: (f32, f32) = (14.0, 22.0);
+
+//the share of workers that should be working on each ware type, in the same order as ALL_WARE_TYPES
+const DESIRED_WORKER_SHARE: [f32; 3] = [0.5, 0.3, 0.2];
+
+//a very simple AI that follows fixed rules: keep the workers busy, split them between the wares according to DESIRED_WORKER_SHARE (preferring wares that are running low), spend spare food on new workers and expand once enough wares are collected
+#[derive(Debug)]
+pub(crate) struct RuleBasedController{
+    next_decision: f32,
+    //every ware type is gathered in its own direction around the base, so the workers don't run across each other all the time
+    gather_directions: [f32; 3],
+}
+
+impl RuleBasedController{
+    pub(crate) fn new() -> Self{
+        let mut rand = rand::thread_rng();
+        let offset = rand.gen_range(0.0..2.0*PI);
+        Self{
+            next_decision: DECISION_INTERVAL_SECS,
+            gather_directions: [offset, offset + 2.0*PI/3.0, offset + 4.0*PI/3.0],
+        }
+    }
+
+    fn pick_ware_type(&self, snapshot: &FactionSnapshot, assigned: &mut [usize; 3]) -> WareType{
+        let total_workers = snapshot.workers.len().max(1) as f32;
+        let mut best = 0;
+        let mut best_deficit = f32::MIN;
+        for (index, ware_type) in ALL_WARE_TYPES.iter().enumerate(){
+            let stock: u32 = snapshot.bases.iter().map(|base| base.get_ware_amount(*ware_type)).sum();
+            //wares that are low in stock weigh a little more, so the economy evens itself out
+            let scarcity = 1.0 / (1.0 + stock as f32 / 100.0);
+            let deficit = DESIRED_WORKER_SHARE[index] * (1.0 + scarcity) - assigned[index] as f32 / total_workers;
+            if deficit > best_deficit{
+                best_deficit = deficit;
+                best = index;
+            }
+        }
+        assigned[best] += 1;
+        ALL_WARE_TYPES[best]
+    }
+
+    //the workers are spread over the deposits closest to the base, if there are none, they just gather somewhere around it
+    fn gather_site(&self, ware_type: WareType, base: &Position, resources: &[ResourceInfo]) -> (f32, f32){
+        let mut rand = rand::thread_rng();
+        let mut deposits: Vec<&ResourceInfo> = resources.iter().filter(|deposit| deposit.ware_type == ware_type).collect();
+        if !deposits.is_empty(){
+            deposits.sort_by(|a, b| a.position.distance_to_position(base).total_cmp(&b.position.distance_to_position(base)));
+            let deposit = deposits[rand.gen_range(0..deposits.len().min(NEAREST_DEPOSITS_TO_USE))];
+            return deposit.position.get_x_y_values();
+        }
+        let angle = self.gather_directions[ware_type as usize] + rand.gen_range(-0.3..0.3);
+        let distance = rand.gen_range(GATHER_DISTANCE.0..GATHER_DISTANCE.1);
+        (base.x + angle.cos() * distance, base.y + angle.sin() * distance)
+    }
+}
+
+impl FactionController for RuleBasedController{
+    fn tick(&mut self, delta_time: Duration) -> bool {
+        self.next_decision -= delta_time.as_secs_f32();
+        if self.next_decision > 0.0{
+            return false;
+        }
+        self.next_decision = DECISION_INTERVAL_SECS;
+        true
+    }
+
+    fn control(&mut self, snapshot: &FactionSnapshot) -> Vec<FactionOrder> {
+        if snapshot.bases.is_empty(){
+            return Vec::new();
+        }
+
+        let mut orders = Vec::new();
+        let mut assigned = [0usize; 3];
+        for worker in &snapshot.workers{
+            if let Some(WorkerJob::Gather { ware_type, site: _ }) = worker.job{
+                assigned[ware_type as usize] += 1;
+            }
+        }
+
+        let idle_workers = snapshot.workers.iter().filter(|worker| worker.state == WorkerState::Idle && worker.job.is_none());
+        for worker in idle_workers.take(MAX_ASSIGNMENTS_PER_DECISION){
+            let ware_type = self.pick_ware_type(snapshot, &mut assigned);
+            //workers gather around the base they bring their wares to, not around the first one of the faction
+            let home = worker.home_base.and_then(|index| snapshot.bases.get(index)).unwrap_or(&snapshot.bases[0]);
+            let site = self.gather_site(ware_type, &home.position, &snapshot.resources);
+            orders.push(FactionOrder::AssignJob { worker_id: worker.id, job: WorkerJob::Gather { ware_type, site } });
+        }
+
+        for (base_index, base) in snapshot.bases.iter().enumerate(){
+            let worker_costs: u32 = UnitType::Worker.costs().iter().filter(|cost| cost.ware_type == WareType::Food as u32).map(|cost| cost.amount).sum();
+            if base.queued_units < MAX_QUEUED_WORKERS_PER_BASE && base.get_ware_amount(WareType::Food) >= FOOD_RESERVE + worker_costs{
+                orders.push(FactionOrder::QueueUnit { base_index, unit: UnitType::Worker });
+            }
+        }
+
+        //expand from the first base that can afford it, the level checks whether the spot is actually free
+        if snapshot.bases.len() < MAX_BASES{
+            let affordable = snapshot.bases.iter().position(|base| BUILDING_COSTS.iter().all(|cost| base.wares.iter().any(|w| w.ware_type == cost.ware_type && w.amount >= cost.amount)));
+            if let Some(base_index) = affordable{
+                let mut rand = rand::thread_rng();
+                let angle = rand.gen_range(0.0..2.0*PI);
+                let distance = rand.gen_range(EXPANSION_DISTANCE.0..EXPANSION_DISTANCE.1);
+                let base_position = snapshot.bases[base_index].position;
+                let position = Position::new(base_position.x + angle.cos() * distance, base_position.y + angle.sin() * distance);
+                orders.push(FactionOrder::PlaceBuilding { base_index, position });
+            }
+        }
+        orders
+    }
+}
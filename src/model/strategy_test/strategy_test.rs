@@ -1,13 +1,13 @@
-use std::{time::Duration, fmt::Debug, sync::{Arc, Weak}, io::Write, f32::NAN};
+use std::{time::Duration, fmt::Debug, sync::Arc};
 
 use tokio::sync::RwLock;
-use futures::lock;
-use rand::Rng;
+use rand::{Rng, rngs::StdRng, SeedableRng};
 
-use crate::{game_objects::{game_object::{VisitableStructure, LogicObject}, buildings::start_obj::StartObj}, model::results::{LogicResult, GameObjects}, controller::position::{self, Position}, rendering::sprites::sprite_mapping::Sprite};
+use crate::{game_objects::{game_object::{VisitableStructure, LogicObject, DrawableObject}, buildings::start_obj::{StartObj, ALL_WARE_TYPES, UnitType}, resource_deposit::ResourceDeposit}, model::results::{LogicResult, GameObjects}, controller::{position::Position, controller_commands::LevelCommand, input::{ControllerInput, BaseProduction}}, model::scripting::{scripted_controller::ScriptedController, script::ScriptLog}, rendering::{tilemap::TileMapData, fog_of_war::FogData}};
 
-use super::{map_chunk::{MapChunk, ChunkInfo}, worker::Worker};
+use super::{map_chunk::{MapChunk, ChunkInfo}, worker::Worker, faction::{Faction, FactionOrder, BUILDING_COSTS, PLAYER_FACTION}, rule_based_ai::RuleBasedController, map_generator::{MapGenerator, TerrainGrid}};
 
+const STARTING_WORKERS_PER_FACTION: usize = 700;
 
 //the idea is to hold information about the current level, this includes the chunks that are currently loaded, the structures that are currently loaded and the borders of the level. This is supposed to be used for stuff like pathfinding and collision detection
 #[derive(Debug)]
@@ -18,11 +18,22 @@ pub struct StratLevel{
     pub(crate) logic_objects: Vec<Arc<RwLock<dyn StrategyLogicObject>>>,
     pub(crate) logic_objects_id_counter: u64,
     pub(crate) borders: ChunkInfo,
+    pub(crate) factions: Vec<Faction>,
+    pub(crate) resources: Vec<Arc<RwLock<ResourceDeposit>>>,
+    pub(crate) terrain: Option<TerrainGrid>,
+    pub(crate) seed: u64,       //the seed for the map generator and the starting workers, the same seed always creates the same map
+    pub(crate) fog_sender: Option<flume::Sender<Option<FogData>>>,     //the vision of the player faction is sent to the renderer through this, if there is one
+    pub(crate) console_sender: Option<flume::Sender<ControllerInput>>,      //what the scripts of ScriptedControllers print also goes to the console, if there is one. The HUD gets the production of the player from here as well
+    reported_production: Option<BaseProduction>,
     pub(crate) id: u32,
 }
 
 impl StratLevel{
     pub(crate) fn new(borders: ChunkInfo) -> Self{
+        Self::with_seed(borders, rand::thread_rng().gen())
+    }
+
+    pub(crate) fn with_seed(borders: ChunkInfo, seed: u64) -> Self{
         StratLevel{
             blocking_chunks: Vec::new(),
             non_blocking_chunks: Vec::new(),
@@ -30,6 +41,13 @@ impl StratLevel{
             logic_objects: Vec::new(),
             logic_objects_id_counter: 0,
             borders,
+            factions: Vec::new(),
+            resources: Vec::new(),
+            terrain: None,
+            seed,
+            fog_sender: None,
+            console_sender: None,
+            reported_production: None,
             id: 0,
         }
     }
@@ -47,57 +65,74 @@ impl StratLevel{
         self.logic_objects_id_counter += 1;
     }
 
+    //same as add_logic_object, but usable from within process_logic
+    pub(crate) fn add_logic_object_blocking(&mut self, logic_object: Arc<RwLock<dyn StrategyLogicObject>>){
+        logic_object.blocking_write().set_id(self.logic_objects_id_counter);
+        self.logic_objects.push(logic_object);
+        self.logic_objects_id_counter += 1;
+    }
+
     pub(crate) async fn initialize(&mut self) -> GameObjects{
         println!("initializing level");
         let mut ret : GameObjects = Vec::new();
-        let mut rand = rand::thread_rng();
-        let other_base = StartObj::new(Position::new(4.0, -2.0), self.logic_objects_id_counter);
-        let third_base = StartObj::new(Position::new(-2.0, 2.0), self.logic_objects_id_counter);
-        let start_base = StartObj::new(Position::new(0.0, 0.0), self.logic_objects_id_counter);
-        let third_base_arxed = Arc::new(RwLock::new(third_base));
-        self.add_logic_object(third_base_arxed.clone()).await;
-        self.structures.push(third_base_arxed.clone());
-        ret.push(third_base_arxed.clone());
-        let other_arxed_base = Arc::new(RwLock::new(other_base));
-        self.add_logic_object(other_arxed_base.clone()).await;
-        self.structures.push(other_arxed_base.clone());
-        ret.push(other_arxed_base.clone());
-        let arxed_base = Arc::new(RwLock::new(start_base));
-        self.add_logic_object(arxed_base.clone()).await;
-        self.structures.push(arxed_base.clone());
-        ret.push(arxed_base.clone());
-       for i in 0..2000{
-            let dist_from_base: f32 = rand.gen_range(15.0..60.0);
-            let mut x = rand.gen_range(-dist_from_base.sqrt()..=dist_from_base.sqrt());
-            let mut y = (dist_from_base - f32::powi(x, 2)).sqrt();
-            if rand.gen_bool(0.5){
-                y *= -1.0;
-            }
-            loop{
-                let mut eligible = true;
-                for structure in &self.structures{
-                    if structure.read().await.get_blocking_chunk().contains(&Position::new(x, y)){
+        let mut rand = StdRng::seed_from_u64(self.seed);     //the workers start at the same spots for the same seed as well
+        //every base is its own faction, driven by the default AI
+        for faction_id in 0..3{
+            self.factions.push(Faction::new(faction_id, Some(Box::new(RuleBasedController::new())), &self.borders));
+        }
+        //far enough apart that the bases and the spots where their workers start don't overlap
+        let base_positions = [Position::new(0.0, 0.0), Position::new(30.0, -20.0), Position::new(-25.0, 25.0)];
+        let map = MapGenerator::new(self.seed).generate(&self.borders, &base_positions);
+        self.blocking_chunks = map.blocking_chunks;
+        self.non_blocking_chunks = map.non_blocking_chunks;
+        self.terrain = Some(map.terrain);
+        for deposit in map.resources{
+            let arxed = Arc::new(RwLock::new(deposit));
+            self.resources.push(arxed.clone());
+            ret.push(arxed);
+        }
+
+        for (faction_index, base_position) in base_positions.iter().enumerate(){
+            let base = StartObj::new(*base_position, faction_index as u32, self.logic_objects_id_counter);
+            let arxed_base = Arc::new(RwLock::new(base));
+            self.add_logic_object(arxed_base.clone()).await;
+            self.structures.push(arxed_base.clone());
+            self.factions[faction_index].bases.push(arxed_base.clone());
+            ret.push(arxed_base);
+        }
+        //every faction starts with the same amount of workers around its own base, otherwise only the first one could ever gather anything
+        for (faction_index, base_position) in base_positions.iter().enumerate(){
+            let home_base = self.factions[faction_index].bases[0].clone();
+            for _ in 0..STARTING_WORKERS_PER_FACTION{
+                //a new distance is picked for every try as well, all spots at one distance can be blocked
+                let position = loop{
+                    let dist_from_base: f32 = rand.gen_range(15.0..60.0);
+                    let x = rand.gen_range(-dist_from_base.sqrt()..=dist_from_base.sqrt());
+                    let mut y = (dist_from_base - f32::powi(x, 2)).sqrt();
+                    if rand.gen_bool(0.5){
+                        y *= -1.0;
+                    }
+                    let position = Position::new(base_position.x + x, base_position.y + y);
+                    let mut eligible = true;
+                    for structure in &self.structures{
+                        if structure.read().await.get_blocking_chunk().contains(&position){
+                            eligible = false;
+                        }
+                    }
+                    if self.blocking_chunks.iter().any(|chunk| chunk.inf().contains(&position)){
                         eligible = false;
                     }
-                }
-                if eligible{
-                    break;
-                }
-                x = rand.gen_range(-dist_from_base.sqrt()..=dist_from_base.sqrt());
-                y = (dist_from_base - f32::powi(x, 2)).sqrt();
-                if y == NAN || y == -NAN{
-                    println!("Dist: {dist_from_base} x: {x} y: {y}");
-                }
-                if rand.gen_bool(0.5){
-                    y *= -1.0;
-                }
-                //genera
-            }
+                    if eligible{
+                        break position;
+                    }
+                };
 
-            let worker = Worker::new(Some(arxed_base.clone()), Position::new(x, y), self.logic_objects_id_counter);
-            let arxed = Arc::new(RwLock::new(worker));
-            ret.push(arxed.clone());
-            self.add_logic_object(arxed).await;
+                let worker = Worker::new(Some(home_base.clone()), position, self.logic_objects_id_counter);
+                let arxed = Arc::new(RwLock::new(worker));
+                ret.push(arxed.clone());
+                self.factions[faction_index].workers.push(arxed.clone());
+                self.add_logic_object(arxed).await;
+            }
         }
     /*    let worker = Worker::new(Some(arxed_base.clone()), Position::new(0.98684025, 4.901647), self.logic_objects_id_counter);
         let arxed = Arc::new(RwLock::new(worker));
@@ -110,6 +145,169 @@ impl StratLevel{
     
     }
 
+    //bases only keep track of which units are done, the actual units are created here, since a base can't hand out references to itself
+    fn spawn_finished_units(&mut self, created: &mut GameObjects){
+        for faction_index in 0..self.factions.len(){
+            for base in self.factions[faction_index].bases.clone(){
+                let finished_units = base.blocking_write().take_finished_units();
+                for unit in finished_units{
+                    match unit{
+                        UnitType::Worker => {
+                            let entry_point = base.blocking_read().get_entry_point();
+                            let worker = Worker::new(Some(base.clone()), entry_point, self.logic_objects_id_counter);
+                            let arxed = Arc::new(RwLock::new(worker));
+                            self.add_logic_object_blocking(arxed.clone());
+                            arxed.blocking_write().initialize_behavior(&self.blocking_chunks, &self.structures);
+                            self.factions[faction_index].workers.push(arxed.clone());
+                            created.push(arxed);
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_base(&self, faction: u32, base_index: usize) -> Option<&Arc<RwLock<StartObj>>>{
+        self.factions.iter().find(|f| f.id == faction)?.bases.get(base_index)
+    }
+
+    //asks every faction controller for its orders and executes them. Newly placed buildings are returned, so they can be handed to the model
+    fn process_factions(&mut self, delta_time: Duration, created: &mut GameObjects){
+        for faction_index in 0..self.factions.len(){
+            let faction = &mut self.factions[faction_index];
+            let wants_control = match &mut faction.controller{
+                Some(controller) => controller.tick(delta_time),
+                None => false,
+            };
+            if !wants_control{
+                continue;
+            }
+            let snapshot = faction.snapshot(&self.resources);
+            let orders = faction.controller.as_mut().unwrap().control(&snapshot);
+            for order in orders{
+                self.execute_order(faction_index, order, created);
+            }
+        }
+    }
+
+    fn execute_order(&mut self, faction_index: usize, order: FactionOrder, created: &mut GameObjects){
+        match order{
+            FactionOrder::AssignJob { worker_id, job } => {
+                let worker = self.factions[faction_index].workers.iter().find(|worker| StrategyLogicObject::get_id(&*worker.blocking_read()) == worker_id);
+                if let Some(worker) = worker{
+                    worker.blocking_write().assign_job(job, &self.blocking_chunks, &self.structures);
+                }
+            },
+            FactionOrder::PlaceBuilding { base_index, position } => {
+                let faction_id = self.factions[faction_index].id;
+                let Some(paying_base) = self.factions[faction_index].bases.get(base_index).cloned() else{
+                    return;
+                };
+                let building = StartObj::new(position, faction_id, self.logic_objects_id_counter);
+                if !self.is_free_for(&building.get_blocking_chunk()) || !self.factions[faction_index].visibility.is_explored(&position){
+                    return;
+                }
+                if !paying_base.blocking_write().take_wares(&BUILDING_COSTS){
+                    return;
+                }
+                let arxed = Arc::new(RwLock::new(building));
+                self.add_logic_object_blocking(arxed.clone());
+                arxed.blocking_write().initialize_behavior(&self.blocking_chunks, &self.structures);
+                self.structures.push(arxed.clone());
+                self.factions[faction_index].bases.push(arxed.clone());
+                created.push(arxed);
+            },
+            FactionOrder::QueueUnit { base_index, unit } => {
+                if let Some(base) = self.factions[faction_index].bases.get(base_index){
+                    base.blocking_write().queue_unit(unit);
+                }
+            },
+        }
+    }
+
+    //checks whether the given area is inside of the level and not blocked by anything
+    pub(crate) fn is_free_for(&self, area: &ChunkInfo) -> bool{
+        if !self.borders.contains_chunk(area){
+            return false;
+        }
+        if self.blocking_chunks.iter().any(|chunk| chunk.inf().intersects(area)){
+            return false;
+        }
+        !self.structures.iter().any(|structure| structure.blocking_read().get_blocking_chunk().intersects(area))
+    }
+
+    //updates the vision of every faction and hides everything the player faction can't see
+    fn update_visibility(&mut self){
+        for faction in self.factions.iter_mut(){
+            faction.update_visibility();
+        }
+        let Some(player) = self.factions.iter().position(|faction| faction.id == PLAYER_FACTION) else{
+            return;
+        };
+        for (index, faction) in self.factions.iter().enumerate(){
+            if index == player{
+                continue;
+            }
+            let player_visibility = &self.factions[player].visibility;
+            for base in &faction.bases{
+                let mut lock = base.blocking_write();
+                let hidden = !player_visibility.is_visible(&lock.position);
+                lock.set_hidden(hidden);
+            }
+            for worker in &faction.workers{
+                let mut lock = worker.blocking_write();
+                let hidden = !player_visibility.is_visible(&lock.get_position());
+                lock.set_hidden(hidden);
+            }
+        }
+        for deposit in &self.resources{
+            let mut lock = deposit.blocking_write();
+            let hidden = !self.factions[player].visibility.is_explored(&lock.position);
+            lock.set_hidden(hidden);
+        }
+        if let Some(sender) = &self.fog_sender{
+            if let Some(fog_data) = self.factions[player].visibility.take_fog_data(){
+                let _ = sender.send(Some(fog_data));
+            }
+        }
+    }
+
+    //the HUD shows the queue of the first base of the player
+    fn report_production(&mut self){
+        let Some(sender) = &self.console_sender else{
+            return;
+        };
+        let Some(base) = self.get_base(PLAYER_FACTION, 0) else{
+            return;
+        };
+        let lock = base.blocking_read();
+        let production = BaseProduction{
+            queued: lock.get_production_queue_length(),
+            progress: (lock.get_production_progress() * 100.0).floor() / 100.0,
+        };
+        drop(lock);
+        if self.reported_production != Some(production){
+            self.reported_production = Some(production);
+            let _ = sender.send(ControllerInput::BaseProduction(production));
+        }
+    }
+
+    pub(crate) fn tile_map_data(&self) -> Option<TileMapData>{
+        self.terrain.as_ref().map(|terrain| terrain.to_tile_map_data())
+    }
+
+    pub(crate) fn print_faction_summary(&self){
+        for faction in &self.factions{
+            let wares = faction.total_wares();
+            let mut line = format!("Faction {}: {} bases, {} workers", faction.id, faction.bases.len(), faction.workers.len());
+            for ware_type in ALL_WARE_TYPES{
+                let amount = wares.iter().find(|w| w.ware_type == ware_type as u32).map(|w| w.amount).unwrap_or(0);
+                line += &format!(", {:?}: {}", ware_type, amount);
+            }
+            println!("{}", line);
+        }
+    }
+
 
 }
 
@@ -122,6 +320,14 @@ impl LogicObject for StratLevel{
             let mut lock = logic_object.blocking_write();
             lock.process_logic(delta_time, &mut self.blocking_chunks, &mut self.structures);
         }
+        let mut created: GameObjects = Vec::new();
+        self.spawn_finished_units(&mut created);
+        self.report_production();
+        self.update_visibility();
+        self.process_factions(delta_time, &mut created);
+        if !created.is_empty(){
+            return LogicResult::CreateGameObjects { game_objects: created };
+        }
         LogicResult::None
     }
 
@@ -132,6 +338,42 @@ impl LogicObject for StratLevel{
     fn get_id(&self) -> u32 {
         self.id
     }
+
+    fn process_command(&mut self, command: &LevelCommand) {
+        match command{
+            LevelCommand::QueueUnit { faction, base_index, unit } => {
+                let Some(base) = self.get_base(*faction, *base_index) else{
+                    println!("Faction {} has no base {}", faction, base_index);
+                    return;
+                };
+                //a full queue or missing wares just leave the queue as it is
+                base.blocking_write().queue_unit(*unit);
+            },
+            LevelCommand::CancelUnit { faction, base_index } => {
+                let Some(base) = self.get_base(*faction, *base_index) else{
+                    println!("Faction {} has no base {}", faction, base_index);
+                    return;
+                };
+                base.blocking_write().cancel_last_unit();
+            },
+            LevelCommand::ControlFaction { faction, script } => {
+                //this is usually typed into the console, so the answer goes there as well
+                let log = ScriptLog::new(self.console_sender.clone());
+                let Some(controlled) = self.factions.iter_mut().find(|f| f.id == *faction) else{
+                    log.print(format!("There is no faction {}", faction));
+                    return;
+                };
+                //the old controller keeps running if the script is broken
+                match ScriptedController::load(script, self.console_sender.clone()){
+                    Ok(controller) => {
+                        controlled.controller = Some(Box::new(controller));
+                        log.print(format!("Faction {} is controlled by {}", faction, script.display()));
+                    },
+                    Err(error) => log.print(format!("Could not control faction {}: {}", faction, error)),
+                }
+            },
+        }
+    }
     
 }
 
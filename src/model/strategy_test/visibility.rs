@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::{controller::position::Position, rendering::fog_of_war::FogData};
+
+use super::map_chunk::{ChunkInfo, IntEdge};
+
+pub(crate) const VISIBILITY_CELL_SIZE: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Visibility{
+    Unexplored,
+    Explored,       //seen before, but currently nobody of the faction is looking at it
+    Visible,
+}
+
+//what one faction can see. Every unit and structure is a viewer that reveals the cells within its sight radius
+//viewers are counted per cell, so moving a viewer only has to touch the cells around its old and new position instead of rebuilding the whole grid every tick
+#[derive(Debug)]
+pub(crate) struct VisibilityGrid{
+    origin: IntEdge,
+    width: usize,
+    height: usize,
+    viewer_counts: Vec<u16>,
+    explored: Vec<bool>,
+    viewers: HashMap<u64, ((i32, i32), f32)>,     //id -> (cell the viewer was in at the last update, sight radius)
+    dirty: bool,
+}
+
+impl VisibilityGrid{
+    pub(crate) fn new(borders: &ChunkInfo) -> Self{
+        let width = (((borders.top_right.x - borders.bottom_left.x) as f32 / VISIBILITY_CELL_SIZE).ceil() as usize).max(1);
+        let height = (((borders.top_right.y - borders.bottom_left.y) as f32 / VISIBILITY_CELL_SIZE).ceil() as usize).max(1);
+        Self{
+            origin: borders.bottom_left,
+            width,
+            height,
+            viewer_counts: vec![0; width * height],
+            explored: vec![false; width * height],
+            viewers: HashMap::new(),
+            dirty: true,
+        }
+    }
+
+    fn cell_of(&self, position: &Position) -> (i32, i32){
+        (((position.x - self.origin.x as f32) / VISIBILITY_CELL_SIZE).floor() as i32, ((position.y - self.origin.y as f32) / VISIBILITY_CELL_SIZE).floor() as i32)
+    }
+
+    fn index_of(&self, cell: (i32, i32)) -> Option<usize>{
+        if cell.0 < 0 || cell.1 < 0 || cell.0 as usize >= self.width || cell.1 as usize >= self.height{
+            return None;
+        }
+        Some(cell.1 as usize * self.width + cell.0 as usize)
+    }
+
+    //adds the viewer to the grid or moves it, nothing happens if it is still in the same cell
+    pub(crate) fn update_viewer(&mut self, id: u64, position: &Position, sight_radius: f32){
+        let cell = self.cell_of(position);
+        if let Some((old_cell, old_radius)) = self.viewers.get(&id).copied(){
+            if old_cell == cell && old_radius == sight_radius{
+                return;
+            }
+            self.apply_disc(old_cell, old_radius, false);
+        }
+        self.apply_disc(cell, sight_radius, true);
+        self.viewers.insert(id, (cell, sight_radius));
+    }
+
+    fn apply_disc(&mut self, center: (i32, i32), radius: f32, add: bool){
+        let cell_radius = (radius / VISIBILITY_CELL_SIZE).ceil() as i32;
+        let radius_squared = (radius / VISIBILITY_CELL_SIZE).powi(2);
+        for dy in -cell_radius..=cell_radius{
+            for dx in -cell_radius..=cell_radius{
+                if (dx * dx + dy * dy) as f32 > radius_squared{
+                    continue;
+                }
+                let Some(index) = self.index_of((center.0 + dx, center.1 + dy)) else{
+                    continue;
+                };
+                if add{
+                    self.viewer_counts[index] += 1;
+                    self.explored[index] = true;
+                }else{
+                    self.viewer_counts[index] = self.viewer_counts[index].saturating_sub(1);
+                }
+            }
+        }
+        self.dirty = true;
+    }
+
+    pub(crate) fn get(&self, position: &Position) -> Visibility{
+        let Some(index) = self.index_of(self.cell_of(position)) else{
+            return Visibility::Unexplored;
+        };
+        if self.viewer_counts[index] > 0{
+            Visibility::Visible
+        }else if self.explored[index]{
+            Visibility::Explored
+        }else{
+            Visibility::Unexplored
+        }
+    }
+
+    pub(crate) fn is_visible(&self, position: &Position) -> bool{
+        self.get(position) == Visibility::Visible
+    }
+
+    pub(crate) fn is_explored(&self, position: &Position) -> bool{
+        self.get(position) != Visibility::Unexplored
+    }
+
+    //returns the data for the renderer, but only if something changed since the last call
+    pub(crate) fn take_fog_data(&mut self) -> Option<FogData>{
+        if !self.dirty{
+            return None;
+        }
+        self.dirty = false;
+        let states = (0..self.width * self.height).map(|index| {
+            if self.viewer_counts[index] > 0{
+                Visibility::Visible as u8
+            }else if self.explored[index]{
+                Visibility::Explored as u8
+            }else{
+                Visibility::Unexplored as u8
+            }
+        }).collect();
+        Some(FogData{
+            origin: [self.origin.x as f32, self.origin.y as f32],
+            cell_size: VISIBILITY_CELL_SIZE,
+            width: self.width as u32,
+            height: self.height as u32,
+            states,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn grid() -> VisibilityGrid{
+        VisibilityGrid::new(&ChunkInfo{ bottom_left: IntEdge::new(-20, -20), top_right: IntEdge::new(20, 20) })
+    }
+
+    #[test]
+    fn viewers_reveal_their_sight_radius(){
+        let mut grid = grid();
+        assert_eq!(grid.get(&Position::new(0.5, 0.5)), Visibility::Unexplored);
+        grid.update_viewer(1, &Position::new(0.5, 0.5), 3.0);
+        assert_eq!(grid.get(&Position::new(0.5, 0.5)), Visibility::Visible);
+        assert_eq!(grid.get(&Position::new(3.5, 0.5)), Visibility::Visible);
+        assert_eq!(grid.get(&Position::new(4.5, 0.5)), Visibility::Unexplored);
+        assert_eq!(grid.get(&Position::new(3.5, 2.5)), Visibility::Unexplored);
+    }
+
+    #[test]
+    fn moving_a_viewer_leaves_explored_cells_behind(){
+        let mut grid = grid();
+        grid.update_viewer(1, &Position::new(0.5, 0.5), 2.0);
+        grid.update_viewer(1, &Position::new(10.5, 0.5), 2.0);
+        assert_eq!(grid.get(&Position::new(0.5, 0.5)), Visibility::Explored);
+        assert_eq!(grid.get(&Position::new(10.5, 0.5)), Visibility::Visible);
+        assert!(grid.is_explored(&Position::new(1.5, 0.5)));
+        assert!(!grid.is_visible(&Position::new(1.5, 0.5)));
+    }
+
+    #[test]
+    fn overlapping_viewers_are_counted(){
+        let mut grid = grid();
+        grid.update_viewer(1, &Position::new(0.5, 0.5), 2.0);
+        grid.update_viewer(2, &Position::new(1.5, 0.5), 2.0);
+        //the cell both of them see stays visible while only one of them leaves
+        grid.update_viewer(1, &Position::new(-10.5, 0.5), 2.0);
+        assert!(grid.is_visible(&Position::new(0.5, 0.5)));
+        grid.update_viewer(2, &Position::new(10.5, 0.5), 2.0);
+        assert_eq!(grid.get(&Position::new(0.5, 0.5)), Visibility::Explored);
+    }
+
+    #[test]
+    fn fog_data_is_only_sent_after_changes(){
+        let mut grid = grid();
+        assert!(grid.take_fog_data().is_some());
+        assert!(grid.take_fog_data().is_none());
+        grid.update_viewer(1, &Position::new(0.5, 0.5), 2.0);
+        let fog = grid.take_fog_data().unwrap();
+        assert_eq!(fog.states.iter().filter(|state| **state == Visibility::Visible as u8).count(), 13);
+        //staying in the same cell doesn't touch the grid
+        grid.update_viewer(1, &Position::new(0.9, 0.1), 2.0);
+        assert!(grid.take_fog_data().is_none());
+    }
+
+    #[test]
+    fn viewers_at_the_border_are_clipped(){
+        let mut grid = grid();
+        grid.update_viewer(1, &Position::new(-19.5, -19.5), 3.0);
+        assert!(grid.is_visible(&Position::new(-19.5, -19.5)));
+        assert_eq!(grid.get(&Position::new(-21.0, -19.5)), Visibility::Unexplored);
+    }
+}
@@ -3,62 +3,131 @@ use std::sync::Arc;
 
 use tokio::sync::RwLock;
 
-use crate::{game_objects::{buildings::start_obj::StartObj, game_object::{DrawableObject, VisitableStructure}}, rendering::sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}, controller::position::Position, model::results::LogicResult};
+use crate::{game_objects::{buildings::start_obj::{StartObj, WareType}, game_object::{DrawableObject, VisitableStructure}}, rendering::{sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration, animation::{Animation, WORKER_IDLE, WORKER_WALKING, WORKER_WORKING}}, depth::RenderLayer, sprite_instance::SpriteTransform}, controller::position::Position, model::results::LogicResult};
 
-use super::strategy_test::StrategyLogicObject;
+use super::{strategy_test::StrategyLogicObject, map_chunk::MapChunk, faction::faction_tint};
+
+const GATHER_TIME_SECS: f32 = 3.0;
+const GATHER_AMOUNT: u32 = 5;
+const SIGHT_RADIUS: f32 = 5.0;
 
 #[derive(Debug)]
 pub(crate) struct Worker{
     home: Option< Arc<RwLock<StartObj>>>,
     position: Position,
+    previous_position: Position,    //at the end of the last tick, for interpolation
     id: u64,
+    game_object_id: u64,    //the model assigns its own ids to game objects, so we keep them apart from the id inside the StratLevel
     origin_positiom: Position,
     goal: Option<(f32, f32)>,
     speed: f32,
     next_tile: Option<(f32, f32)>,
     path: Vec<(f32, f32)>,
-    texture: Sprite,
+    animation: Animation,
     state: WorkerState,
+    job: Option<WorkerJob>,
+    carrying: Option<(WareType, u32)>,
+    work_timer: f32,
+    hidden: bool,
+    facing: f32,        //angle of the last movement direction, the sprite is rotated to point there
+    tint: [f32; 4],     //the color of the faction of the home base
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum WorkerState{
     Idle,
     Moving,
     Working,
 }
 
+//jobs are handed out by a FactionController, a worker keeps repeating its job until it gets a new one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum WorkerJob{
+    Gather{ ware_type: WareType, site: (f32, f32) },
+}
+
 impl Worker{
     pub(crate) fn new(home: Option<Arc<RwLock<StartObj>>>, position: Position, id: u64) -> Self{
+        let mut tint = [1.0; 4];
         if let Some(home_base) = &home{
-            home_base.blocking_write().add_associated_unit(id);
+            let mut home_lock = home_base.blocking_write();
+            home_lock.add_associated_unit(id);
+            tint = faction_tint(home_lock.get_faction());
         }
-        println!("I got created with id {}", id);
         Self{
             home,
             position,
+            previous_position: position,
             id,
+            game_object_id: 0,
             origin_positiom: position,
             goal: None,
             speed: 0.5f32,
             next_tile: None,
             path: Vec::new(),
-            texture : Sprite::WorkerBasic,
+            animation: Animation::new(&WORKER_IDLE),
             state: WorkerState::Idle,
+            job: None,
+            carrying: None,
+            work_timer: 0.0,
+            hidden: false,
+            facing: 0.0,
+            tint,
         }
     }
 
-}
+    pub(crate) fn get_sight_radius(&self) -> f32{
+        SIGHT_RADIUS
+    }
 
-impl StrategyLogicObject for Worker{
-    fn process_logic(&mut self, delta_time: std::time::Duration, blockers: &mut Vec<Box<dyn super::map_chunk::MapChunk>>, structures: &mut Vec<Arc<RwLock<dyn VisitableStructure>>>) -> LogicResult {
+    pub(crate) fn set_hidden(&mut self, hidden: bool){
+        self.hidden = hidden;
+    }
+
+    pub(crate) fn get_state(&self) -> WorkerState{
+        self.state
+    }
+
+    pub(crate) fn get_job(&self) -> Option<WorkerJob>{
+        self.job
+    }
+
+    pub(crate) fn get_home(&self) -> Option<&Arc<RwLock<StartObj>>>{
+        self.home.as_ref()
+    }
+
+    //wares that are carried while the job changes are dropped, there is no way to store them anywhere else yet
+    pub(crate) fn assign_job(&mut self, job: WorkerJob, blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<RwLock<dyn VisitableStructure>>>){
+        self.job = Some(job);
+        self.carrying = None;
+        match job{
+            WorkerJob::Gather { ware_type: _, site } => self.move_to(&site, blockers, structures),
+        }
+    }
+
+    fn move_to(&mut self, goal: &(f32, f32), blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<RwLock<dyn VisitableStructure>>>){
+        self.goal = Some(*goal);
+        self.path = self.position.find_path_to(goal, blockers, structures).unwrap_or_default();
+        if self.path.is_empty(){
+            //the goal can't be reached, so there is no point in keeping the job
+            self.state = WorkerState::Idle;
+            self.job = None;
+            self.carrying = None;
+            return;
+        }
+        self.state = WorkerState::Moving;
+    }
+
+    //moves along the path, returns true once the end of the path has been reached
+    fn follow_path(&mut self, delta_time: std::time::Duration) -> bool{
         let mut covered_distance = self.speed * delta_time.as_secs_f32();
         if self.path.len() == 0{
-            return LogicResult::None;
+            return true;
         }
         let dist =self.position.distance(&self.path[0]);
         if  dist >= covered_distance{
             let direction = self.position.direction_to(&self.path[0]);
+            self.face(direction);
             self.position.x += direction.0 * covered_distance;
             self.position.y += direction.1 * covered_distance;
         }
@@ -67,14 +136,67 @@ impl StrategyLogicObject for Worker{
             self.position = Position::new(self.path[0].0, self.path[0].1);
             self.path.remove(0);
             if self.path.len() == 0{
-                self.state = WorkerState::Idle;
-                println!("I am a worker and I am done! My id is: {} and I came from {:?}", self.id, self.origin_positiom);
-            }
-            else{
-                let direction = self.position.direction_to(&self.path[0]);
-                self.position.x += direction.0 * covered_distance;
-                self.position.y += direction.1 * covered_distance;
+                return true;
             }
+            let direction = self.position.direction_to(&self.path[0]);
+            self.face(direction);
+            self.position.x += direction.0 * covered_distance;
+            self.position.y += direction.1 * covered_distance;
+        }
+        false
+    }
+
+    fn face(&mut self, direction: (f32, f32)){
+        //direction_to returns NaN if the worker already stands on the target
+        if direction.0.is_finite() && direction.1.is_finite() && (direction.0 != 0.0 || direction.1 != 0.0){
+            self.facing = direction.1.atan2(direction.0);
+        }
+    }
+
+    fn arrive(&mut self, blockers: &Vec<Box<dyn MapChunk>>, structures: &Vec<Arc<RwLock<dyn VisitableStructure>>>){
+        match (self.job, self.carrying.take()){
+            (Some(WorkerJob::Gather { ware_type: _, site }), Some((ware_type, amount))) => {
+                if let Some(home) = &self.home{
+                    home.blocking_write().add_wares(ware_type, amount);
+                }
+                self.move_to(&site, blockers, structures);
+            },
+            (Some(WorkerJob::Gather { .. }), None) => {
+                self.state = WorkerState::Working;
+                self.work_timer = GATHER_TIME_SECS;
+            },
+            (None, _) => {
+                self.state = WorkerState::Idle;
+            },
+        }
+    }
+
+}
+
+impl StrategyLogicObject for Worker{
+    fn process_logic(&mut self, delta_time: std::time::Duration, blockers: &mut Vec<Box<dyn super::map_chunk::MapChunk>>, structures: &mut Vec<Arc<RwLock<dyn VisitableStructure>>>) -> LogicResult {
+        match self.state{
+            WorkerState::Idle => (),
+            WorkerState::Moving => {
+                if self.follow_path(delta_time){
+                    self.arrive(blockers, structures);
+                }
+            },
+            WorkerState::Working => {
+                self.work_timer -= delta_time.as_secs_f32();
+                if self.work_timer <= 0.0{
+                    if let Some(WorkerJob::Gather { ware_type, site: _ }) = self.job{
+                        self.carrying = Some((ware_type, GATHER_AMOUNT));
+                    }
+                    match &self.home{
+                        Some(home) => {
+                            let entry = home.blocking_read().get_entry_point().get_x_y_values();
+                            self.move_to(&entry, blockers, structures);
+                        },
+                        None => self.state = WorkerState::Idle,
+                    }
+                }
+            },
         }
         LogicResult::None
     }
@@ -103,6 +225,9 @@ impl StrategyLogicObject for Worker{
         self.path = if let Some(path) = start_position.find_path_to(self.goal.as_ref().unwrap(), blockers, structures){ path } else{
             Vec::new()
         };
+        if !self.path.is_empty(){
+            self.state = WorkerState::Moving;
+        }
     }
 }
 
@@ -111,6 +236,14 @@ impl DrawableObject for Worker{
         self.position
     }
 
+    fn get_previous_position(&self) -> Position {
+        self.previous_position
+    }
+
+    fn store_previous_position(&mut self) {
+        self.previous_position = self.position;
+    }
+
     fn get_x_y_values(&self) -> (f32, f32) {
         (self.position.x, self.position.y)
     }
@@ -120,11 +253,29 @@ impl DrawableObject for Worker{
     }
 
     fn get_texture(&self) -> &Sprite {
-        return &self.texture;
+        self.animation.get_sprite()
     }
 
     fn process_animation(&mut self, delta_time: f64) {
-        
+        let clip = match self.state{
+            WorkerState::Idle => &WORKER_IDLE,
+            WorkerState::Moving => &WORKER_WALKING,
+            WorkerState::Working => &WORKER_WORKING,
+        };
+        self.animation.play(clip);
+        self.animation.advance(delta_time);
+    }
+
+    fn get_frame(&self) -> u32 {
+        self.animation.get_frame()
+    }
+
+    fn get_transform(&self) -> SpriteTransform {
+        SpriteTransform{
+            rotation: self.facing + std::f32::consts::FRAC_PI_2,     //the tip of the triangle in the sprite points down
+            tint: self.tint,
+            ..Default::default()
+        }
     }
 
     fn get_vertex_configuration(&self) -> &VertexConfigration {
@@ -132,10 +283,18 @@ impl DrawableObject for Worker{
     }
 
     fn get_id(&self) -> u64 {
-        return self.id;
+        self.game_object_id
     }
 
     fn set_id(&mut self, id: u64) {
-        self.id = id;
+        self.game_object_id = id;
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    fn get_layer(&self) -> RenderLayer {
+        RenderLayer::Units
     }
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+use std::{sync::Mutex, time::{Instant, Duration}};
+
+//when the model finished its last ticks, so the render side can tell how far it is between two ticks
+#[derive(Debug)]
+pub(crate) struct TickClock{
+    state: Mutex<TickState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TickState{
+    tick: u64,
+    last_tick: Instant,
+    interval: Duration,     //between the last two ticks, this follows the actual tick rate of the model
+}
+
+impl TickClock{
+    pub(crate) fn new(expected_interval: Duration) -> Self{
+        Self{
+            state: Mutex::new(TickState{
+                tick: 0,
+                last_tick: Instant::now(),
+                interval: expected_interval,
+            }),
+        }
+    }
+
+    //called by the model after every update, returns the number of the new tick
+    pub(crate) fn tick(&self) -> u64{
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.interval = now - state.last_tick;
+        state.last_tick = now;
+        state.tick += 1;
+        state.tick
+    }
+
+    //the number of the last tick and how far the time is between it and the next one, from 0.0 (just ticked) to 1.0 (the next tick is due)
+    pub(crate) fn progress(&self) -> (u64, f32){
+        let state = *self.state.lock().unwrap();
+        if state.interval.is_zero(){
+            return (state.tick, 1.0);
+        }
+        let fraction = state.last_tick.elapsed().as_secs_f32() / state.interval.as_secs_f32();
+        (state.tick, fraction.clamp(0.0, 1.0))
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use super::model::MODEL_TICK_INTERVAL;
+
+//how fast the simulation runs compared to real time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TimeScale{
+    Scaled(f32),
+    Unlimited,      //the model ticks as fast as it can and every tick counts as one regular tick interval
+}
+
+//the range a scale set from the console is clamped to, anything beyond is what Unlimited is for
+pub(crate) const MIN_TIME_SCALE: f32 = 0.25;
+pub(crate) const MAX_TIME_SCALE: f32 = 8.0;
+
+//the speeds that + and - step through
+const TIME_SCALES: [TimeScale; 7] = [
+    TimeScale::Scaled(0.25),
+    TimeScale::Scaled(0.5),
+    TimeScale::Scaled(1.0),
+    TimeScale::Scaled(2.0),
+    TimeScale::Scaled(4.0),
+    TimeScale::Scaled(8.0),
+    TimeScale::Unlimited,
+];
+
+impl TimeScale{
+    pub(crate) const NORMAL: TimeScale = TimeScale::Scaled(1.0);
+
+    //None for scales that can't be simulated, like nan or everything at or below 0
+    pub(crate) fn clamped(scale: f32) -> Option<TimeScale>{
+        if !scale.is_finite() || scale <= 0.0{
+            return None;
+        }
+        Some(TimeScale::Scaled(scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)))
+    }
+
+    //the first speed of TIME_SCALES above this one, so scales set from the console step to their neighbours
+    pub(crate) fn faster(&self) -> TimeScale{
+        match self{
+            TimeScale::Scaled(scale) => TIME_SCALES.iter().copied().find(|time_scale| match time_scale{
+                TimeScale::Scaled(step) => step > scale,
+                TimeScale::Unlimited => true,
+            }).unwrap_or(TimeScale::Unlimited),
+            TimeScale::Unlimited => TimeScale::Unlimited,
+        }
+    }
+
+    //the last speed of TIME_SCALES below this one, the slowest speed stays where it is
+    pub(crate) fn slower(&self) -> TimeScale{
+        match self{
+            TimeScale::Scaled(scale) => TIME_SCALES.iter().copied().rev().find(|time_scale| matches!(time_scale, TimeScale::Scaled(step) if step < scale)).unwrap_or(TIME_SCALES[0]),
+            TimeScale::Unlimited => TIME_SCALES[TIME_SCALES.len() - 2],
+        }
+    }
+
+    //the simulated time of a tick that took elapsed in real time
+    pub(crate) fn scale(&self, elapsed: Duration) -> Duration{
+        match self{
+            TimeScale::Scaled(scale) if scale.is_finite() => elapsed.mul_f32(scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)),     //mul_f32 panics on nan, inf or overflow
+            TimeScale::Scaled(_) => elapsed,
+            TimeScale::Unlimited => MODEL_TICK_INTERVAL,
+        }
+    }
+}
+
+impl std::fmt::Display for TimeScale{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            TimeScale::Scaled(scale) => write!(f, "{}x", scale),
+            TimeScale::Unlimited => write!(f, "as fast as possible"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn steps_through_the_time_scales(){
+        assert_eq!(TimeScale::NORMAL.faster(), TimeScale::Scaled(2.0));
+        assert_eq!(TimeScale::NORMAL.slower(), TimeScale::Scaled(0.5));
+        assert_eq!(TimeScale::Scaled(8.0).faster(), TimeScale::Unlimited);
+        assert_eq!(TimeScale::Unlimited.faster(), TimeScale::Unlimited);
+        assert_eq!(TimeScale::Scaled(0.25).slower(), TimeScale::Scaled(0.25));
+        assert_eq!(TimeScale::Unlimited.slower(), TimeScale::Scaled(8.0));
+    }
+
+    #[test]
+    fn scales_in_between_step_to_their_neighbours(){
+        assert_eq!(TimeScale::Scaled(3.0).faster(), TimeScale::Scaled(4.0));
+        assert_eq!(TimeScale::Scaled(3.0).slower(), TimeScale::Scaled(2.0));
+    }
+
+    #[test]
+    fn scales_the_elapsed_time(){
+        let elapsed = Duration::from_secs(1);
+        assert_eq!(TimeScale::Scaled(2.0).scale(elapsed), Duration::from_secs(2));
+        assert_eq!(TimeScale::Unlimited.scale(elapsed), MODEL_TICK_INTERVAL);
+        assert_eq!(TimeScale::Scaled(f32::NAN).scale(elapsed), elapsed);
+        assert_eq!(TimeScale::Scaled(1e30).scale(elapsed), elapsed.mul_f32(MAX_TIME_SCALE));
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt::Display;
+
+use wgpu::{Adapter, Instance, Surface};
+
+use super::sprites::atlas::{AtlasEntry, MAX_ATLAS_ENTRIES};
+
+//the atlas pages are at most this big, smaller limits only mean more pages
+const MIN_TEXTURE_SIZE: u32 = 1024;
+
+//what can be chosen on the command line: --backend <vulkan,metal,dx12,dx11,gl> and --fallback-adapter
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GraphicsOptions{
+    pub(crate) backends: wgpu::Backends,
+    pub(crate) force_fallback_adapter: bool,
+}
+
+impl Default for GraphicsOptions{
+    fn default() -> Self{
+        Self{
+            backends: wgpu::Backends::all(),
+            force_fallback_adapter: false,
+        }
+    }
+}
+
+impl GraphicsOptions{
+    pub(crate) fn from_args(args: &[String]) -> Result<Self, GraphicsError>{
+        let mut options = Self::default();
+        if let Some(index) = args.iter().position(|arg| arg == "--backend"){
+            let list = args.get(index + 1).ok_or(GraphicsError::UnknownBackend(String::new()))?;
+            options.backends = parse_backends(list)?;
+        }
+        options.force_fallback_adapter = args.iter().any(|arg| arg == "--fallback-adapter");
+        Ok(options)
+    }
+}
+
+fn parse_backends(list: &str) -> Result<wgpu::Backends, GraphicsError>{
+    let mut backends = wgpu::Backends::empty();
+    for name in list.split(',').map(|name| name.trim().to_lowercase()){
+        backends |= match name.as_str(){
+            "vulkan" | "vk" => wgpu::Backends::VULKAN,
+            "metal" | "mtl" => wgpu::Backends::METAL,
+            "dx12" | "d3d12" => wgpu::Backends::DX12,
+            "dx11" | "d3d11" => wgpu::Backends::DX11,
+            "gl" | "opengl" | "gles" => wgpu::Backends::GL,
+            "all" => wgpu::Backends::all(),
+            _ => return Err(GraphicsError::UnknownBackend(name)),
+        };
+    }
+    Ok(backends)
+}
+
+#[derive(Debug)]
+pub(crate) enum GraphicsError{
+    UnknownBackend(String),
+    Window(winit::error::OsError),
+    Surface(wgpu::CreateSurfaceError),
+    NoAdapter{ backends: wgpu::Backends, force_fallback_adapter: bool },
+    IncompatibleSurface(wgpu::AdapterInfo),
+    MissingCapability{ adapter: wgpu::AdapterInfo, missing: String },
+    Device(wgpu::RequestDeviceError),
+}
+
+impl Display for GraphicsError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            GraphicsError::UnknownBackend(name) => write!(f, "unknown backend \"{}\", expected a comma separated list of vulkan, metal, dx12, dx11, gl or all", name),
+            GraphicsError::Window(error) => write!(f, "could not open the window: {}", error),
+            GraphicsError::Surface(error) => write!(f, "could not create a surface for the window: {}", error),
+            GraphicsError::NoAdapter{ backends, force_fallback_adapter: true } => write!(f, "there is no software adapter for the backends {:?}, try without --fallback-adapter or with another --backend", backends),
+            GraphicsError::NoAdapter{ backends, force_fallback_adapter: false } => write!(f, "no graphics adapter (not even a software one) was found for the backends {:?}. Updating the graphics driver or trying another --backend might help, --render-level still works without one", backends),
+            GraphicsError::IncompatibleSurface(adapter) => write!(f, "{} ({:?}) can't draw into the window", adapter.name, adapter.backend),
+            GraphicsError::MissingCapability{ adapter, missing } => write!(f, "{} ({:?}) is not supported: {}", adapter.name, adapter.backend, missing),
+            GraphicsError::Device(error) => write!(f, "could not open the graphics device: {}", error),
+        }
+    }
+}
+
+//asks for a hardware adapter first and for the software adapter of wgpu if there is none or it can't run the game, unless the software adapter is forced anyway
+pub(crate) async fn select_adapter(instance: &Instance, surface: Option<&Surface>, options: &GraphicsOptions) -> Result<Adapter, GraphicsError>{
+    let mut attempts = vec![options.force_fallback_adapter];
+    if !options.force_fallback_adapter{
+        attempts.push(true);
+    }
+    let mut error = GraphicsError::NoAdapter{ backends: options.backends, force_fallback_adapter: options.force_fallback_adapter };
+    for force_fallback_adapter in attempts{
+        let adapter = instance.request_adapter(
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: surface,
+                force_fallback_adapter,
+            },
+        ).await;
+        let Some(adapter) = adapter else{
+            continue;
+        };
+        let info = adapter.get_info();
+        if let Some(surface) = surface{
+            if surface.get_capabilities(&adapter).formats.is_empty(){
+                error = GraphicsError::IncompatibleSurface(info);
+                continue;
+            }
+        }
+        if let Err(missing) = check_capabilities(&adapter){
+            println!("Skipping adapter: {}", missing);
+            error = missing;
+            continue;
+        }
+        println!("Rendering with {} ({:?}, {:?})", info.name, info.backend, info.device_type);
+        return Ok(adapter);
+    }
+    Err(error)
+}
+
+//the atlas path needs no optional features, only a few limits that even old and mobile adapters have
+fn check_capabilities(adapter: &Adapter) -> Result<(), GraphicsError>{
+    let limits = adapter.limits();
+    let missing = |missing: String| Err(GraphicsError::MissingCapability{ adapter: adapter.get_info(), missing });
+    if limits.max_texture_dimension_2d < MIN_TEXTURE_SIZE{
+        return missing(format!("textures have to be at least {0}x{0} pixels, but the adapter only supports {1}x{1}", MIN_TEXTURE_SIZE, limits.max_texture_dimension_2d));
+    }
+    let atlas_size = (MAX_ATLAS_ENTRIES * std::mem::size_of::<AtlasEntry>()) as u32;
+    if limits.max_uniform_buffer_binding_size < atlas_size{
+        return missing(format!("the sprite atlas needs uniform buffers of {} bytes, but the adapter only supports {}", atlas_size, limits.max_uniform_buffer_binding_size));
+    }
+    if limits.max_vertex_attributes < 10{
+        return missing(format!("sprites need 10 vertex attributes, but the adapter only supports {}", limits.max_vertex_attributes));
+    }
+    Ok(())
+}
+
+//only what the game needs, with the texture size of the adapter, so the atlas can use as big pages as possible
+pub(crate) fn required_limits(adapter: &Adapter) -> wgpu::Limits{
+    wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+}
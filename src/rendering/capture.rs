@@ -0,0 +1,231 @@
+use std::{path::PathBuf, time::SystemTime, io::Cursor, sync::Arc};
+
+use async_std::task::block_on;
+use flume::{Receiver, Sender};
+use image::RgbaImage;
+
+use crate::{cam_organizer::cam_organizer::{CamState, CamOrganizer}, model::{model::{Model, ModelChannels, MODEL_TICK_INTERVAL}, load_level_functions::Level, tick_clock::TickClock, render_snapshot::{new_shared_render_snapshot, SharedRenderSnapshot}}, rendering::debug_overlay::stats::DebugStats, controller::{controller_commands::ControllerCommand, input::ControllerInput}};
+
+use super::{wgpurenderer::{Renderer, RenderChunk}, init::init_headless, sprites::load_sprites::{try_load_sprites, SpritePipeline}, tilemap::{TileLayer, TileMapData}, fog_of_war::{FogLayer, FogData}, software_renderer::SoftwareRenderer, capabilities::GraphicsOptions, text::{text_layer::TextLayer, layout::GlyphInstance}};
+
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+impl Renderer{
+    //draws the frame into an offscreen texture of the size of the window and copies it back from the GPU
+    pub(crate) fn render_to_image(&mut self, render_ops: &[RenderChunk], glyphs: &[GlyphInstance], cam_state: &CamState, sprite_pipeline: &SpritePipeline) -> Result<RgbaImage, String>{
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen render target"),
+            size: wgpu::Extent3d { width: self.config.width, height: self.config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,     //the pipelines are created for this format
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.draw_frame(&view, render_ops, glyphs, cam_state, sprite_pipeline);
+        self.read_texture(&texture)
+    }
+
+    //blocks until the GPU is done with everything submitted so far
+    fn read_texture(&self, texture: &wgpu::Texture) -> Result<RgbaImage, String>{
+        let (width, height) = (self.config.width, self.config.height);
+        let unpadded_bytes_per_row = 4 * width;
+        //every row of a texture copy has to start at a multiple of 256 bytes
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = flume::bounded(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| { let _ = sender.send(result); });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().map_err(|error| error.to_string())?.map_err(|error| format!("could not read the frame back: {}", error))?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize){
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        match self.config.format{
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {},
+            //most windows use bgra
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => pixels.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2)),
+            format => return Err(format!("frames in the format {:?} can't be saved", format)),
+        }
+        RgbaImage::from_raw(width, height, pixels).ok_or_else(|| "the frame has the wrong size".to_string())
+    }
+}
+
+//writes the image to screenshots/spawg_<unix time in ms>.png. Encoding takes a while, so it doesn't happen on the render thread
+pub(crate) fn save_screenshot(image: RgbaImage){
+    std::thread::spawn(move || {
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_millis();
+        let path = PathBuf::from(SCREENSHOT_DIRECTORY).join(format!("spawg_{}.png", timestamp));
+        let result = std::fs::create_dir_all(SCREENSHOT_DIRECTORY).map_err(|error| error.to_string())
+            .and_then(|_| image.save(&path).map_err(|error| error.to_string()));
+        match result{
+            Ok(_) => println!("Saved screenshot to {}", path.display()),
+            Err(error) => eprintln!("Could not save screenshot to {}: {}", path.display(), error),
+        }
+    });
+}
+
+//a model without its thread, it only loads a level so its first frame can be rendered without a window
+struct HeadlessModel{
+    model: Model,
+    render_snapshot: SharedRenderSnapshot,
+    tile_map_receiver: Receiver<Option<TileMapData>>,
+    fog_receiver: Receiver<Option<FogData>>,
+    _controller_sender: Sender<ControllerCommand>,     //the other ends of the channels stay open, so the model can send into them while loading
+    _console_receiver: Receiver<ControllerInput>,
+    _sprite_set_receiver: Receiver<u32>,
+}
+
+impl HeadlessModel{
+    fn new() -> Self{
+        let render_snapshot = new_shared_render_snapshot();
+        let (controller_sender, controller_receiver) = flume::unbounded();
+        let (console_sender, console_receiver) = flume::unbounded();
+        let (tile_map_sender, tile_map_receiver) = flume::unbounded();
+        let (fog_sender, fog_receiver) = flume::unbounded();
+        let (sprite_set_sender, sprite_set_receiver) = flume::unbounded();
+        let channels = ModelChannels{ controller_receiver, tile_map_sender, fog_sender, sprite_set_sender, console_sender };
+        let model = Model::new(channels, render_snapshot.clone(), Arc::new(TickClock::new(MODEL_TICK_INTERVAL)), Arc::new(DebugStats::default()));
+        Self{
+            model,
+            render_snapshot,
+            tile_map_receiver,
+            fog_receiver,
+            _controller_sender: controller_sender,
+            _console_receiver: console_receiver,
+            _sprite_set_receiver: sprite_set_receiver,
+        }
+    }
+
+    async fn frame(&self) -> (Vec<RenderChunk>, Vec<GlyphInstance>){
+        self.model.publish_render_snapshot(0).await;
+        let snapshot = self.render_snapshot.load();
+        (CamOrganizer::collect_render_chunks(&snapshot, None), CamOrganizer::collect_glyphs(&snapshot, None))
+    }
+
+    //draws the loaded level with the whole pipeline: sprites, tile map, fog of war and text
+    async fn render_with(&self, mut renderer: Renderer, sprite_set: u32, camera: &CamState) -> Result<RgbaImage, String>{
+        let (render_ops, glyphs) = self.frame().await;
+        let sprite_pipeline = try_load_sprites(sprite_set, &renderer)?;
+        renderer.tile_layer = Some(TileLayer::new(&renderer));
+        renderer.tile_map_receiver = Some(self.tile_map_receiver.clone());
+        renderer.fog_layer = Some(FogLayer::new(&renderer));
+        renderer.fog_receiver = Some(self.fog_receiver.clone());
+        renderer.update_tile_map();
+        renderer.update_fog();
+        renderer.text_layer = Some(TextLayer::new(&renderer));
+
+        renderer.render_to_image(&render_ops, &glyphs, camera, &sprite_pipeline)
+    }
+}
+
+//the software adapter of wgpu, so the result is the same on every machine and can be compared against golden images
+fn headless_options() -> GraphicsOptions{
+    GraphicsOptions{ force_fallback_adapter: true, ..Default::default() }
+}
+
+//loads the level without starting the model, the controller or a window and renders its first frame with the software adapter of wgpu
+pub(crate) fn render_level_to_image(level: Level, camera: CamState, size: (u32, u32)) -> Result<RgbaImage, String>{
+    block_on(async{
+        let mut headless = HeadlessModel::new();
+        headless.model.load_level(level).await;
+        match init_headless(size, &headless_options()).await{
+            Ok(renderer) => headless.render_with(renderer, level.sprite_set(), &camera).await,
+            Err(error) => {
+                //without any adapter there is still the software renderer, it only draws the sprites and no text
+                println!("{}, rendering the sprites on the cpu instead", error);
+                let (render_ops, _) = headless.frame().await;
+                SoftwareRenderer::for_level(level.sprite_set(), size).map(|renderer| renderer.render(&render_ops, &camera))
+            }
+        }
+    })
+}
+
+//same as render_level_to_image, but encoded as png
+pub(crate) fn render_level_to_png(level: Level, camera: CamState, size: (u32, u32)) -> Result<Vec<u8>, String>{
+    let image = render_level_to_image(level, camera, size)?;
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png).map_err(|error| error.to_string())?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests{
+    use std::path::Path;
+
+    use crate::controller::controller::{CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT};
+
+    use super::*;
+
+    const GOLDEN_DIRECTORY: &str = "textures/golden";
+    const GOLDEN_SIZE: (u32, u32) = (640, 360);
+    const CHANNEL_TOLERANCE: u8 = 8;        //drivers round and filter a little differently
+    const MAX_DIFFERENT_PIXELS: f32 = 0.01;
+    const GOLDEN_SEED: u64 = 7;
+
+    //set SPAWG_BLESS_GOLDEN to write the current frame as the new golden image, after a change that is meant to look different
+    fn compare_with_golden(name: &str, image: &RgbaImage){
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_DIRECTORY).join(format!("{}.png", name));
+        if std::env::var_os("SPAWG_BLESS_GOLDEN").is_some(){
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            image.save(&path).unwrap();
+            return;
+        }
+        let golden = image::open(&path).unwrap_or_else(|error| panic!("could not open {}: {}", path.display(), error)).to_rgba8();
+        assert_eq!(golden.dimensions(), image.dimensions());
+        let different = golden.pixels().zip(image.pixels())
+            .filter(|(expected, actual)| expected.0.iter().zip(actual.0.iter()).any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE))
+            .count();
+        let allowed = (MAX_DIFFERENT_PIXELS * (golden.width() * golden.height()) as f32) as usize;
+        assert!(different <= allowed, "{} pixels differ from {}", different, path.display());
+    }
+
+    //the strategy level with a fixed seed, so its tiles, the player base and the workers around it are always at the same spots
+    #[test]
+    fn strategy_level_matches_its_golden_image(){
+        let renderer = match block_on(init_headless(GOLDEN_SIZE, &headless_options())){
+            Ok(renderer) => renderer,
+            Err(error) => {
+                //the cpu fallback of render_level_to_image draws no text and no tiles, so it can't be compared against the golden image
+                eprintln!("skipping strategy_level_matches_its_golden_image: {}", error);
+                return;
+            }
+        };
+        let mut headless = HeadlessModel::new();
+        block_on(headless.model.load_strategy_test_with_seed(Some(GOLDEN_SEED)));
+        let camera = CamState{ cam_size: [CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT], cam_pos: [0.0, 0.0] };
+        let image = block_on(headless.render_with(renderer, Level::StrategyTest.sprite_set(), &camera)).unwrap();
+        compare_with_golden("strategy_test", &image);
+    }
+}
@@ -0,0 +1,40 @@
+// Debug overlay shader, draws solid rectangles given in pixels of the window, independent of the camera
+
+struct RectInput {
+    @location(0) rect: vec4<f32>,      // x, y of the top left corner, width, height
+    @location(1) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+struct Screen {
+    size: vec4<f32>,    // only xy is used, the rest pads the uniform
+}
+
+@group(0) @binding(0)
+var<uniform> screen: Screen;
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    rect: RectInput,
+) -> VertexOutput {
+    // two triangles, the pipeline doesn't cull, so the winding doesn't matter
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(1.0, 0.0),
+    );
+    let pixel = rect.rect.xy + corners[index] * rect.rect.zw;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(pixel.x / screen.size.x * 2.0 - 1.0, 1.0 - pixel.y / screen.size.y * 2.0, 0.0, 1.0);
+    out.color = rect.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
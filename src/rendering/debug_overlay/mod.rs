@@ -0,0 +1,3 @@
+pub(crate) mod stats;
+pub(crate) mod overlay;
+mod pixel_font;
@@ -0,0 +1,282 @@
+use std::{sync::Arc, time::Duration};
+
+use wgpu::{RenderPipeline, BindGroup, Buffer, RenderPass};
+
+use crate::rendering::{wgpurenderer::Renderer, depth::ignored_depth_state, sprites::vertex_configration::{VertexConfigHandle, VertexConfigration}};
+
+use super::{stats::{DebugStats, RenderStats, FRAME_TIME_HISTORY}, pixel_font::{glyph, GLYPH_WIDTH, GLYPH_HEIGHT}};
+
+//every pixel of the font is drawn as a square of this many pixels
+const FONT_SCALE: f32 = 2.0;
+const LINE_HEIGHT: f32 = (GLYPH_HEIGHT as f32 + 2.0) * FONT_SCALE;
+const CHARACTER_ADVANCE: f32 = (GLYPH_WIDTH as f32 + 1.0) * FONT_SCALE;
+const PADDING: f32 = 8.0;
+//the graph has one bar per frame and frame times above this are cut off
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+const GRAPH_MAX_FRAME_TIME: Duration = Duration::from_millis(50);
+const TARGET_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const HEADING_COLOR: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+const FAST_FRAME_COLOR: [f32; 4] = [0.2, 0.9, 0.2, 0.9];
+const SLOW_FRAME_COLOR: [f32; 4] = [0.95, 0.8, 0.1, 0.9];
+const VERY_SLOW_FRAME_COLOR: [f32; 4] = [0.95, 0.15, 0.1, 0.9];
+const TARGET_LINE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+
+//a solid rectangle in pixels of the window, has to match RectInput in debug_overlay.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
+struct OverlayRect{
+    rect: [f32; 4],     //x and y of the top left corner, width and height
+    color: [f32; 4],
+}
+
+const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+    0 => Float32x4,
+    1 => Float32x4,
+];
+
+//the statistics of the model, the CamOrganizer and the renderer in the top left corner of the window, toggled with F3. It is drawn on top of everything and doesn't move with the camera
+#[derive(Debug)]
+pub(crate) struct DebugOverlay{
+    pipeline: RenderPipeline,
+    screen_buffer: Buffer,
+    screen_bind_group: BindGroup,
+    rect_buffer: Buffer,
+    rect_capacity: usize,
+    rect_count: u32,
+    stats: Arc<DebugStats>,
+    pub(crate) visible: bool,
+}
+
+impl DebugOverlay{
+    pub(crate) fn new(renderer: &Renderer, stats: Arc<DebugStats>) -> Self{
+        let device = &renderer.device;
+        let screen_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay Screen Buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let screen_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("overlay_screen_bind_group_layout"),
+        });
+        let screen_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &screen_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("overlay_screen_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("debug_overlay.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Overlay Pipeline Layout"),
+            bind_group_layouts: &[&screen_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout{
+                    array_stride: std::mem::size_of::<OverlayRect>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &ATTRIBUTES,
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: renderer.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(ignored_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let rect_capacity = 4096;
+        Self{
+            pipeline,
+            screen_buffer,
+            screen_bind_group,
+            rect_buffer: create_rect_buffer(device, rect_capacity),
+            rect_capacity,
+            rect_count: 0,
+            stats,
+            visible: false,
+        }
+    }
+
+    //builds the rectangles of this frame and uploads them, has to be called before the render pass is started
+    pub(crate) fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: (u32, u32), render_stats: &RenderStats, dropped_frames: u64){
+        if !self.visible{
+            return;
+        }
+        let rects = self.build(render_stats, dropped_frames);
+        if rects.len() > self.rect_capacity{
+            self.rect_capacity = rects.len().next_power_of_two();
+            self.rect_buffer = create_rect_buffer(device, self.rect_capacity);
+        }
+        queue.write_buffer(&self.screen_buffer, 0, bytemuck::cast_slice(&[size.0 as f32, size.1 as f32, 0.0, 0.0]));
+        queue.write_buffer(&self.rect_buffer, 0, bytemuck::cast_slice(&rects));
+        self.rect_count = rects.len() as u32;
+    }
+
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>){
+        if !self.visible || self.rect_count == 0{
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.screen_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.rect_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.rect_count);
+    }
+
+    fn build(&self, render_stats: &RenderStats, dropped_frames: u64) -> Vec<OverlayRect>{
+        let mut panel = Panel::new();
+        let (game_objects, logic_objects, static_objects) = self.stats.object_counts();
+        let average_frame_time = render_stats.average_frame_time();
+
+        panel.line("PERFORMANCE", HEADING_COLOR);
+        panel.line(&format!("MODEL TPS: {:.1}", self.stats.model_tps()), TEXT_COLOR);
+        panel.line(&format!("ORGANIZER FPS: {:.1}", self.stats.organizer_fps()), TEXT_COLOR);
+        match average_frame_time{
+            Some(frame_time) => panel.line(&format!("RENDER FPS: {:.1} ({:.2} MS)", 1.0 / frame_time.as_secs_f32(), frame_time.as_secs_f32() * 1000.0), TEXT_COLOR),
+            None => panel.line("RENDER FPS: -", TEXT_COLOR),
+        }
+        panel.frame_time_graph(render_stats);
+        panel.line(&format!("DROPPED FRAMES: {}", dropped_frames), TEXT_COLOR);
+        let (paused, time_scale) = self.stats.simulation_speed();
+        panel.line(&format!("SPEED: {}{}", time_scale, if paused { " (PAUSED)" } else { "" }), TEXT_COLOR);
+
+        panel.line("OBJECTS", HEADING_COLOR);
+        panel.line(&format!("GAME: {}  LOGIC: {}  STATIC: {}", game_objects, logic_objects, static_objects), TEXT_COLOR);
+
+        panel.line("INSTANCES", HEADING_COLOR);
+        for (handle, count) in render_stats.instance_counts.iter(){
+            panel.line(&format!("{}: {}", vertex_configuration_name(*handle), count), TEXT_COLOR);
+        }
+        panel.line(&format!("BUFFER REALLOCATIONS: {}", render_stats.buffer_reallocations), TEXT_COLOR);
+        panel.line(&format!("VERTEX BUFFERS CREATED: {}", render_stats.vertex_buffers_created), TEXT_COLOR);
+        panel.finish()
+    }
+}
+
+fn create_rect_buffer(device: &wgpu::Device, capacity: usize) -> Buffer{
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Overlay Rect Buffer"),
+        size: (capacity * std::mem::size_of::<OverlayRect>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn vertex_configuration_name(handle: VertexConfigHandle) -> String{
+    match handle.to_configuration(){
+        VertexConfigration::Dynamic(handle) => format!("DYNAMIC {}", handle.0),
+        configuration => format!("{:?}", configuration),
+    }
+}
+
+//lays out the lines of the overlay from top to bottom, the background grows with them
+struct Panel{
+    rects: Vec<OverlayRect>,
+    cursor_y: f32,
+    width: f32,
+}
+
+impl Panel{
+    fn new() -> Self{
+        Self{
+            rects: vec![OverlayRect{ rect: [0.0; 4], color: BACKGROUND_COLOR }],   //resized in finish, it has to be drawn first
+            cursor_y: PADDING,
+            width: 0.0,
+        }
+    }
+
+    fn line(&mut self, text: &str, color: [f32; 4]){
+        let mut x = PADDING;
+        for character in text.chars(){
+            let rows = glyph(character);
+            for (row, bits) in rows.iter().enumerate(){
+                for column in 0..GLYPH_WIDTH{
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0{
+                        self.rects.push(OverlayRect{
+                            rect: [x + column as f32 * FONT_SCALE, self.cursor_y + row as f32 * FONT_SCALE, FONT_SCALE, FONT_SCALE],
+                            color,
+                        });
+                    }
+                }
+            }
+            x += CHARACTER_ADVANCE;
+        }
+        self.width = self.width.max(x);
+        self.cursor_y += LINE_HEIGHT;
+    }
+
+    //one bar per presented frame, the newest on the right. The line marks 60 fps
+    fn frame_time_graph(&mut self, render_stats: &RenderStats){
+        let top = self.cursor_y;
+        let bottom = top + GRAPH_HEIGHT;
+        let graph_width = FRAME_TIME_HISTORY as f32 * GRAPH_BAR_WIDTH;
+        let scale = GRAPH_HEIGHT / GRAPH_MAX_FRAME_TIME.as_secs_f32();
+        let first_x = PADDING + graph_width - render_stats.frame_times.len() as f32 * GRAPH_BAR_WIDTH;
+        for (index, frame_time) in render_stats.frame_times.iter().enumerate(){
+            let height = (frame_time.as_secs_f32() * scale).min(GRAPH_HEIGHT);
+            let color = if *frame_time <= TARGET_FRAME_TIME.mul_f32(1.05){
+                FAST_FRAME_COLOR
+            }else if *frame_time <= TARGET_FRAME_TIME * 2{
+                SLOW_FRAME_COLOR
+            }else{
+                VERY_SLOW_FRAME_COLOR
+            };
+            self.rects.push(OverlayRect{ rect: [first_x + index as f32 * GRAPH_BAR_WIDTH, bottom - height, GRAPH_BAR_WIDTH, height], color });
+        }
+        let target_y = bottom - TARGET_FRAME_TIME.as_secs_f32() * scale;
+        self.rects.push(OverlayRect{ rect: [PADDING, target_y, graph_width, 1.0], color: TARGET_LINE_COLOR });
+        self.width = self.width.max(PADDING + graph_width);
+        self.cursor_y = bottom + LINE_HEIGHT - GLYPH_HEIGHT as f32 * FONT_SCALE;
+    }
+
+    fn finish(mut self) -> Vec<OverlayRect>{
+        self.rects[0].rect = [0.0, 0.0, self.width + PADDING, self.cursor_y + PADDING - (LINE_HEIGHT - GLYPH_HEIGHT as f32 * FONT_SCALE)];
+        self.rects
+    }
+}
@@ -0,0 +1,110 @@
+use std::{sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering}, collections::VecDeque, time::{Instant, Duration}};
+
+use crate::{rendering::sprites::vertex_configration::VertexConfigHandle, model::time_scale::TimeScale};
+
+//how DebugStats stores TimeScale::Unlimited, it is a NaN and never a real scale. 0 means the model hasn't reported yet
+const UNLIMITED_TIME_SCALE_BITS: u32 = u32::MAX;
+
+//how many frame times the graph of the overlay shows
+pub(crate) const FRAME_TIME_HISTORY: usize = 120;
+
+//what the model and the CamOrganizer report for the debug overlay. They write it whenever their LoopHelper reports a rate, so atomics are enough
+#[derive(Debug, Default)]
+pub(crate) struct DebugStats{
+    model_tps: AtomicU32,           //bits of an f32
+    organizer_fps: AtomicU32,       //bits of an f32
+    game_objects: AtomicUsize,
+    logic_objects: AtomicUsize,
+    static_objects: AtomicUsize,
+    paused: AtomicBool,
+    time_scale: AtomicU32,
+}
+
+impl DebugStats{
+    pub(crate) fn set_model_tps(&self, tps: f64){
+        self.model_tps.store((tps as f32).to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn model_tps(&self) -> f32{
+        f32::from_bits(self.model_tps.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn set_organizer_fps(&self, fps: f64){
+        self.organizer_fps.store((fps as f32).to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn organizer_fps(&self) -> f32{
+        f32::from_bits(self.organizer_fps.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn set_object_counts(&self, game_objects: usize, logic_objects: usize, static_objects: usize){
+        self.game_objects.store(game_objects, Ordering::Relaxed);
+        self.logic_objects.store(logic_objects, Ordering::Relaxed);
+        self.static_objects.store(static_objects, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_simulation_speed(&self, paused: bool, time_scale: TimeScale){
+        self.paused.store(paused, Ordering::Relaxed);
+        let bits = match time_scale{
+            TimeScale::Scaled(scale) => scale.to_bits(),
+            TimeScale::Unlimited => UNLIMITED_TIME_SCALE_BITS,
+        };
+        self.time_scale.store(bits, Ordering::Relaxed);
+    }
+
+    //whether the model is paused and how fast it runs otherwise
+    pub(crate) fn simulation_speed(&self) -> (bool, TimeScale){
+        let time_scale = match self.time_scale.load(Ordering::Relaxed){
+            0 => TimeScale::NORMAL,
+            UNLIMITED_TIME_SCALE_BITS => TimeScale::Unlimited,
+            bits => TimeScale::Scaled(f32::from_bits(bits)),
+        };
+        (self.paused.load(Ordering::Relaxed), time_scale)
+    }
+
+    //game, logic and static objects
+    pub(crate) fn object_counts(&self) -> (usize, usize, usize){
+        (self.game_objects.load(Ordering::Relaxed), self.logic_objects.load(Ordering::Relaxed), self.static_objects.load(Ordering::Relaxed))
+    }
+}
+
+//what the renderer measures itself, it is kept up to date even while the overlay is hidden
+#[derive(Debug)]
+pub(crate) struct RenderStats{
+    pub(crate) frame_times: VecDeque<Duration>,     //between the last presented frames, the newest is at the back
+    last_frame: Option<Instant>,
+    pub(crate) instance_counts: Vec<(VertexConfigHandle, usize)>,      //of the last frame
+    pub(crate) buffer_reallocations: u32,    //instance buffers that were too small and had to be replaced
+    pub(crate) vertex_buffers_created: u32,
+}
+
+impl RenderStats{
+    pub(crate) fn new() -> Self{
+        Self{
+            frame_times: VecDeque::with_capacity(FRAME_TIME_HISTORY),
+            last_frame: None,
+            instance_counts: Vec::new(),
+            buffer_reallocations: 0,
+            vertex_buffers_created: 0,
+        }
+    }
+
+    pub(crate) fn frame_presented(&mut self){
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame{
+            if self.frame_times.len() == FRAME_TIME_HISTORY{
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(now - last_frame);
+        }
+        self.last_frame = Some(now);
+    }
+
+    //over the whole history, so a single slow frame doesn't make the number jump
+    pub(crate) fn average_frame_time(&self) -> Option<Duration>{
+        if self.frame_times.is_empty(){
+            return None;
+        }
+        Some(self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32)
+    }
+}
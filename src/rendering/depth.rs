@@ -0,0 +1,106 @@
+use wgpu::{Device, SurfaceConfiguration, TextureView};
+
+pub(crate) const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+//world units after which the y-sort is mostly saturated. Objects further apart than this still sort correctly, the depth differences just get smaller
+const Y_SORT_SCALE: f32 = 1000.0;
+
+//the layers are drawn from back (Background) to front (Overlay), no matter in which order the objects were added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RenderLayer{
+    Background,
+    Ground,         //things lying on the ground, like resources or the tiles of a maze
+    Structures,
+    Units,
+    Overlay,        //always on top, for example markers or debug lines
+}
+
+pub(crate) const NUM_RENDER_LAYERS: usize = 5;
+
+impl RenderLayer{
+    //only layers where objects can overlap each other are y-sorted: lower objects are closer to the viewer and cover the ones above them
+    pub(crate) fn is_y_sorted(&self) -> bool{
+        matches!(self, RenderLayer::Structures | RenderLayer::Units)
+    }
+
+    //the depth that is written into the SpriteInstance. 0.0 is the closest to the viewer and 1.0 the farthest, every layer gets a slice of that range
+    //within a slice the y position decides, objects with the same depth are drawn in the order they were queued
+    pub(crate) fn depth(&self, y: f32) -> f32{
+        let in_layer = if self.is_y_sorted(){
+            0.5 + (y / Y_SORT_SCALE).atan() / std::f32::consts::PI      //maps any y into (0, 1), increasing with y
+        }else{
+            0.5
+        };
+        let slice = (NUM_RENDER_LAYERS - 1 - *self as usize) as f32;
+        (slice + in_layer.clamp(0.001, 0.999)) / NUM_RENDER_LAYERS as f32
+    }
+}
+
+//the depth texture has to have the same size as the surface, so it is recreated whenever the window is resized
+pub(crate) fn create_depth_view(device: &Device, config: &SurfaceConfiguration) -> TextureView{
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("depth texture"),
+        size: wgpu::Extent3d { width: config.width.max(1), height: config.height.max(1), depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+//layers that are drawn in a fixed order around the sprites (ground tiles, fog) don't take part in the depth test, but the pipeline still has to know about the depth attachment
+pub(crate) fn ignored_depth_state() -> wgpu::DepthStencilState{
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const LAYERS: [RenderLayer; NUM_RENDER_LAYERS] = [RenderLayer::Background, RenderLayer::Ground, RenderLayer::Structures, RenderLayer::Units, RenderLayer::Overlay];
+
+    #[test]
+    fn later_layers_are_always_in_front(){
+        //even the farthest object of a layer is closer than the closest one of the layer below it
+        for pair in LAYERS.windows(2){
+            let (back, front) = (pair[0], pair[1]);
+            assert!(front.depth(1.0e9) < back.depth(-1.0e9), "{:?} is not in front of {:?}", front, back);
+        }
+    }
+
+    #[test]
+    fn lower_objects_cover_the_ones_above_them(){
+        for layer in [RenderLayer::Structures, RenderLayer::Units]{
+            assert!(layer.depth(-5.0) < layer.depth(0.0));
+            assert!(layer.depth(0.0) < layer.depth(0.01));
+            assert!(layer.depth(100.0) < layer.depth(200.0));
+        }
+    }
+
+    #[test]
+    fn flat_layers_ignore_the_y_position(){
+        for layer in [RenderLayer::Background, RenderLayer::Ground, RenderLayer::Overlay]{
+            assert_eq!(layer.depth(-50.0), layer.depth(50.0));
+        }
+    }
+
+    #[test]
+    fn depths_stay_inside_the_depth_range(){
+        for layer in LAYERS{
+            for y in [f32::MIN, -1.0e6, 0.0, 1.0e6, f32::MAX]{
+                let depth = layer.depth(y);
+                assert!(depth > 0.0 && depth < 1.0, "{:?} at {} has the depth {}", layer, y, depth);
+            }
+        }
+    }
+}
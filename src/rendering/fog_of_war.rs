@@ -0,0 +1,238 @@
+use std::num::NonZeroU64;
+
+use wgpu::{util::DeviceExt, RenderPipeline, BindGroup, BindGroupLayout, Buffer, RenderPass, Texture, Sampler};
+
+use crate::cam_organizer::cam_organizer::CamState;
+
+use super::{wgpurenderer::Renderer, vertex::Vertex, depth::ignored_depth_state};
+
+//what the model sends to the renderer whenever the visibility of the player faction changes. states[y * width + x] is the Visibility (as u8) of the cell (x, y)
+#[derive(Debug)]
+pub(crate) struct FogData{
+    pub(crate) origin: [f32; 2],
+    pub(crate) cell_size: f32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) states: Vec<u8>,
+}
+
+//a single quad over the whole level that is drawn on top of everything else. Unexplored cells are black, explored cells are dimmed
+#[derive(Debug)]
+pub(crate) struct FogLayer{
+    pipeline: RenderPipeline,
+    camera_bind_group: BindGroup,
+    fog_bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+    fog: Option<FogTexture>,
+}
+
+#[derive(Debug)]
+struct FogTexture{
+    texture: Texture,
+    bind_group: BindGroup,
+    vertex_buffer: Buffer,
+    size: (u32, u32),
+}
+
+impl FogLayer{
+    pub(crate) fn new(renderer: &Renderer) -> Self{
+        let device = &renderer.device;
+        //linear filtering makes the border of the vision a soft gradient instead of hard cell edges
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let fog_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("fog_bind_group_layout"),
+        });
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(std::mem::size_of::<CamState>() as u64),
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("fog_camera_bind_group_layout"),
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: renderer.camera_buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("fog_camera_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("fog_of_war.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Fog Pipeline Layout"),
+            bind_group_layouts: &[&fog_bind_group_layout, &camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Fog Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: renderer.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(ignored_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self{
+            pipeline,
+            camera_bind_group,
+            fog_bind_group_layout,
+            sampler,
+            fog: None,
+        }
+    }
+
+    //None removes the fog, for levels without vision
+    pub(crate) fn set_fog(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: Option<FogData>){
+        let Some(data) = data else{
+            self.fog = None;
+            return;
+        };
+        //the texture is only recreated if the size of the grid changes, otherwise the new states are just written into it
+        if self.fog.as_ref().map(|fog| fog.size) != Some((data.width, data.height)){
+            self.fog = Some(self.create_fog_texture(device, &data));
+        }
+        let fog = self.fog.as_ref().unwrap();
+        //0 = unexplored, 1 = explored, 2 = visible, spread over the whole range of the texture
+        let texels: Vec<u8> = data.states.iter().map(|state| state.saturating_mul(127)).collect();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &fog.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            texels.as_slice(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(data.width),
+                rows_per_image: Some(data.height),
+            },
+            wgpu::Extent3d { width: data.width, height: data.height, depth_or_array_layers: 1 },
+        );
+    }
+
+    fn create_fog_texture(&self, device: &wgpu::Device, data: &FogData) -> FogTexture{
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("fog texture"),
+            size: wgpu::Extent3d { width: data.width, height: data.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.fog_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some("fog_bind_group"),
+        });
+        //the quad covers the whole grid, texture row 0 is the bottom row of the grid
+        let left = data.origin[0];
+        let bottom = data.origin[1];
+        let right = left + data.width as f32 * data.cell_size;
+        let top = bottom + data.height as f32 * data.cell_size;
+        let vertices = [
+            Vertex { position: [right, bottom], tex_coords: [1.0, 0.0] },
+            Vertex { position: [right, top], tex_coords: [1.0, 1.0] },
+            Vertex { position: [left, top], tex_coords: [0.0, 1.0] },
+            Vertex { position: [left, bottom], tex_coords: [0.0, 0.0] },
+        ];
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fog Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        FogTexture{
+            texture,
+            bind_group,
+            vertex_buffer,
+            size: (data.width, data.height),
+        }
+    }
+
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, index_buffer: &'a Buffer){
+        let Some(fog) = &self.fog else{
+            return;
+        };
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &fog.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, fog.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..1);
+    }
+}
@@ -0,0 +1,50 @@
+// Fog of war shader, draws a single quad in world space over the whole level
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+struct Camera {
+    position: vec2<f32>,
+    size: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> cam: Camera;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    var semi_size = cam.size / 2.0;
+    out.clip_position = vec4<f32>((model.position - cam.position) / semi_size, 0.0, 1.0);
+    out.tex_coords = model.tex_coords;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_fog: texture_2d<f32>;
+@group(0) @binding(1)
+var s_fog: sampler;
+
+const EXPLORED_ALPHA: f32 = 0.55;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 0.0 = unexplored, 0.5 = explored, 1.0 = visible
+    let state = textureSample(t_fog, s_fog, in.tex_coords).r;
+    var alpha: f32;
+    if state < 0.5 {
+        alpha = mix(1.0, EXPLORED_ALPHA, state * 2.0);
+    } else {
+        alpha = mix(EXPLORED_ALPHA, 0.0, (state - 0.5) * 2.0);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, alpha);
+}
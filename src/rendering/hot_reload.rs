@@ -0,0 +1,99 @@
+use std::{path::{Path, PathBuf}, collections::HashMap, time::{SystemTime, Instant, Duration}};
+
+use async_std::task::block_on;
+
+use crate::rendering::{sprites::{load_level_sprites::manifest::manifest_path_for_level, load_sprites::{try_load_sprites, SpritePipeline}}, wgpurenderer::Renderer};
+
+//how often the watched files are checked for changes, polling is cheap enough for the few files of the game
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+//only used in dev mode (--dev): watches the sprite shader and the texture directory, so changes show up without a rebuild
+#[derive(Debug)]
+pub(crate) struct HotReloader{
+    pub(crate) shader_path: PathBuf,
+    texture_directory: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+    last_poll: Instant,
+}
+
+impl HotReloader{
+    pub(crate) fn new(level: u32) -> Self{
+        //the shader is compiled into the game, the file only exists in the source tree
+        let shader_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/rendering/shader.wgsl");
+        //the manifests are in textures/manifests, so this also covers them
+        let manifest_path = manifest_path_for_level(level);
+        let texture_directory = manifest_path.parent().and_then(|manifests| manifests.parent()).unwrap_or(Path::new("textures")).to_path_buf();
+        println!("Dev mode: watching {} and {} for changes", shader_path.display(), texture_directory.display());
+        let mut reloader = Self{
+            shader_path,
+            texture_directory,
+            modified: HashMap::new(),
+            last_poll: Instant::now(),
+        };
+        reloader.modified = reloader.scan();
+        reloader
+    }
+
+    //returns true if any watched file was added, removed or changed since the last call that returned true
+    pub(crate) fn poll(&mut self) -> bool{
+        if self.last_poll.elapsed() < POLL_INTERVAL{
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let modified = self.scan();
+        if modified == self.modified{
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, SystemTime>{
+        let mut modified = HashMap::new();
+        add_modified_time(&self.shader_path, &mut modified);
+        let mut directories = vec![self.texture_directory.clone()];
+        while let Some(directory) = directories.pop(){
+            let Ok(entries) = std::fs::read_dir(&directory) else{
+                continue;
+            };
+            for entry in entries.flatten(){
+                let path = entry.path();
+                if path.is_dir(){
+                    directories.push(path);
+                }else{
+                    add_modified_time(&path, &mut modified);
+                }
+            }
+        }
+        modified
+    }
+}
+
+fn add_modified_time(path: &Path, modified: &mut HashMap<PathBuf, SystemTime>){
+    if let Ok(time) = std::fs::metadata(path).and_then(|metadata| metadata.modified()){
+        modified.insert(path.to_path_buf(), time);
+    }
+}
+
+impl Renderer{
+    //reads the shader from disk and rebuilds the sprite pipeline and bind groups. On any error the old shader stays in place and the error is returned instead of crashing
+    pub(crate) fn hot_reload(&mut self, shader_path: &Path, level: u32) -> Result<SpritePipeline, String>{
+        let source = std::fs::read_to_string(shader_path).map_err(|error| format!("could not read {}: {}", shader_path.display(), error))?;
+        //validation errors (including shader compile errors) would otherwise go to the default handler, which panics
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let old_shader = std::mem::replace(&mut self.shader, shader);
+        let result = try_load_sprites(level, self);
+        if let Some(error) = block_on(self.device.pop_error_scope()){
+            self.shader = old_shader;
+            return Err(error.to_string());
+        }
+        if result.is_err(){
+            self.shader = old_shader;
+        }
+        result
+    }
+}
@@ -1,23 +1,23 @@
-use std::{sync::{atomic::AtomicBool, Arc}, num::NonZeroU32, io::Read, cell::RefCell};
+use std::{sync::{atomic::AtomicBool, Arc}, cell::RefCell};
 
-use wgpu::{Queue, Surface, Device, SurfaceConfiguration, RenderPipeline, util::DeviceExt, ShaderModule};
-use winit::{window::{Window, WindowBuilder, Fullscreen}, event_loop::{EventLoop, self, EventLoopBuilder}, dpi::PhysicalSize, event::WindowEvent};
+use wgpu::{Queue, Surface, Device, SurfaceConfiguration, util::DeviceExt};
+use winit::{window::{Window, WindowBuilder}, event_loop::{self, EventLoopBuilder}, dpi::PhysicalSize, event::WindowEvent};
 
-use crate::{controller::{position::Position, controller::{SharablePosition, CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT}}, rendering::{sprites::load_sprites::Camera, wgpurenderer::VertexBufferStructs}};
+use crate::{controller::controller::{CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT}, rendering::sprites::load_sprites::Camera};
 
-use super::{wgpurenderer::{Renderer, VertexBuffers, VertexBufferStruct, InstanceBufferState}, vertex::Vertex, sprites::vertex_configration::{VertexConfigration, VertexConfigrationTrait}, sprite_instance::SpriteInstance};
+use super::{wgpurenderer::{Renderer, InstanceBufferState}, sprites::vertex_configration::{VertexConfigration, VertexConfigrationTrait}, sprite_instance::SpriteInstance, depth::create_depth_view, capabilities::{GraphicsOptions, GraphicsError, select_adapter, required_limits}, debug_overlay::stats::RenderStats};
 
 // Creating some of the wgpu types requires async code
-pub async fn init(running: Arc<AtomicBool>, cam_position: SharablePosition) -> (Renderer, event_loop::EventLoop<WindowEvent<'static>>) {
+pub async fn init(running: Arc<AtomicBool>, options: &GraphicsOptions) -> Result<(Renderer, event_loop::EventLoop<WindowEvent<'static>>), GraphicsError> {
     let event_loop = EventLoopBuilder::<WindowEvent>::with_user_event().build();          //event loop is the basic loop of a window. A window needs one, otherwise it does nothing
     const FORMAT: f64 = 16.0 / 9.0;                  //the aspect ratio of the window
     let requested_size = PhysicalSize::new(1400, (1400.0 / FORMAT) as u32);
-    let window = WindowBuilder::new().with_inner_size(requested_size).with_title("spawg").build(&event_loop).unwrap();     //builds a window with the event loop. We could open multiple windows from a single program, but for now we don't need to
+    let window = WindowBuilder::new().with_inner_size(requested_size).with_title("spawg").build(&event_loop).map_err(GraphicsError::Window)?;     //builds a window with the event loop. We could open multiple windows from a single program, but for now we don't need to
     let size = window.inner_size();
     // the instance is an actual wgpu object that we use to do everything in
-    // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
+    // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU, unless --backend says otherwise
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
+        backends: options.backends,
         dx12_shader_compiler: Default::default(),
     });
     
@@ -25,31 +25,23 @@ pub async fn init(running: Arc<AtomicBool>, cam_position: SharablePosition) -> (
     //
     // The surface needs to live as long as the window that created it.
     // State owns the window so this should be safe. This unsafe block is required, otherwise it won't work
-    let surface = unsafe { instance.create_surface(&window) }.unwrap();
+    let surface = unsafe { instance.create_surface(&window) }.map_err(GraphicsError::Surface)?;
 
 
-    //an adapter means a physical connection (to a GPU or other device) that supports the given Options
-    let adapter = instance.request_adapter(
-        &wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        },
-    ).await.unwrap();
+    //an adapter means a physical connection (to a GPU or other device) that supports the given Options, see capabilities.rs for how it is chosen
+    let adapter = select_adapter(&instance, Some(&surface), options).await?;
 
 
     let (device, queue) = adapter.request_device(      //this is the actual device (logical device) that we will use to render, this communicates with the physical device. The queue is what is used to execute command buffers (groups of commands to the GPU)
         &wgpu::DeviceDescriptor {
-            features: (wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER | /* <-- this is a bitwise operator, not a logical OR, which practically means AND here */ wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-                | wgpu::Features::TEXTURE_BINDING_ARRAY),            //you can get a list of supported features by calling adapter.features() or device.features()
+            features: wgpu::Features::empty(),            //the sprites are packed into an atlas, so no optional features are needed. You can get a list of supported features by calling adapter.features() or device.features()
             // WebGL doesn't support all of wgpu's features, so if
             // we're building for the web we'll have to disable some.
-            limits: wgpu::Limits::default()
-            ,
+            limits: required_limits(&adapter),
             label: None,
         },
         None, // Trace path
-    ).await.unwrap();
+    ).await.map_err(GraphicsError::Device)?;
 
     let surface_caps = surface.get_capabilities(&adapter);
 
@@ -80,6 +72,47 @@ pub async fn init(running: Arc<AtomicBool>, cam_position: SharablePosition) -> (
     };
     surface.configure(&device, &config);
 
+    //now we create a struct that holds all these important things, so we can use it 
+    Ok((
+        create_renderer(Some(window), Some(surface), device, queue, config, running),
+        event_loop
+    ))
+}
+
+//a renderer without window and surface, it can only render into textures (see capture.rs). With force_fallback_adapter the frames are rendered by the software adapter, which gives the same pixels on every machine
+pub(crate) async fn init_headless(size: (u32, u32), options: &GraphicsOptions) -> Result<Renderer, GraphicsError> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: options.backends,
+        dx12_shader_compiler: Default::default(),
+    });
+    let adapter = select_adapter(&instance, None, options).await?;
+
+    let (device, queue) = adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            features: wgpu::Features::empty(),
+            limits: required_limits(&adapter),
+            label: None,
+        },
+        None,
+    ).await.map_err(GraphicsError::Device)?;
+
+    //there is no surface, but the pipelines are created for the format of the config, so it has to exist anyway
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        width: size.0,
+        height: size.1,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+        view_formats: vec![],
+    };
+    Ok(create_renderer(None, None, device, queue, config, Arc::new(AtomicBool::new(true))))
+}
+
+//everything that doesn't depend on the window
+fn create_renderer(window: Option<Window>, surface: Option<Surface>, device: Device, queue: Queue, config: SurfaceConfiguration, running: Arc<AtomicBool>) -> Renderer {
+    let depth_view = create_depth_view(&device, &config);
+
 
     let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
 
@@ -97,7 +130,6 @@ pub async fn init(running: Arc<AtomicBool>, cam_position: SharablePosition) -> (
         }
     );
 
-
     let cam_size = [CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT];
     let camera: Camera = Camera{
         size: cam_size,
@@ -111,65 +143,36 @@ pub async fn init(running: Arc<AtomicBool>, cam_position: SharablePosition) -> (
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
     });
 
-    let vertex_structs: VertexBufferStructs = load_buffer_structs(&device);
-
-
-    //now we create a struct that holds all these important things, so we can use it 
-    (
-        Renderer{
-            window,
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            running,
-            shader,
-            render_receiver: None,
-            index_buffer,
-            vertex_structs,
-            cam_size,
-            camera_buffer: uniform_camera_buffer,
-            to_upgrade_vec: RefCell::new(Vec::new()),
-        },
-        event_loop
-    )
-}
-
-
-fn load_buffer_structs(device: &Device) -> VertexBufferStructs{
-
-    [
-        VertexBufferStruct{
-            vertex_buffer: create_vertex_buffer_for_config(device, VertexConfigration::SQUARE_SMALL_1),
-            instance_state: create_initial_instance_buffer_struct(device, 10),
-        },
-        VertexBufferStruct{
-            vertex_buffer: create_vertex_buffer_for_config(device, VertexConfigration::NEARLY_SQUARE_RECTANGLE_0),
-            instance_state: create_initial_instance_buffer_struct(device, 1500),
-        },
-        VertexBufferStruct{
-            vertex_buffer: create_vertex_buffer_for_config(device,VertexConfigration::LINE_HORIZONTAL),
-            instance_state: create_initial_instance_buffer_struct(device, 1000),
-        },
-        VertexBufferStruct{
-            vertex_buffer:  create_vertex_buffer_for_config(device,VertexConfigration::LINE_VERTICAL),
-            instance_state: create_initial_instance_buffer_struct(device, 1000),
-        },
-        VertexBufferStruct{
-            vertex_buffer: create_vertex_buffer_for_config(device,VertexConfigration::SMALL_ENTITY_WORKER),
-            instance_state: create_initial_instance_buffer_struct(device, 50),
-        },
-        VertexBufferStruct{
-            vertex_buffer: create_vertex_buffer_for_config(device,VertexConfigration::BIG_BASE_BASE_4X2),
-            instance_state: create_initial_instance_buffer_struct(device, 10),
-        },
-
-    ]
-
+    let size = PhysicalSize::new(config.width, config.height);
+    Renderer{
+        window,
+        surface,
+        device,
+        queue,
+        config,
+        size,
+        running,
+        shader,
+        frame_mailbox: None,
+        index_buffer,
+        vertex_structs: Vec::new(),    //created when a vertex configuration is first rendered
+        cam_size,
+        camera_buffer: uniform_camera_buffer,
+        to_upgrade_vec: RefCell::new(Vec::new()),
+        tile_layer: None,
+        tile_map_receiver: None,
+        fog_layer: None,
+        fog_receiver: None,
+        depth_view,
+        screenshot_requested: false,
+        render_stats: RenderStats::new(),
+        text_layer: None,
+        ui_layer: None,
+        debug_overlay: None,
+    }
 }
 
-fn create_initial_instance_buffer_struct(device: &Device, instance_size: usize) -> InstanceBufferState{
+pub(crate) fn create_initial_instance_buffer_struct(device: &Device, instance_size: usize) -> InstanceBufferState{
     
     let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Instance Buffer"),
@@ -185,7 +188,7 @@ fn create_initial_instance_buffer_struct(device: &Device, instance_size: usize)
 
 
 
-fn create_vertex_buffer_for_config(device: &Device, config: VertexConfigration)-> wgpu::Buffer{
+pub(crate) fn create_vertex_buffer_for_config(device: &Device, config: VertexConfigration)-> wgpu::Buffer{
     let vertices = config.get_vertices();
     let vertex_buffer = device.create_buffer_init(
         &wgpu::util::BufferInitDescriptor {
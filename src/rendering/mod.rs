@@ -4,3 +4,14 @@ pub mod window_event_loop;
 pub(crate) mod sprites;
 pub mod vertex;
 pub mod sprite_instance;
+pub mod tilemap;
+pub mod fog_of_war;
+pub mod depth;
+
+pub mod hot_reload;
+pub mod capture;
+pub mod software_renderer;
+pub mod capabilities;
+pub mod debug_overlay;
+pub mod text;
+pub mod ui_layer;
\ No newline at end of file
@@ -8,15 +8,38 @@ struct VertexInput {
 struct InstanceInput {
     @location(2) sprite_position: vec2<f32>,
     @location(3) tex_i: u32,
+    @location(4) depth: f32,
+    @location(5) frame: u32,
+    @location(6) scale: vec2<f32>,
+    @location(7) rotation: f32,
+    @location(8) flip: u32,
+    @location(9) tint: vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
-    @location(1) tex_i: u32
+    @location(1) @interpolate(flat) page: u32,
+    @location(2) tint: vec4<f32>,
 
 };
 
+// where a sprite is in the atlas, has to match AtlasEntry in atlas.rs
+struct AtlasEntry {
+    uv_rect: vec4<f32>,
+    page: u32,
+    frames_start: u32,
+    pivot: vec2<f32>,
+    frame_count: u32,
+}
+
+// have to match FLIP_X and FLIP_Y in sprite_instance.rs
+const FLIP_X: u32 = 1u;
+const FLIP_Y: u32 = 2u;
+
+// has to match MAX_ATLAS_ENTRIES in atlas.rs
+const MAX_ATLAS_ENTRIES: u32 = 256u;
+
 struct Camera {
     position: vec2<f32>,
     size: vec2<f32>,
@@ -25,26 +48,57 @@ struct Camera {
 @group(1) @binding(0)
 var<uniform> cam: Camera;
 
+@group(0) @binding(0)
+var t_diffuse: texture_2d_array<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+@group(0) @binding(2)
+var<uniform> atlas: array<AtlasEntry, MAX_ATLAS_ENTRIES>;
+
+// SoftwareRenderer (software_renderer.rs) does the same on the cpu, changes here have to be made there as well
 @vertex
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
+    let sprite = atlas[instance.tex_i];
+    // frame 0 is the sprite itself, the other frames of a sprite sheet are stored one after another starting at frames_start
+    var entry = sprite;
+    let frame = instance.frame % sprite.frame_count;
+    if frame > 0u {
+        entry = atlas[sprite.frames_start + frame - 1u];
+    }
+    var tex_coords = model.tex_coords;
+    if (instance.flip & FLIP_X) != 0u {
+        tex_coords.x = 1.0 - tex_coords.x;
+    }
+    if (instance.flip & FLIP_Y) != 0u {
+        tex_coords.y = 1.0 - tex_coords.y;
+    }
+    out.tex_coords = entry.uv_rect.xy + tex_coords * entry.uv_rect.zw;
+    out.tint = instance.tint;
+    out.page = entry.page;
+    // all vertex configurations are centered rectangles, so the quad is shifted by how far the pivot is from the center. The image y axis points downwards
+    let quad_size = abs(model.position) * 2.0;
+    let pivot_offset = vec2<f32>(0.5 - sprite.pivot.x, sprite.pivot.y - 0.5) * quad_size;
+    // scale and rotation happen around the position of the object, after the pivot has been applied
+    let local = (model.position + pivot_offset) * instance.scale;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
     var semi_size = cam.size / 2.0;
-    out.clip_position = vec4<f32>(model.position /semi_size + (instance.sprite_position - cam.position)/ semi_size, 0.0, 1.0);
-    out.tex_i = instance.tex_i;
+    out.clip_position = vec4<f32>(rotated /semi_size + (instance.sprite_position - cam.position)/ semi_size, instance.depth, 1.0);
     return out;
 }
 
-@group(0) @binding(0)
-var t_diffuse: binding_array<texture_2d<f32>>;
-@group(0)@binding(1)
-var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let image = t_diffuse[in.tex_i];
-    return textureSample(image, s_diffuse, in.tex_coords);
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords, in.page) * in.tint;
+    // transparent pixels must not write into the depth buffer, otherwise they would hide whatever is behind them
+    if color.a < 0.01 {
+        discard;
+    }
+    return color;
 }
\ No newline at end of file
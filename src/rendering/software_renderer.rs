@@ -0,0 +1,270 @@
+use image::RgbaImage;
+
+use crate::cam_organizer::cam_organizer::CamState;
+
+use super::{wgpurenderer::RenderChunk, sprite_instance::{SpriteInstance, FLIP_X, FLIP_Y}, vertex::Vertex, sprites::{load_level_sprites::{manifest::{load_level_sprites, LoadedSprite}, helper_functions::to_srgba}, vertex_configration::VertexConfigrationTrait}};
+
+//same as the clear color of the wgpu renderer, in linear space
+const CLEAR_COLOR: [f32; 4] = [0.1, 0.2, 0.3, 1.0];
+//fragments with less alpha are discarded, see fs_main
+const ALPHA_DISCARD_THRESHOLD: f32 = 0.01;
+
+//draws the sprites of a frame on the cpu, the same way shader.wgsl does on the gpu. It is slow, but it needs no adapter, so the batching and camera math can be checked
+//without a gpu, and there is still a picture on machines wgpu can't run on. Tile map, fog and text are not drawn, the sprites are sampled without filtering
+#[derive(Debug)]
+pub(crate) struct SoftwareRenderer{
+    sprites: Vec<SoftwareSprite>,    //indexed by the texture_id of the instances
+    size: (u32, u32),
+}
+
+#[derive(Debug)]
+struct SoftwareSprite{
+    frames: Vec<LinearImage>,   //frame 0 is the sprite itself, like in the atlas
+    pivot: [f32; 2],
+}
+
+//the gpu samples the atlas after to_srgba, so the images are converted the same way before they are blended
+#[derive(Debug)]
+struct LinearImage{
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl LinearImage{
+    fn new(image: RgbaImage) -> Self{
+        let (width, height) = image.dimensions();
+        let pixels = to_srgba(image).chunks_exact(4).map(|rgba| std::array::from_fn(|channel| rgba[channel] as f32 / 255.0)).collect();
+        Self{ width, height, pixels }
+    }
+
+    //nearest neighbor with clamp to edge, like the sampler of the atlas minus the linear filtering
+    fn sample(&self, uv: [f32; 2]) -> [f32; 4]{
+        let x = ((uv[0] * self.width as f32) as i64).clamp(0, self.width as i64 - 1) as u32;
+        let y = ((uv[1] * self.height as f32) as i64).clamp(0, self.height as i64 - 1) as u32;
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+//one corner of a quad after vs_main
+#[derive(Debug, Clone, Copy)]
+struct ClipVertex{
+    position: [f32; 2],     //clip space, x and y from -1.0 to 1.0 with y up
+    tex_coords: [f32; 2],   //within the frame image
+}
+
+impl SoftwareRenderer{
+    pub(crate) fn new(sprites: Vec<LoadedSprite>, size: (u32, u32)) -> Self{
+        let sprites = sprites.into_iter().map(|loaded| {
+            let mut frames = vec![LinearImage::new(loaded.image)];
+            frames.extend(loaded.extra_frames.into_iter().map(LinearImage::new));
+            SoftwareSprite{ frames, pivot: loaded.pivot }
+        }).collect();
+        Self{ sprites, size }
+    }
+
+    //loads the sprites from the manifest of the level, like load_sprites does for the gpu
+    pub(crate) fn for_level(level: u32, size: (u32, u32)) -> Result<Self, String>{
+        let sprites = load_level_sprites(level).map_err(|error| format!("Could not load the sprites of level {}: {}", level, error))?;
+        Ok(Self::new(sprites, size))
+    }
+
+    //takes what CamOrganizer sends to the renderer. The depth test, culling and blending work like the sprite pipeline in load_sprites.rs
+    pub(crate) fn render(&self, render_ops: &[RenderChunk], cam_state: &CamState) -> RgbaImage{
+        let (width, height) = self.size;
+        let mut color = vec![CLEAR_COLOR; (width * height) as usize];
+        let mut depth = vec![1.0f32; (width * height) as usize];
+        for chunk in render_ops.iter(){
+            let vertices = chunk.vertex_conf.to_configuration().get_vertices();
+            for instance in chunk.instance_buffer.iter(){
+                let Some(sprite) = self.sprites.get(instance.texture_id as usize) else{
+                    continue;   //the gpu would read an empty atlas entry and draw nothing
+                };
+                //frame 0 is the sprite itself, see vs_main
+                let frame = &sprite.frames[instance.frame as usize % sprite.frames.len()];
+                let corners = vertices.map(|vertex| transform_vertex(&vertex, instance, sprite.pivot, cam_state));
+                //the index buffer splits every quad into ABC and ACD
+                for triangle in [[corners[0], corners[1], corners[2]], [corners[0], corners[2], corners[3]]]{
+                    self.draw_triangle(triangle, instance, frame, &mut color, &mut depth);
+                }
+            }
+        }
+        let pixels = color.into_iter().flat_map(|pixel| [linear_to_srgb(pixel[0]), linear_to_srgb(pixel[1]), linear_to_srgb(pixel[2]), (pixel[3].clamp(0.0, 1.0) * 255.0).round() as u8]).collect();
+        RgbaImage::from_raw(width, height, pixels).unwrap()
+    }
+
+    fn draw_triangle(&self, triangle: [ClipVertex; 3], instance: &SpriteInstance, frame: &LinearImage, color: &mut [[f32; 4]], depth: &mut [f32]){
+        //the depth of the whole quad is the one of the instance, anything outside of the depth range is clipped by the gpu
+        if !(0.0..=1.0).contains(&instance.depth){
+            return;
+        }
+        let (width, height) = self.size;
+        let screen = triangle.map(|vertex| [(vertex.position[0] + 1.0) / 2.0 * width as f32, (1.0 - vertex.position[1]) / 2.0 * height as f32]);
+        //the y axis is flipped on screen, so counter-clockwise triangles (the front faces) have a negative area here. Back faces are culled
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area >= 0.0{
+            return;
+        }
+        let min_x = screen.iter().map(|point| point[0]).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let max_x = screen.iter().map(|point| point[0]).fold(f32::MIN, f32::max).ceil().min(width as f32) as u32;
+        let min_y = screen.iter().map(|point| point[1]).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let max_y = screen.iter().map(|point| point[1]).fold(f32::MIN, f32::max).ceil().min(height as f32) as u32;
+        for y in min_y..max_y{
+            for x in min_x..max_x{
+                let point = [x as f32 + 0.5, y as f32 + 0.5];   //pixels are sampled at their center
+                let weights = [edge(screen[1], screen[2], point) / area, edge(screen[2], screen[0], point) / area, edge(screen[0], screen[1], point) / area];
+                if weights.iter().any(|weight| *weight < 0.0){
+                    continue;
+                }
+                let index = (y * width + x) as usize;
+                if instance.depth > depth[index]{
+                    continue;
+                }
+                let u = weights[0] * triangle[0].tex_coords[0] + weights[1] * triangle[1].tex_coords[0] + weights[2] * triangle[2].tex_coords[0];
+                let v = weights[0] * triangle[0].tex_coords[1] + weights[1] * triangle[1].tex_coords[1] + weights[2] * triangle[2].tex_coords[1];
+                let texel = frame.sample([u, v]);
+                let source: [f32; 4] = std::array::from_fn(|channel| texel[channel] * instance.tint[channel]);
+                if source[3] < ALPHA_DISCARD_THRESHOLD{
+                    continue;
+                }
+                //wgpu::BlendState::ALPHA_BLENDING
+                let destination = color[index];
+                let alpha = source[3];
+                color[index] = [
+                    source[0] * alpha + destination[0] * (1.0 - alpha),
+                    source[1] * alpha + destination[1] * (1.0 - alpha),
+                    source[2] * alpha + destination[2] * (1.0 - alpha),
+                    alpha + destination[3] * (1.0 - alpha),
+                ];
+                depth[index] = instance.depth;
+            }
+        }
+    }
+}
+
+//has to match vs_main in shader.wgsl
+fn transform_vertex(vertex: &Vertex, instance: &SpriteInstance, pivot: [f32; 2], cam_state: &CamState) -> ClipVertex{
+    let mut tex_coords = vertex.tex_coords;
+    if instance.flip & FLIP_X != 0{
+        tex_coords[0] = 1.0 - tex_coords[0];
+    }
+    if instance.flip & FLIP_Y != 0{
+        tex_coords[1] = 1.0 - tex_coords[1];
+    }
+    let quad_size = [vertex.position[0].abs() * 2.0, vertex.position[1].abs() * 2.0];
+    let pivot_offset = [(0.5 - pivot[0]) * quad_size[0], (pivot[1] - 0.5) * quad_size[1]];
+    let local = [(vertex.position[0] + pivot_offset[0]) * instance.scale[0], (vertex.position[1] + pivot_offset[1]) * instance.scale[1]];
+    let (s, c) = instance.rotation.sin_cos();
+    let rotated = [local[0] * c - local[1] * s, local[0] * s + local[1] * c];
+    let semi_size = [cam_state.cam_size[0] / 2.0, cam_state.cam_size[1] / 2.0];
+    ClipVertex{
+        position: [
+            rotated[0] / semi_size[0] + (instance.position[0] - cam_state.cam_pos[0]) / semi_size[0],
+            rotated[1] / semi_size[1] + (instance.position[1] - cam_state.cam_pos[1]) / semi_size[1],
+        ],
+        tex_coords,
+    }
+}
+
+//twice the signed area of the triangle abc
+fn edge(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32{
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+//what an srgb surface does when a color is written to it
+fn linear_to_srgb(value: f32) -> u8{
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.0031308{
+        value * 12.92
+    }else{
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests{
+    use image::Rgba;
+
+    use crate::rendering::{sprite_instance::SpriteTransform, sprites::{sprite_mapping::Sprite, vertex_configration::VertexConfigration}};
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    //texture 0 is red on the left and blue on the right, texture 1 is plain blue
+    fn renderer() -> SoftwareRenderer{
+        let mut split = RgbaImage::new(2, 1);
+        split.put_pixel(0, 0, RED);
+        split.put_pixel(1, 0, BLUE);
+        let sprites = vec![
+            LoadedSprite{ sprite: Sprite::Green, image: split, extra_frames: Vec::new(), pivot: [0.5, 0.5] },
+            LoadedSprite{ sprite: Sprite::Green, image: RgbaImage::from_pixel(1, 1, BLUE), extra_frames: Vec::new(), pivot: [0.5, 0.5] },
+        ];
+        SoftwareRenderer::new(sprites, (40, 40))
+    }
+
+    //a 2x2 square at the position, the camera shows 4x4 units around the origin, so every unit is 10 pixels
+    fn square(position: [f32; 2], texture_id: u32, depth: f32, transform: SpriteTransform) -> RenderChunk{
+        let transform = SpriteTransform{ scale: [2.0, 2.0], ..transform };
+        RenderChunk{
+            vertex_conf: VertexConfigration::UNIT_SQUARE.handle(),
+            instance_buffer: vec![SpriteInstance::new(position, texture_id, depth, 0, &transform)],
+        }
+    }
+
+    fn camera() -> CamState{
+        CamState{ cam_size: [4.0, 4.0], cam_pos: [0.0, 0.0] }
+    }
+
+    fn clear_color() -> Rgba<u8>{
+        Rgba([linear_to_srgb(CLEAR_COLOR[0]), linear_to_srgb(CLEAR_COLOR[1]), linear_to_srgb(CLEAR_COLOR[2]), 255])
+    }
+
+    #[test]
+    fn draws_a_chunk_where_the_camera_puts_it(){
+        let image = renderer().render(&[square([0.0, 0.0], 0, 0.5, SpriteTransform::default())], &camera());
+        assert_eq!(*image.get_pixel(15, 20), RED);
+        assert_eq!(*image.get_pixel(25, 20), BLUE);
+        assert_eq!(*image.get_pixel(5, 20), clear_color());
+        assert_eq!(*image.get_pixel(20, 35), clear_color());
+
+        //moving the camera right moves the sprite left on screen, y is up in the world and down in the image
+        let moved = CamState{ cam_size: [4.0, 4.0], cam_pos: [1.0, -1.0] };
+        let image = renderer().render(&[square([0.0, 0.0], 0, 0.5, SpriteTransform::default())], &moved);
+        assert_eq!(*image.get_pixel(5, 5), RED);
+        assert_eq!(*image.get_pixel(15, 5), BLUE);
+        assert_eq!(*image.get_pixel(5, 25), clear_color());
+    }
+
+    #[test]
+    fn flips_mirror_the_texture(){
+        let flipped = SpriteTransform{ flip_x: true, ..Default::default() };
+        let image = renderer().render(&[square([0.0, 0.0], 0, 0.5, flipped)], &camera());
+        assert_eq!(*image.get_pixel(15, 20), BLUE);
+        assert_eq!(*image.get_pixel(25, 20), RED);
+    }
+
+    #[test]
+    fn the_smaller_depth_is_in_front_whatever_the_chunk_order(){
+        //the blue square covers the red half of texture 0
+        let back = square([0.0, 0.0], 0, 0.8, SpriteTransform::default());
+        let front = square([-1.0, 0.0], 1, 0.2, SpriteTransform::default());
+        let in_order = renderer().render(&[back, front], &camera());
+        let back = square([0.0, 0.0], 0, 0.8, SpriteTransform::default());
+        let front = square([-1.0, 0.0], 1, 0.2, SpriteTransform::default());
+        let reversed = renderer().render(&[front, back], &camera());
+        for image in [in_order, reversed]{
+            assert_eq!(*image.get_pixel(5, 20), BLUE);
+            assert_eq!(*image.get_pixel(15, 20), BLUE);
+            assert_eq!(*image.get_pixel(25, 20), BLUE);
+        }
+    }
+
+    #[test]
+    fn instances_with_unknown_textures_are_skipped(){
+        let image = renderer().render(&[square([0.0, 0.0], 7, 0.5, SpriteTransform::default())], &camera());
+        assert!(image.pixels().all(|pixel| *pixel == clear_color()));
+    }
+}
@@ -3,12 +3,40 @@
 pub(crate) struct SpriteInstance{
     pub(crate) position: [f32;2],
     pub(crate) texture_id: u32,
+    pub(crate) depth: f32,      //see RenderLayer::depth
+    pub(crate) frame: u32,      //animation frame of the sprite, 0 for sprites without animations
+    pub(crate) scale: [f32;2],  //multiplies the size of the vertex configuration
+    pub(crate) rotation: f32,   //counter-clockwise in radians, around the position of the object
+    pub(crate) flip: u32,       //FLIP_X and FLIP_Y, mirrors the texture
+    pub(crate) tint: [f32;4],   //multiplied with the color of the texture, alpha included
 }
+
+pub(crate) const FLIP_X: u32 = 1;
+pub(crate) const FLIP_Y: u32 = 2;
+
+//the attributes are numbered after the ones of Vertex and have to match InstanceInput in shader.wgsl
+const ATTRIBUTES: [wgpu::VertexAttribute; 8] = wgpu::vertex_attr_array![
+    2 => Float32x2,
+    3 => Uint32,
+    4 => Float32,
+    5 => Uint32,
+    6 => Float32x2,
+    7 => Float32,
+    8 => Uint32,
+    9 => Float32x4,
+];
+
 impl SpriteInstance{
-    pub(crate) fn new(position: [f32;2], texture_id: u32) -> Self{
+    pub(crate) fn new(position: [f32;2], texture_id: u32, depth: f32, frame: u32, transform: &SpriteTransform) -> Self{
         Self{
             position,
             texture_id,
+            depth,
+            frame,
+            scale: transform.scale,
+            rotation: transform.rotation,
+            flip: transform.flip_bits(),
+            tint: transform.tint,
         }
     }
 
@@ -16,18 +44,35 @@ impl SpriteInstance{
         wgpu::VertexBufferLayout{
             array_stride: std::mem::size_of::<SpriteInstance>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute{
-                    offset: 0,
-                    shader_location: 2,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-                wgpu::VertexAttribute{
-                    offset: std::mem::size_of::<[f32;2]>() as wgpu::BufferAddress,
-                    shader_location: 3,
-                    format: wgpu::VertexFormat::Uint32,
-                }
-            ]
+            attributes: &ATTRIBUTES,
         }
     }
 }
+
+//how a DrawableObject wants its sprite to be transformed, the default leaves it as it is
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct SpriteTransform{
+    pub(crate) scale: [f32;2],
+    pub(crate) rotation: f32,
+    pub(crate) tint: [f32;4],
+    pub(crate) flip_x: bool,
+    pub(crate) flip_y: bool,
+}
+
+impl Default for SpriteTransform{
+    fn default() -> Self{
+        Self{
+            scale: [1.0, 1.0],
+            rotation: 0.0,
+            tint: [1.0, 1.0, 1.0, 1.0],
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+}
+
+impl SpriteTransform{
+    fn flip_bits(&self) -> u32{
+        (if self.flip_x { FLIP_X } else { 0 }) | (if self.flip_y { FLIP_Y } else { 0 })
+    }
+}
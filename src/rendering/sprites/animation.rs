@@ -0,0 +1,87 @@
+use super::sprite_mapping::Sprite;
+
+//a sequence of frames of a sprite sheet. The frame numbers refer to the "frames" of the sprite in the manifest, frame 0 is the sprite itself
+#[derive(Debug)]
+pub(crate) struct AnimationClip{
+    pub(crate) sprite: Sprite,
+    pub(crate) frames: &'static [(u32, f64)],     //(frame, how long it is shown in seconds)
+    pub(crate) looping: bool,                       //one-shot clips stop on their last frame
+}
+
+pub(crate) const WORKER_IDLE: AnimationClip = AnimationClip{
+    sprite: Sprite::WorkerBasic,
+    frames: &[(0, 1.0)],
+    looping: true,
+};
+
+pub(crate) const WORKER_WALKING: AnimationClip = AnimationClip{
+    sprite: Sprite::WorkerBasic,
+    frames: &[(1, 0.15), (0, 0.1), (2, 0.15), (0, 0.1)],
+    looping: true,
+};
+
+pub(crate) const WORKER_WORKING: AnimationClip = AnimationClip{
+    sprite: Sprite::WorkerBasic,
+    frames: &[(0, 0.35), (3, 0.25)],
+    looping: true,
+};
+
+//the playback state of a clip, objects keep one of these and advance it in DrawableObject::process_animation
+#[derive(Debug)]
+pub(crate) struct Animation{
+    clip: &'static AnimationClip,
+    index: usize,       //position in clip.frames
+    elapsed: f64,       //time the current frame has been shown
+    finished: bool,
+}
+
+impl Animation{
+    pub(crate) fn new(clip: &'static AnimationClip) -> Self{
+        Self{
+            clip,
+            index: 0,
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    //switches to another clip and starts it from the beginning, nothing happens if the clip is already playing
+    pub(crate) fn play(&mut self, clip: &'static AnimationClip){
+        if std::ptr::eq(self.clip, clip){
+            return;
+        }
+        *self = Self::new(clip);
+    }
+
+    pub(crate) fn advance(&mut self, delta_time: f64){
+        if self.finished{
+            return;
+        }
+        self.elapsed += delta_time;
+        //a long frame time can skip several frames at once
+        while self.elapsed >= self.frame_duration(){
+            self.elapsed -= self.frame_duration();
+            if self.index + 1 < self.clip.frames.len(){
+                self.index += 1;
+            }else if self.clip.looping{
+                self.index = 0;
+            }else{
+                self.finished = true;
+                return;
+            }
+        }
+    }
+
+    //frames without a duration would never let the loop above end
+    fn frame_duration(&self) -> f64{
+        self.clip.frames[self.index].1.max(0.001)
+    }
+
+    pub(crate) fn get_sprite(&self) -> &Sprite{
+        &self.clip.sprite
+    }
+
+    pub(crate) fn get_frame(&self) -> u32{
+        self.clip.frames[self.index].0
+    }
+}
@@ -0,0 +1,233 @@
+use image::{RgbaImage, GenericImage};
+
+//the biggest page the atlas builder creates, even if the adapter allows bigger textures. Most sprites are small, so a few pages of this size are plenty
+pub(crate) const MAX_ATLAS_PAGE_SIZE: u32 = 4096;
+//empty pixels between two packed images. The border pixels of every image are repeated into this space, so linear filtering never mixes in a neighbor
+pub(crate) const ATLAS_PADDING: u32 = 2;
+//the sprite shader holds the rects of all sprites in a uniform array of this length, has to match MAX_ATLAS_ENTRIES in shader.wgsl
+pub(crate) const MAX_ATLAS_ENTRIES: usize = 256;
+
+//where a sprite ended up in the atlas, this is what the sprite shader reads to sample the right part of the right page
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct AtlasEntry{
+    pub(crate) uv_rect: [f32; 4],   //x, y, width, height in texture coordinates (0.0 - 1.0)
+    pub(crate) page: u32,
+    pub(crate) frames_start: u32,   //index of the entry of frame 1 of a sprite sheet, the frames of a sheet follow each other
+    pub(crate) pivot: [f32; 2],     //see SpriteDefinition, the atlas builder always sets it to the center
+    pub(crate) frame_count: u32,    //1 for sprites without animation frames
+    _padding: [u32; 3],             //uniform arrays need a stride that is a multiple of 16 bytes
+}
+
+#[derive(Debug)]
+pub(crate) struct TextureAtlas{
+    pub(crate) page_size: u32,
+    pub(crate) pages: Vec<RgbaImage>,
+    pub(crate) entries: Vec<AtlasEntry>,     //in the order the images were given to the builder
+}
+
+//packs images into square pages with a simple shelf packer: the images are placed in rows from left to right, sorted by height, and a new row (or page) is started once one is full
+#[derive(Debug)]
+pub(crate) struct AtlasBuilder{
+    page_size: u32,
+    padding: u32,
+    images: Vec<RgbaImage>,
+}
+
+impl AtlasBuilder{
+    pub(crate) fn new(max_texture_size: u32) -> Self{
+        Self{
+            page_size: max_texture_size.min(MAX_ATLAS_PAGE_SIZE),
+            padding: ATLAS_PADDING,
+            images: Vec::new(),
+        }
+    }
+
+    //returns the index of the entry of this image in the finished atlas
+    pub(crate) fn add(&mut self, image: RgbaImage) -> usize{
+        self.images.push(image);
+        self.images.len() - 1
+    }
+
+    pub(crate) fn build(self) -> Result<TextureAtlas, String>{
+        if self.images.len() > MAX_ATLAS_ENTRIES{
+            return Err(format!("{} sprites were loaded, but the atlas supports at most {}", self.images.len(), MAX_ATLAS_ENTRIES));
+        }
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by(|a, b| self.images[*b].height().cmp(&self.images[*a].height()));
+
+        //first only the positions are computed, so the pages can be allocated with their final size
+        let mut placements = vec![(0u32, 0u32, 0u32); self.images.len()];      //(page, x, y) of the top left pixel of the image
+        let mut page = 0;
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        let mut last_page_height = 0;
+        for index in order.iter().copied(){
+            let (width, height) = self.images[index].dimensions();
+            let padded_width = width + 2 * self.padding;
+            let padded_height = height + 2 * self.padding;
+            if padded_width > self.page_size || padded_height > self.page_size{
+                return Err(format!("sprite {} is {}x{} pixels, which does not fit into an atlas page of {}x{}", index, width, height, self.page_size, self.page_size));
+            }
+            if x + padded_width > self.page_size{
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            if y + padded_height > self.page_size{
+                page += 1;
+                x = 0;
+                y = 0;
+                row_height = 0;
+            }
+            placements[index] = (page, x + self.padding, y + self.padding);
+            x += padded_width;
+            row_height = row_height.max(padded_height);
+            last_page_height = y + row_height;
+        }
+
+        //all pages have the same size since they end up as layers of one texture array. If everything fits on one page, the page is shrunk to what is actually used
+        let page_size = if page == 0{
+            let used_width = order.iter().map(|index| placements[*index].1 + self.images[*index].width() + self.padding).max().unwrap_or(1);
+            used_width.max(last_page_height).max(1).next_power_of_two().min(self.page_size)
+        }else{
+            self.page_size
+        };
+        let mut pages: Vec<RgbaImage> = (0..=page).map(|_| RgbaImage::new(page_size, page_size)).collect();
+        let mut entries = Vec::with_capacity(self.images.len());
+        for (index, image) in self.images.iter().enumerate(){
+            let (page, x, y) = placements[index];
+            let (width, height) = image.dimensions();
+            blit_with_extruded_border(&mut pages[page as usize], image, x, y, self.padding);
+            entries.push(AtlasEntry{
+                uv_rect: [x as f32 / page_size as f32, y as f32 / page_size as f32, width as f32 / page_size as f32, height as f32 / page_size as f32],
+                page,
+                frames_start: 0,
+                pivot: [0.5, 0.5],
+                frame_count: 1,
+                _padding: [0; 3],
+            });
+        }
+        Ok(TextureAtlas{
+            page_size,
+            pages,
+            entries,
+        })
+    }
+}
+
+//copies the image to (x, y) and repeats its outermost pixels into the padding around it
+fn blit_with_extruded_border(page: &mut RgbaImage, image: &RgbaImage, x: u32, y: u32, padding: u32){
+    page.copy_from(image, x, y).unwrap();
+    let (width, height) = image.dimensions();
+    for dy in 0..height + 2 * padding{
+        for dx in 0..width + 2 * padding{
+            let inside_x = dx >= padding && dx < width + padding;
+            let inside_y = dy >= padding && dy < height + padding;
+            if inside_x && inside_y{
+                continue;
+            }
+            let source_x = dx.saturating_sub(padding).min(width - 1);
+            let source_y = dy.saturating_sub(padding).min(height - 1);
+            page.put_pixel(x - padding + dx, y - padding + dy, *image.get_pixel(source_x, source_y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use image::Rgba;
+
+    use super::*;
+
+    fn filled(width: u32, height: u32, color: [u8; 4]) -> RgbaImage{
+        RgbaImage::from_pixel(width, height, Rgba(color))
+    }
+
+    //the pixel rect of an entry on its page
+    fn pixel_rect(atlas: &TextureAtlas, entry: &AtlasEntry) -> [u32; 4]{
+        let size = atlas.page_size as f32;
+        entry.uv_rect.map(|value| (value * size).round() as u32)
+    }
+
+    #[test]
+    fn packed_images_keep_their_pixels_and_dont_overlap(){
+        let mut builder = AtlasBuilder::new(64);
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 0, 255], [0, 255, 255, 255]];
+        let sizes = [(10, 20), (16, 16), (30, 5), (7, 7), (20, 12)];
+        for (size, color) in sizes.iter().zip(colors){
+            builder.add(filled(size.0, size.1, color));
+        }
+        let atlas = builder.build().unwrap();
+        assert_eq!(atlas.entries.len(), sizes.len());
+        assert_eq!(atlas.pages.len(), 1);
+
+        let rects: Vec<[u32; 4]> = atlas.entries.iter().map(|entry| pixel_rect(&atlas, entry)).collect();
+        for (index, rect) in rects.iter().enumerate(){
+            assert_eq!([rect[2], rect[3]], [sizes[index].0, sizes[index].1]);
+            assert!(rect[0] + rect[2] <= atlas.page_size && rect[1] + rect[3] <= atlas.page_size);
+            for y in rect[1]..rect[1] + rect[3]{
+                for x in rect[0]..rect[0] + rect[2]{
+                    assert_eq!(atlas.pages[0].get_pixel(x, y).0, colors[index]);
+                }
+            }
+            //the padding keeps the images apart, so not even the extruded borders touch
+            for other in &rects[index + 1..]{
+                let apart_x = rect[0] + rect[2] + 2 * ATLAS_PADDING <= other[0] || other[0] + other[2] + 2 * ATLAS_PADDING <= rect[0];
+                let apart_y = rect[1] + rect[3] + 2 * ATLAS_PADDING <= other[1] || other[1] + other[3] + 2 * ATLAS_PADDING <= rect[1];
+                assert!(apart_x || apart_y, "{:?} and {:?} overlap", rect, other);
+            }
+        }
+    }
+
+    #[test]
+    fn the_border_pixels_are_extruded_into_the_padding(){
+        let mut image = filled(4, 4, [0, 0, 0, 255]);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        image.put_pixel(3, 3, Rgba([0, 0, 255, 255]));
+        image.put_pixel(3, 1, Rgba([0, 255, 0, 255]));
+        let mut builder = AtlasBuilder::new(64);
+        builder.add(image);
+        let atlas = builder.build().unwrap();
+        let [x, y, width, height] = pixel_rect(&atlas, &atlas.entries[0]);
+        let page = &atlas.pages[0];
+        for distance in 1..=ATLAS_PADDING{
+            assert_eq!(page.get_pixel(x - distance, y - distance).0, [255, 0, 0, 255]);
+            assert_eq!(page.get_pixel(x + width - 1 + distance, y + height - 1 + distance).0, [0, 0, 255, 255]);
+            assert_eq!(page.get_pixel(x + width - 1 + distance, y + 1).0, [0, 255, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn a_single_page_is_shrunk_to_a_power_of_two(){
+        let mut builder = AtlasBuilder::new(4096);
+        builder.add(filled(20, 20, [255; 4]));
+        let atlas = builder.build().unwrap();
+        assert_eq!(atlas.page_size, 32);
+    }
+
+    #[test]
+    fn images_that_dont_fit_start_a_new_page(){
+        let mut builder = AtlasBuilder::new(32);
+        for _ in 0..3{
+            builder.add(filled(20, 20, [255; 4]));
+        }
+        let atlas = builder.build().unwrap();
+        assert_eq!(atlas.pages.len(), 3);
+        assert_eq!(atlas.page_size, 32);
+        let pages: Vec<u32> = atlas.entries.iter().map(|entry| entry.page).collect();
+        assert_eq!(pages, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn too_big_or_too_many_images_are_errors(){
+        let mut builder = AtlasBuilder::new(32);
+        builder.add(filled(30, 30, [255; 4]));
+        assert!(builder.build().is_err());
+
+        let mut builder = AtlasBuilder::new(4096);
+        for _ in 0..=MAX_ATLAS_ENTRIES{
+            builder.add(filled(1, 1, [255; 4]));
+        }
+        assert!(builder.build().is_err());
+    }
+}
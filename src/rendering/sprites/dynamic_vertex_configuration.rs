@@ -1,14 +1,71 @@
-use super::vertex_configration::VertexConfigrationTrait;
+use std::{sync::RwLock, collections::BTreeMap};
 
+use crate::rendering::vertex::Vertex;
 
+use super::vertex_configration::{VertexConfigrationTrait, VertexConfigration, VertexConfigHandle, NUM_BUILTIN_VERTEX_CONFIGURATIONS, centered_rectangle};
 
+//every shape gets its own buffers in the renderer, so scripts that spawn sprites of ever new sizes can't register more than this
+pub(crate) const MAX_DYNAMIC_VERTEX_CONFIGURATIONS: usize = 1024;
 
-struct DynamicVertexConfiguration{
-    data: [crate::rendering::vertex::Vertex; 4],
+//all quad shapes registered at runtime, the handle of an entry is its index + NUM_BUILTIN_VERTEX_CONFIGURATIONS. Entries are never removed, so handles stay valid across levels
+static DYNAMIC_VERTEX_CONFIGURATIONS: RwLock<Registry> = RwLock::new(Registry{ configurations: Vec::new(), indices: BTreeMap::new() });
+
+struct Registry{
+    configurations: Vec<DynamicVertexConfiguration>,
+    indices: BTreeMap<[u32; 16], usize>,     //the bits of the vertices, so registering a shape again doesn't have to go through all of them
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DynamicVertexConfiguration{
+    data: [Vertex; 4],
 }
 
 impl VertexConfigrationTrait for DynamicVertexConfiguration{
-    fn get_vertices(&self) -> [crate::rendering::vertex::Vertex; 4] {
+    fn get_vertices(&self) -> [Vertex; 4] {
         return self.data;
     }
-}
\ No newline at end of file
+}
+
+//levels call this while loading, the renderer creates the buffers of the shape the first time an object uses it. Registering the same shape twice returns the same handle
+//None once MAX_DYNAMIC_VERTEX_CONFIGURATIONS different shapes are registered
+pub(crate) fn register_vertex_configuration(vertices: [Vertex; 4]) -> Option<VertexConfigration>{
+    let key: [u32; 16] = bytemuck::cast(vertices);
+    let mut registry = DYNAMIC_VERTEX_CONFIGURATIONS.write().unwrap();
+    let index = match registry.indices.get(&key){
+        Some(index) => *index,
+        None => {
+            if registry.configurations.len() >= MAX_DYNAMIC_VERTEX_CONFIGURATIONS{
+                return None;
+            }
+            registry.configurations.push(DynamicVertexConfiguration{ data: vertices });
+            let index = registry.configurations.len() - 1;
+            registry.indices.insert(key, index);
+            index
+        }
+    };
+    Some(VertexConfigration::Dynamic(VertexConfigHandle((index + NUM_BUILTIN_VERTEX_CONFIGURATIONS) as u32)))
+}
+
+//a rectangle centered on the position of the object, which is what most sprites want
+pub(crate) fn register_rectangle(width: f32, height: f32) -> Option<VertexConfigration>{
+    register_vertex_configuration(centered_rectangle(width, height))
+}
+
+pub(crate) fn get_dynamic_vertex_configuration(handle: VertexConfigHandle) -> Option<DynamicVertexConfiguration>{
+    let index = (handle.0 as usize).checked_sub(NUM_BUILTIN_VERTEX_CONFIGURATIONS)?;
+    DYNAMIC_VERTEX_CONFIGURATIONS.read().unwrap().configurations.get(index).copied()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn registering_a_shape_again_returns_its_handle(){
+        let first = register_rectangle(2.5, 1.25).unwrap();
+        let other = register_rectangle(1.25, 2.5).unwrap();
+        assert_eq!(register_rectangle(2.5, 1.25), Some(first));
+        assert_ne!(first, other);
+        assert_eq!(first.get_vertices(), centered_rectangle(2.5, 1.25));
+    }
+}
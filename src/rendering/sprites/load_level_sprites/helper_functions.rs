@@ -1,74 +1,29 @@
-use image::{GenericImageView, ImageBuffer};
-use wgpu::{TextureUsages, TextureView};
+use image::{GenericImageView, ImageBuffer, RgbaImage, ImageResult, ImageError, error::{ParameterError, ParameterErrorKind}};
 
-use crate::rendering::wgpurenderer::Renderer;
-
-pub(super) fn load_sprite_from_memory_uncropped(diffuse_bytes: &[u8], renderer: &Renderer) -> TextureView{
-    load_sprite_from_memory(diffuse_bytes, 0, 0, 0, 0, renderer)
+pub(super) fn load_sprite_from_memory_uncropped(diffuse_bytes: &[u8]) -> ImageResult<RgbaImage>{
+    load_sprite_from_memory(diffuse_bytes, 0, 0, 0, 0)
 }
 
-pub(super) fn load_sprite_from_memory(diffuse_bytes: &[u8], crop_x: u32, crop_y: u32, crop_width: u32, crop_height: u32, renderer: &Renderer)  -> TextureView{
+//decodes and crops the image, the pixels end up in the sprite atlas which is uploaded to the gpu as a whole
+pub(super) fn load_sprite_from_memory(diffuse_bytes: &[u8], crop_x: u32, crop_y: u32, crop_width: u32, crop_height: u32)  -> ImageResult<RgbaImage>{
     //loading an image from a file
-    let diffuse_image = image::load_from_memory(diffuse_bytes).unwrap();
+    let diffuse_image = image::load_from_memory(diffuse_bytes)?;
     let cropped_image;
 
     if crop_width == 0 || crop_height == 0  {
         cropped_image = diffuse_image;
     } else {
+        //crop_imm silently clamps the rectangle, but a crop outside of the image is always a mistake in the manifest
+        let (width, height) = diffuse_image.dimensions();
+        let inside = crop_x.checked_add(crop_width).is_some_and(|right| right <= width) && crop_y.checked_add(crop_height).is_some_and(|bottom| bottom <= height);
+        if !inside{
+            return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+        }
         // Crop the image to remove dead spaces
         cropped_image = diffuse_image.crop_imm(crop_x,crop_y, crop_width, crop_height);   //this crops the image, in this case we just shave off the empty space to the sides etc. This is most likely specific (if needed at all) for every texture
     }
 
-    
-    let dimensions = cropped_image.dimensions();
-    
-    let diffuse_rgba: ImageBuffer<image::Rgba<u8>, Vec<u8>> = cropped_image.to_rgba8();
-    let diffuse_rgba: Vec<u8> = to_srgba(diffuse_rgba);    //this is necessary for images that are in rgba format, if the image is in srgba format, this should not be done, otherwise the colors will get distorted
-    
-    let texture_size = wgpu::Extent3d {
-        width: dimensions.0,
-        height: dimensions.1,
-        depth_or_array_layers: 1,
-    };
-
-    let diffuse_texture = renderer.device.create_texture(&wgpu::TextureDescriptor {
-
-        label: Some("distinct texture"),
-        size: texture_size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8Unorm,
-        usage: TextureUsages::COPY_DST | TextureUsages::COPY_SRC | TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],  //Default, RGBA8Unorm is always supported
-    
-        
-    });
-
-
-    //this execute a write on the gpu from the loaded image pixel data into our created texture
-    renderer.queue.write_texture(
-        // Tells wgpu where to copy the pixel data
-        wgpu::ImageCopyTexture {
-            texture: &diffuse_texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-            aspect: wgpu::TextureAspect::All,
-        },
-        // The actual pixel data
-        diffuse_rgba.as_slice(),
-        // The layout of the texture
-        wgpu::ImageDataLayout {
-            offset: 0,
-            bytes_per_row: Some(4 * dimensions.0),
-            rows_per_image: Some(dimensions.1),
-        },
-        texture_size,
-    );
-    // We don't need to configure the texture view much, so let's
-    // let wgpu define it.
-    let texture_view = diffuse_texture.create_view(&wgpu::TextureViewDescriptor::default());        //create a handle to access the texture we just created
-    texture_view
+    Ok(cropped_image.to_rgba8())
 
 
         /*      This is another way to load an image, but it is not as easy to use as the one above (not as flexible), tho more slim
@@ -111,7 +66,7 @@ pub(super) fn load_sprite_from_memory(diffuse_bytes: &[u8], crop_x: u32, crop_y:
 
 
 //convert the given image buffer from rgba into srgba
-pub(super) fn to_srgba(image: ImageBuffer<image::Rgba<u8>, Vec<u8>>) -> Vec<u8> {
+pub(crate) fn to_srgba(image: ImageBuffer<image::Rgba<u8>, Vec<u8>>) -> Vec<u8> {
     image.chunks_exact(4)
     .flat_map(|rgba| {
         let r = rgba[0] as f32 / 255.0;
@@ -0,0 +1,212 @@
+use std::{path::{Path, PathBuf}, fmt::Display, collections::HashSet};
+
+use image::{RgbaImage, GenericImageView};
+use serde::Deserialize;
+
+use crate::rendering::sprites::sprite_mapping::{Sprite, ALL_SPRITES};
+
+use super::helper_functions::{load_sprite_from_memory, load_sprite_from_memory_uncropped};
+
+//the manifests are looked up relative to the working directory first and then relative to the crate, so the game also starts from inside target/
+const MANIFEST_DIRECTORY: &str = "textures/manifests";
+
+//what a level manifest file looks like, see textures/manifests/level_0.json
+#[derive(Debug, Deserialize)]
+pub(crate) struct SpriteManifest{
+    pub(crate) sprites: Vec<SpriteDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SpriteDefinition{
+    pub(crate) name: String,
+    pub(crate) path: String,            //relative to the manifest file
+    #[serde(default)]
+    pub(crate) crop: Option<CropRect>,  //only this part of the image is used, to shave off empty space around it
+    #[serde(default = "default_pivot")]
+    pub(crate) pivot: [f32; 2],         //the point of the image (0.0 - 1.0, from the top left) that is placed at the position of the object
+    #[serde(default)]
+    pub(crate) frames: Vec<CropRect>,   //for sprite sheets: the animation frames within the (cropped) image. If there are any, the first one is what is shown without an animation
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub(crate) struct CropRect{
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+fn default_pivot() -> [f32; 2]{
+    [0.5, 0.5]
+}
+
+//a sprite of the manifest with its image already loaded and cropped
+#[derive(Debug)]
+pub(crate) struct LoadedSprite{
+    pub(crate) sprite: Sprite,
+    pub(crate) image: RgbaImage,
+    pub(crate) extra_frames: Vec<RgbaImage>,     //all frames after the first one, which is in image
+    pub(crate) pivot: [f32; 2],
+}
+
+#[derive(Debug)]
+pub(crate) enum ManifestError{
+    Io{ path: PathBuf, error: std::io::Error },
+    Parse{ path: PathBuf, error: serde_json::Error },
+    UnknownSprite(String),
+    DuplicateSprite(String),
+    MissingSprites(Vec<&'static str>),
+    InvalidPivot(String),
+    InvalidFrame{ name: String, frame: usize },
+    Image{ name: String, error: image::ImageError },
+}
+
+impl Display for ManifestError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            ManifestError::Io{ path, error } => write!(f, "could not read {}: {}", path.display(), error),
+            ManifestError::Parse{ path, error } => write!(f, "{} is not a valid sprite manifest: {}", path.display(), error),
+            ManifestError::UnknownSprite(name) => write!(f, "the manifest lists the sprite \"{}\", but there is no such Sprite", name),
+            ManifestError::DuplicateSprite(name) => write!(f, "the sprite \"{}\" is listed more than once", name),
+            ManifestError::MissingSprites(names) => write!(f, "the manifest is missing the sprites {}", names.join(", ")),
+            ManifestError::InvalidPivot(name) => write!(f, "the pivot of \"{}\" has to be between 0.0 and 1.0", name),
+            ManifestError::InvalidFrame{ name, frame } => write!(f, "frame {} of \"{}\" is empty or outside of the image", frame, name),
+            ManifestError::Image{ name, error } => write!(f, "could not load the image of \"{}\": {}", name, error),
+        }
+    }
+}
+
+pub(crate) fn manifest_path_for_level(level: u32) -> PathBuf{
+    let file_name = format!("level_{}.json", level);
+    let relative = Path::new(MANIFEST_DIRECTORY).join(&file_name);
+    if relative.exists(){
+        return relative;
+    }
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(MANIFEST_DIRECTORY).join(file_name)
+}
+
+pub(crate) fn read_manifest(path: &Path) -> Result<SpriteManifest, ManifestError>{
+    let content = std::fs::read_to_string(path).map_err(|error| ManifestError::Io{ path: path.to_path_buf(), error })?;
+    serde_json::from_str(&content).map_err(|error| ManifestError::Parse{ path: path.to_path_buf(), error })
+}
+
+//every Sprite has to be listed exactly once, otherwise objects would be drawn with the wrong image or none at all
+pub(crate) fn validate_manifest(manifest: &SpriteManifest) -> Result<(), ManifestError>{
+    let mut seen = HashSet::new();
+    for definition in manifest.sprites.iter(){
+        let Some(sprite) = Sprite::from_name(&definition.name) else{
+            return Err(ManifestError::UnknownSprite(definition.name.clone()));
+        };
+        if !seen.insert(sprite){
+            return Err(ManifestError::DuplicateSprite(definition.name.clone()));
+        }
+        if definition.pivot.iter().any(|value| !(0.0..=1.0).contains(value)){
+            return Err(ManifestError::InvalidPivot(definition.name.clone()));
+        }
+    }
+    let missing: Vec<&'static str> = ALL_SPRITES.iter().filter(|sprite| !seen.contains(*sprite)).map(|sprite| sprite.name()).collect();
+    if !missing.is_empty(){
+        return Err(ManifestError::MissingSprites(missing));
+    }
+    Ok(())
+}
+
+//reads and validates the manifest of the level and loads all images listed in it, the result is sorted in the order of ALL_SPRITES
+pub(crate) fn load_level_sprites(level: u32) -> Result<Vec<LoadedSprite>, ManifestError>{
+    let manifest_path = manifest_path_for_level(level);
+    println!("Loading sprites of level {} from {}", level, manifest_path.display());
+    let manifest = read_manifest(&manifest_path)?;
+    validate_manifest(&manifest)?;
+
+    let directory = manifest_path.parent().unwrap_or(Path::new("."));
+    let mut sprites = Vec::with_capacity(manifest.sprites.len());
+    for definition in manifest.sprites.into_iter(){
+        let image_path = directory.join(&definition.path);
+        let bytes = std::fs::read(&image_path).map_err(|error| ManifestError::Io{ path: image_path.clone(), error })?;
+        let image = match definition.crop{
+            Some(crop) => load_sprite_from_memory(&bytes, crop.x, crop.y, crop.width, crop.height),
+            None => load_sprite_from_memory_uncropped(&bytes),
+        }.map_err(|error| ManifestError::Image{ name: definition.name.clone(), error })?;
+        let mut frames = Vec::with_capacity(definition.frames.len());
+        for (index, rect) in definition.frames.iter().enumerate(){
+            let inside = rect.x.checked_add(rect.width).is_some_and(|right| right <= image.width()) && rect.y.checked_add(rect.height).is_some_and(|bottom| bottom <= image.height());
+            if rect.width == 0 || rect.height == 0 || !inside{
+                return Err(ManifestError::InvalidFrame{ name: definition.name.clone(), frame: index });
+            }
+            frames.push(image.view(rect.x, rect.y, rect.width, rect.height).to_image());
+        }
+        let (image, extra_frames) = if frames.is_empty(){
+            (image, Vec::new())
+        }else{
+            let extra_frames = frames.split_off(1);
+            (frames.pop().unwrap(), extra_frames)
+        };
+        sprites.push(LoadedSprite{
+            sprite: Sprite::from_name(&definition.name).unwrap(),  //checked by validate_manifest
+            image,
+            extra_frames,
+            pivot: definition.pivot,
+        });
+    }
+    sprites.sort_by_key(|loaded| loaded.sprite as usize);
+    Ok(sprites)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    //a manifest with every sprite, extra_json is appended to the list
+    fn manifest(extra_json: &str, skip: Option<Sprite>) -> SpriteManifest{
+        let definitions: Vec<String> = ALL_SPRITES.iter().filter(|sprite| Some(**sprite) != skip).map(|sprite| format!("{{ \"name\": \"{}\", \"path\": \"image.png\" }}", sprite.name())).collect();
+        let mut sprites = definitions.join(", ");
+        if !extra_json.is_empty(){
+            sprites += &format!(", {}", extra_json);
+        }
+        serde_json::from_str(&format!("{{ \"sprites\": [{}] }}", sprites)).unwrap()
+    }
+
+    #[test]
+    fn a_complete_manifest_is_valid(){
+        assert!(validate_manifest(&manifest("", None)).is_ok());
+    }
+
+    #[test]
+    fn unknown_and_duplicate_sprites_are_rejected(){
+        assert!(matches!(validate_manifest(&manifest(r#"{ "name": "dragon", "path": "dragon.png" }"#, None)), Err(ManifestError::UnknownSprite(name)) if name == "dragon"));
+        let duplicate = format!(r#"{{ "name": "{}", "path": "other.png" }}"#, ALL_SPRITES[0].name());
+        assert!(matches!(validate_manifest(&manifest(&duplicate, None)), Err(ManifestError::DuplicateSprite(name)) if name == ALL_SPRITES[0].name()));
+    }
+
+    #[test]
+    fn missing_sprites_are_listed(){
+        let missing = ALL_SPRITES[1];
+        match validate_manifest(&manifest("", Some(missing))){
+            Err(ManifestError::MissingSprites(names)) => assert_eq!(names, vec![missing.name()]),
+            other => panic!("expected the missing sprite, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pivots_outside_of_the_image_are_rejected(){
+        let skipped = ALL_SPRITES[0];
+        let definition = format!(r#"{{ "name": "{}", "path": "image.png", "pivot": [0.5, 1.5] }}"#, skipped.name());
+        assert!(matches!(validate_manifest(&manifest(&definition, Some(skipped))), Err(ManifestError::InvalidPivot(name)) if name == skipped.name()));
+    }
+
+    #[test]
+    fn crops_outside_of_the_image_are_errors_instead_of_overflows(){
+        let mut bytes = Vec::new();
+        RgbaImage::new(8, 8).write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png).unwrap();
+        assert_eq!(load_sprite_from_memory(&bytes, 2, 2, 4, 4).unwrap().dimensions(), (4, 4));
+        assert!(load_sprite_from_memory(&bytes, 6, 0, 4, 4).is_err());
+        assert!(load_sprite_from_memory(&bytes, u32::MAX, 0, 2, 2).is_err());
+        assert!(load_sprite_from_memory(&bytes, 0, 1, 2, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn the_level_manifest_is_valid(){
+        let manifest = read_manifest(&manifest_path_for_level(0)).unwrap();
+        validate_manifest(&manifest).unwrap();
+    }
+}
@@ -1,2 +1,2 @@
-pub(crate) mod initial_level;
+pub(crate) mod manifest;
 pub(crate) mod helper_functions;
\ No newline at end of file
@@ -2,15 +2,59 @@ use std::{num::{NonZeroU32, NonZeroU64}, default};
 
 use wgpu::{TextureUsages, Device, RenderPipeline, BindGroup, ShaderModule, SurfaceConfiguration, TextureView, util::DeviceExt};
 
-use crate::{rendering::{vertex::Vertex, wgpurenderer::Renderer, sprite_instance::SpriteInstance}, controller::controller::{CAM_INITIAL_WIDTH, CAM_INITIAL_HEIGHT}};
+use crate::{rendering::{vertex::Vertex, wgpurenderer::Renderer, sprite_instance::SpriteInstance, depth::DEPTH_FORMAT, sprites::load_level_sprites::helper_functions::to_srgba}};
 use image::{GenericImageView, ImageBuffer};
-use super::load_level_sprites::initial_level::load_initial_level_sprites;
+use super::{load_level_sprites::manifest::load_level_sprites, atlas::{AtlasBuilder, AtlasEntry, TextureAtlas, MAX_ATLAS_ENTRIES}};
 
 
-pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup, BindGroup) {
+//everything the sprites are drawn with, it is replaced as a whole when another sprite set is loaded or the shader is reloaded
+pub(crate) struct SpritePipeline{
+    pub(crate) render_pipeline: RenderPipeline,
+    pub(crate) bind_group: BindGroup,      //the texture atlas
+    pub(crate) camera_bind_group: BindGroup,
+}
+
+//loads the sprites listed in the manifest of the given level, see textures/manifests
+pub fn load_sprites(level: u32, renderer: &Renderer) -> SpritePipeline {
+    try_load_sprites(level, renderer).unwrap_or_else(|error| panic!("{}", error))
+}
+
+//same as load_sprites, but a broken manifest or image is returned as an error, so hot reloading can keep the old sprites
+pub(crate) fn try_load_sprites(level: u32, renderer: &Renderer) -> Result<SpritePipeline, String> {
         
 
-        let texture_view_array = load_initial_level_sprites(renderer);
+        //all sprites are packed into one texture array, so the shader only needs baseline features to pick the right one
+        let level_sprites = load_level_sprites(level).map_err(|error| format!("Could not load the sprites of level {}: {}", level, error))?;
+        let mut atlas_builder = AtlasBuilder::new(renderer.device.limits().max_texture_dimension_2d);
+        let mut pivots = Vec::with_capacity(level_sprites.len());
+        let mut extra_frames = Vec::with_capacity(level_sprites.len());
+        for loaded in level_sprites.into_iter(){
+            atlas_builder.add(loaded.image);     //the sprites are sorted, so the index in the atlas is the same as the index of the Sprite
+            pivots.push(loaded.pivot);
+            extra_frames.push(loaded.extra_frames);
+        }
+        //the additional frames of sprite sheets go after all sprites
+        let mut frame_ranges = Vec::with_capacity(extra_frames.len());
+        for frames in extra_frames.into_iter(){
+            let frame_count = frames.len() as u32 + 1;
+            let frames_start = frames.into_iter().map(|frame| atlas_builder.add(frame)).min().unwrap_or(0) as u32;
+            frame_ranges.push((frames_start, frame_count));
+        }
+        let mut atlas = atlas_builder.build().map_err(|error| format!("Could not pack the sprites of level {}: {}", level, error))?;
+        for ((entry, pivot), (frames_start, frame_count)) in atlas.entries.iter_mut().zip(pivots).zip(frame_ranges){
+            entry.pivot = pivot;
+            entry.frames_start = frames_start;
+            entry.frame_count = frame_count;
+        }
+        let atlas_view = create_atlas_texture(&atlas, renderer);
+
+        let mut atlas_entries = [AtlasEntry::default(); MAX_ATLAS_ENTRIES];
+        atlas_entries[..atlas.entries.len()].copy_from_slice(&atlas.entries);
+        let atlas_entry_buffer = renderer.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Atlas Entry Buffer"),
+            contents: bytemuck::cast_slice(&atlas_entries),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
         
         let diffuse_sampler = renderer.device.create_sampler(&wgpu::SamplerDescriptor { //a sampler will accept coordinates (X/Y) and return the color data. So this object is asked when the texture is the source of any color operation
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -24,16 +68,16 @@ pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup,
         
         //bind groups describe resources that a shaders has access to
         let texture_bind_group_layout = renderer.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[                     //2 Entries: 1st: Texture, 2nd: Sampler for texture
+            entries: &[                     //3 Entries: 1st: Atlas pages, 2nd: Sampler for the pages, 3rd: where every sprite is in the atlas
                 wgpu::BindGroupLayoutEntry {    
                     binding: 0,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
                         multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
                         sample_type: wgpu::TextureSampleType::Float { filterable: true },
                     },
-                    count: NonZeroU32::new(texture_view_array.len() as u32),
+                    count: None,
                 },
                 wgpu::BindGroupLayoutEntry {
                     binding: 1,
@@ -43,13 +87,20 @@ pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup,
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
             label: Some("texture_bind_group_layout"),
         });
 
-
-        let texture_view_array = texture_view_array.iter().collect::<Vec<&TextureView>>();
-        
         //create the actual bind group based on the bind-group-layout. This looks almost identical tho, but it means you could switch these out at runtime, go for another bind group and thus change the textures
         let diffuse_bind_group = renderer.device.create_bind_group(
             &wgpu::BindGroupDescriptor {
@@ -57,11 +108,15 @@ pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureViewArray(&texture_view_array),
+                        resource: wgpu::BindingResource::TextureView(&atlas_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
                         resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: atlas_entry_buffer.as_entire_binding(),
                     }
                 ],
                 label: Some("diffuse_bind_group"),
@@ -140,7 +195,15 @@ pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup,
                 unclipped_depth: false,
                 // Requires Features::CONSERVATIVE_RASTERIZATION
                 conservative: false,
-            },    depth_stencil: None, // 1.
+            },
+            //sprites are depth tested, so the layer of an object decides what is in front and not the order in which the objects were queued
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
             multisample: wgpu::MultisampleState {
                 count: 1, // 2.
                 mask: !0, // 3.
@@ -152,11 +215,62 @@ pub fn load_sprites(_i: u32, renderer: &Renderer) -> (RenderPipeline, BindGroup,
 
 
 
-        (render_pipeline, diffuse_bind_group, camera_bind_group)
+        Ok(SpritePipeline{ render_pipeline, bind_group: diffuse_bind_group, camera_bind_group })
 }
 
 
 
+//uploads every page of the atlas as one layer of a texture array
+fn create_atlas_texture(atlas: &TextureAtlas, renderer: &Renderer) -> TextureView{
+    let texture_size = wgpu::Extent3d {
+        width: atlas.page_size,
+        height: atlas.page_size,
+        depth_or_array_layers: gl_safe_layer_count(atlas.pages.len() as u32),
+    };
+    let atlas_texture = renderer.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("sprite atlas texture"),
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::COPY_DST | TextureUsages::COPY_SRC | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    for (layer, page) in atlas.pages.iter().enumerate(){
+        let page_rgba = to_srgba(page.clone());    //the images are loaded as rgba, the shader expects srgba
+        renderer.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &atlas_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: layer as u32 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            page_rgba.as_slice(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * atlas.page_size),
+                rows_per_image: Some(atlas.page_size),
+            },
+            wgpu::Extent3d { width: atlas.page_size, height: atlas.page_size, depth_or_array_layers: 1 },
+        );
+    }
+    //the view has to be an array, even if there is only a single page
+    atlas_texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    })
+}
+
+//the GL backend decides by the number of layers what kind of texture it creates: 1 layer becomes a plain 2D texture and a multiple of 6 square layers a cube map, both of which the shader can't sample as an array. The extra layers stay empty
+fn gl_safe_layer_count(pages: u32) -> u32{
+    match pages{
+        0 | 1 => 2,
+        pages if pages % 6 == 0 => pages + 1,
+        pages => pages,
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct Camera{
@@ -2,4 +2,6 @@ pub(crate) mod load_sprites;
 pub(crate) mod sprite_mapping;
 pub(crate) mod load_level_sprites;
 pub(crate) mod vertex_configration;
-pub(crate) mod dynamic_vertex_configuration;
\ No newline at end of file
+pub(crate) mod dynamic_vertex_configuration;
+pub(crate) mod atlas;
+pub(crate) mod animation;
\ No newline at end of file
@@ -1,8 +1,8 @@
 #![allow(dead_code)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 
 /**
- * These need to be in the same order as loaded in load_level_sprites
+ * The handles of all sprites. The images behind them are listed by name in the sprite manifest of a level (textures/manifests), the order there doesn't matter
  */
 pub(crate) enum Sprite {
     DwarfBaseHouse,
@@ -12,4 +12,38 @@ pub(crate) enum Sprite {
     Green,
     WorkerBasic,
     BaseBaseLarge,
-} 
\ No newline at end of file
+    DarkerGreen,
+}
+
+pub(crate) const NUM_SPRITES: usize = 8;
+
+pub(crate) const ALL_SPRITES: [Sprite; NUM_SPRITES] = [
+    Sprite::DwarfBaseHouse,
+    Sprite::DwarfBaseHousePixelated,
+    Sprite::Black,
+    Sprite::DarkBlue,
+    Sprite::Green,
+    Sprite::WorkerBasic,
+    Sprite::BaseBaseLarge,
+    Sprite::DarkerGreen,
+];
+
+impl Sprite{
+    //the name under which the sprite is listed in the manifest
+    pub(crate) fn name(&self) -> &'static str{
+        match self{
+            Sprite::DwarfBaseHouse => "dwarf_base_house",
+            Sprite::DwarfBaseHousePixelated => "dwarf_base_house_pixelated",
+            Sprite::Black => "black",
+            Sprite::DarkBlue => "dark_blue",
+            Sprite::Green => "green",
+            Sprite::WorkerBasic => "worker_basic",
+            Sprite::BaseBaseLarge => "base_base_large",
+            Sprite::DarkerGreen => "darker_green",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Sprite>{
+        ALL_SPRITES.iter().copied().find(|sprite| sprite.name() == name)
+    }
+}
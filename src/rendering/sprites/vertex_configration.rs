@@ -2,28 +2,64 @@
 
 use crate::rendering::vertex::Vertex;
 
+use super::dynamic_vertex_configuration::get_dynamic_vertex_configuration;
 
 
-pub(crate) const NUM_VERTEX_CONFIGURATIONS: usize = 6;   //UPDATE THIS IF YOU ADD MORE VERTEX CONFIGURATIONS!
 
-#[derive(Debug, Clone, Copy)]
+pub(crate) const NUM_BUILTIN_VERTEX_CONFIGURATIONS: usize = 7;   //handles of dynamic vertex configurations start after the built-in ones
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum VertexConfigration {
     SQUARE_SMALL_1,
     NEARLY_SQUARE_RECTANGLE_0,
-        //update the NUM_VERTEX_CONFIGURATIONS constant if you add more vertex configurations! These must be in the same order as the struct in init.rs
     LINE_HORIZONTAL,
     LINE_VERTICAL,
     SMALL_ENTITY_WORKER,
     BIG_BASE_BASE_4X2,
+    UNIT_SQUARE,        //1x1, meant to be sized with the scale of SpriteTransform
+    Dynamic(VertexConfigHandle),    //registered at runtime, see dynamic_vertex_configuration.rs
 
 } 
 
+//what the renderer knows a vertex configuration by, it creates the buffers of a handle the first time it sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct VertexConfigHandle(pub(crate) u32);
+
+impl VertexConfigration{
+    pub(crate) fn handle(&self) -> VertexConfigHandle{
+        match self{
+            VertexConfigration::SQUARE_SMALL_1 => VertexConfigHandle(0),
+            VertexConfigration::NEARLY_SQUARE_RECTANGLE_0 => VertexConfigHandle(1),
+            VertexConfigration::LINE_HORIZONTAL => VertexConfigHandle(2),
+            VertexConfigration::LINE_VERTICAL => VertexConfigHandle(3),
+            VertexConfigration::SMALL_ENTITY_WORKER => VertexConfigHandle(4),
+            VertexConfigration::BIG_BASE_BASE_4X2 => VertexConfigHandle(5),
+            VertexConfigration::UNIT_SQUARE => VertexConfigHandle(6),
+            VertexConfigration::Dynamic(handle) => *handle,
+        }
+    }
+}
+
+impl VertexConfigHandle{
+    pub(crate) fn to_configuration(self) -> VertexConfigration{
+        match self.0{
+            0 => VertexConfigration::SQUARE_SMALL_1,
+            1 => VertexConfigration::NEARLY_SQUARE_RECTANGLE_0,
+            2 => VertexConfigration::LINE_HORIZONTAL,
+            3 => VertexConfigration::LINE_VERTICAL,
+            4 => VertexConfigration::SMALL_ENTITY_WORKER,
+            5 => VertexConfigration::BIG_BASE_BASE_4X2,
+            6 => VertexConfigration::UNIT_SQUARE,
+            _ => VertexConfigration::Dynamic(self),
+        }
+    }
+}
+
 pub trait VertexConfigrationTrait {
 
     fn get_vertices(&self) -> [Vertex; 4];
 }
 
-//dynamically sized objects need to implement their own .get_vertices() method
 impl VertexConfigrationTrait for VertexConfigration {
     #[inline(always)]
     fn get_vertices(&self) -> [Vertex; 4] {
@@ -34,7 +70,9 @@ impl VertexConfigrationTrait for VertexConfigration {
             VertexConfigration::NEARLY_SQUARE_RECTANGLE_0 => SQUARE_0_0_VERTEX_CONF(),
             VertexConfigration::SMALL_ENTITY_WORKER => SMALL_ENTITY_WORKER(),
             VertexConfigration::BIG_BASE_BASE_4X2 => BIG_BASE_BASE_5X3() ,
-            _ => todo!(),
+            VertexConfigration::UNIT_SQUARE => centered_rectangle(1.0, 1.0),
+            //handles are only handed out by register_vertex_configuration and never removed
+            VertexConfigration::Dynamic(handle) => get_dynamic_vertex_configuration(*handle).expect("unknown dynamic vertex configuration").get_vertices(),
         }
     }
 }
@@ -76,7 +114,7 @@ fn SMALL_LINE_VERTICAL_VERTEX_CONF()->  [Vertex; 4]{
 }
 
 #[inline(always)]
-fn centered_rectangle(width:  f32, height: f32) -> [Vertex; 4] {
+pub(crate) fn centered_rectangle(width:  f32, height: f32) -> [Vertex; 4] {
     [
         Vertex { position: [width / 2.0, -height / 2.0], tex_coords: [1.0, 1.0]}, // A
         Vertex { position: [width / 2.0, height / 2.0], tex_coords: [1.0, 0.0]}, // B
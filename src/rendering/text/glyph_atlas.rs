@@ -0,0 +1,102 @@
+use std::sync::OnceLock;
+
+use fontdue::{Font, FontSettings};
+
+//the font is compiled into the binary, so text works no matter from where the game is started. DejaVu Sans Mono, see fonts/LICENSE-DejaVu.txt
+const FONT_DATA: &[u8] = include_bytes!("../../../fonts/DejaVuSansMono.ttf");
+//the glyphs are rasterized once at this size in pixels, text that is drawn much larger gets blurry
+const RASTER_SIZE: f32 = 48.0;
+const ATLAS_WIDTH: u32 = 512;
+//empty pixels around every glyph, so the linear filtering doesn't pull in the neighbours
+const GLYPH_PADDING: u32 = 2;
+//printable ascii, everything else is drawn as FALLBACK_CHARACTER
+const FIRST_CHARACTER: char = ' ';
+const LAST_CHARACTER: char = '~';
+const FALLBACK_CHARACTER: char = '?';
+//a fully covered block in the top left corner, so the ui can draw solid rectangles with the same texture as its text
+const SOLID_BLOCK_SIZE: u32 = 4;
+
+static GLYPH_ATLAS: OnceLock<GlyphAtlas> = OnceLock::new();
+
+//where a glyph is in the atlas and how it is placed. Sizes are in em, so they only have to be multiplied with the font size of a text
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GlyphInfo{
+    pub(crate) uv_rect: [f32; 4],   //x, y, width and height in the atlas, from 0.0 to 1.0
+    pub(crate) size: [f32; 2],
+    pub(crate) offset: [f32; 2],    //from the pen position at the top of the line to the top left corner of the glyph, y pointing down
+    pub(crate) advance: f32,
+}
+
+//all glyphs rasterized into one coverage texture. It is built once and shared by the CamOrganizer, which lays out the text, and the renderer, which uploads the pixels
+#[derive(Debug)]
+pub(crate) struct GlyphAtlas{
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) pixels: Vec<u8>,     //one byte of coverage per pixel, row 0 is the top
+    pub(crate) line_height: f32,    //in em
+    pub(crate) solid_uv_rect: [f32; 4],     //the inner part of the solid block, the filtering doesn't reach its edges from there
+    glyphs: Vec<GlyphInfo>,         //indexed by the character minus FIRST_CHARACTER
+}
+
+pub(crate) fn glyph_atlas() -> &'static GlyphAtlas{
+    GLYPH_ATLAS.get_or_init(GlyphAtlas::rasterize)
+}
+
+impl GlyphAtlas{
+    fn rasterize() -> Self{
+        let font = Font::from_bytes(FONT_DATA, FontSettings::default()).expect("the bundled font is valid");
+        let line_metrics = font.horizontal_line_metrics(RASTER_SIZE).expect("the bundled font has horizontal metrics");
+        let rasterized: Vec<_> = (FIRST_CHARACTER..=LAST_CHARACTER).map(|character| font.rasterize(character, RASTER_SIZE)).collect();
+
+        //shelf packing: the glyphs are put next to each other and a new row is started when one doesn't fit anymore
+        let mut placements = Vec::with_capacity(rasterized.len());
+        let (mut x, mut y, mut row_height) = (GLYPH_PADDING * 2 + SOLID_BLOCK_SIZE, GLYPH_PADDING, SOLID_BLOCK_SIZE);
+        for (metrics, _) in rasterized.iter(){
+            let (width, height) = (metrics.width as u32, metrics.height as u32);
+            if x + width + GLYPH_PADDING > ATLAS_WIDTH{
+                x = GLYPH_PADDING;
+                y += row_height + GLYPH_PADDING;
+                row_height = 0;
+            }
+            placements.push((x, y));
+            x += width + GLYPH_PADDING;
+            row_height = row_height.max(height);
+        }
+        let height = (y + row_height + GLYPH_PADDING).next_power_of_two();
+
+        let mut pixels = vec![0u8; (ATLAS_WIDTH * height) as usize];
+        for row in GLYPH_PADDING..GLYPH_PADDING + SOLID_BLOCK_SIZE{
+            let start = (row * ATLAS_WIDTH + GLYPH_PADDING) as usize;
+            pixels[start..start + SOLID_BLOCK_SIZE as usize].fill(u8::MAX);
+        }
+        let solid_center = [(GLYPH_PADDING as f32 + SOLID_BLOCK_SIZE as f32 / 2.0) / ATLAS_WIDTH as f32, (GLYPH_PADDING as f32 + SOLID_BLOCK_SIZE as f32 / 2.0) / height as f32];
+        let mut glyphs = Vec::with_capacity(rasterized.len());
+        for ((metrics, bitmap), (x, y)) in rasterized.iter().zip(placements){
+            for row in 0..metrics.height{
+                let target = (y as usize + row) * ATLAS_WIDTH as usize + x as usize;
+                pixels[target..target + metrics.width].copy_from_slice(&bitmap[row * metrics.width..(row + 1) * metrics.width]);
+            }
+            //ymin is the distance of the bottom of the bitmap above the baseline
+            let top = line_metrics.ascent - (metrics.ymin as f32 + metrics.height as f32);
+            glyphs.push(GlyphInfo{
+                uv_rect: [x as f32 / ATLAS_WIDTH as f32, y as f32 / height as f32, metrics.width as f32 / ATLAS_WIDTH as f32, metrics.height as f32 / height as f32],
+                size: [metrics.width as f32 / RASTER_SIZE, metrics.height as f32 / RASTER_SIZE],
+                offset: [metrics.xmin as f32 / RASTER_SIZE, top / RASTER_SIZE],
+                advance: metrics.advance_width / RASTER_SIZE,
+            });
+        }
+        Self{
+            width: ATLAS_WIDTH,
+            height,
+            pixels,
+            line_height: line_metrics.new_line_size / RASTER_SIZE,
+            solid_uv_rect: [solid_center[0], solid_center[1], 0.0, 0.0],
+            glyphs,
+        }
+    }
+
+    pub(crate) fn glyph(&self, character: char) -> &GlyphInfo{
+        let character = if (FIRST_CHARACTER..=LAST_CHARACTER).contains(&character) { character } else { FALLBACK_CHARACTER };
+        &self.glyphs[character as usize - FIRST_CHARACTER as usize]
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use super::glyph_atlas::{glyph_atlas, GlyphInfo};
+
+//where the position of a text is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextSpace{
+    World,      //world units through the camera, y up. The text moves and zooms with the map
+    Screen,     //pixels of the window from its top left corner, y down. Not affected by the camera
+}
+
+//what a Text drawable shows. The content is shared, so copying it into the render snapshot every tick is cheap
+#[derive(Debug, Clone)]
+pub(crate) struct TextContent{
+    pub(crate) content: Arc<str>,
+    pub(crate) size: f32,           //height of one em, in world units or pixels depending on the space
+    pub(crate) color: [f32; 4],
+    pub(crate) space: TextSpace,
+}
+
+//one quad of the text pipeline, has to match GlyphInput in text.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
+pub(crate) struct GlyphInstance{
+    pub(crate) rect: [f32; 4],      //top left corner, width and height. In world space the height extends downwards, so the text reads the same in both spaces
+    pub(crate) uv_rect: [f32; 4],
+    pub(crate) color: [f32; 4],
+    pub(crate) space: u32,          //TextSpace as u32
+}
+
+const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+    0 => Float32x4,
+    1 => Float32x4,
+    2 => Float32x4,
+    3 => Uint32,
+];
+
+impl GlyphInstance{
+    pub(crate) const fn desc() -> wgpu::VertexBufferLayout<'static>{
+        wgpu::VertexBufferLayout{
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+//appends one GlyphInstance per visible character. The position is the top left corner of the first line, \n starts a new line
+pub(crate) fn layout_text(text: &TextContent, position: [f32; 2], glyphs: &mut Vec<GlyphInstance>){
+    //distances are calculated with y pointing down, in world space they are mirrored
+    let down = match text.space{
+        TextSpace::World => -1.0,
+        TextSpace::Screen => 1.0,
+    };
+    place_glyphs(&text.content, text.size, |rect, glyph| {
+        glyphs.push(GlyphInstance{
+            rect: [position[0] + rect[0], position[1] + rect[1] * down, rect[2], rect[3]],
+            uv_rect: glyph.uv_rect,
+            color: text.color,
+            space: text.space as u32,
+        });
+    });
+}
+
+//calls place with the rectangle of every visible character relative to the top left corner of the text (x, y pointing down, width, height), already multiplied with the size
+pub(crate) fn place_glyphs(content: &str, size: f32, mut place: impl FnMut([f32; 4], &GlyphInfo)){
+    let atlas = glyph_atlas();
+    let mut pen = [0.0f32, 0.0f32];
+    for character in content.chars(){
+        if character == '\n'{
+            pen = [0.0, pen[1] + atlas.line_height];
+            continue;
+        }
+        let glyph = atlas.glyph(character);
+        if glyph.size[0] > 0.0 && glyph.size[1] > 0.0{
+            place([(pen[0] + glyph.offset[0]) * size, (pen[1] + glyph.offset[1]) * size, glyph.size[0] * size, glyph.size[1] * size], glyph);
+        }
+        pen[0] += glyph.advance;
+    }
+}
+
+//width and height of the text, the height covers whole lines
+pub(crate) fn measure_text(content: &str, size: f32) -> [f32; 2]{
+    let atlas = glyph_atlas();
+    let width = content.split('\n').map(|line| line.chars().map(|character| atlas.glyph(character).advance).sum::<f32>()).fold(0.0, f32::max);
+    let lines = content.split('\n').count();
+    [width * size, lines as f32 * atlas.line_height * size]
+}
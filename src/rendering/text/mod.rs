@@ -0,0 +1,3 @@
+pub(crate) mod glyph_atlas;
+pub(crate) mod layout;
+pub(crate) mod text_layer;
@@ -0,0 +1,63 @@
+// Text shader, draws the glyphs of the glyph atlas either in world space through the camera or in pixels of the window
+
+struct GlyphInput {
+    @location(0) rect: vec4<f32>,      // top left corner, width and height, the height extends downwards on screen
+    @location(1) uv_rect: vec4<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) space: u32,           // 0 = world, 1 = screen
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct Camera {
+    position: vec2<f32>,
+    size: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> cam: Camera;
+@group(1) @binding(1)
+var<uniform> screen: vec4<f32>;    // only xy is used, the size of the window in pixels
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    glyph: GlyphInput,
+) -> VertexOutput {
+    // two triangles, the pipeline doesn't cull, so the winding doesn't matter
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(1.0, 0.0),
+    );
+    let corner = corners[index];
+    var out: VertexOutput;
+    if glyph.space == 0u {
+        let world = vec2<f32>(glyph.rect.x + corner.x * glyph.rect.z, glyph.rect.y - corner.y * glyph.rect.w);
+        out.clip_position = vec4<f32>((world - cam.position) / (cam.size / 2.0), 0.0, 1.0);
+    } else {
+        let pixel = glyph.rect.xy + corner * glyph.rect.zw;
+        out.clip_position = vec4<f32>(pixel.x / screen.x * 2.0 - 1.0, 1.0 - pixel.y / screen.y * 2.0, 0.0, 1.0);
+    }
+    out.tex_coords = glyph.uv_rect.xy + corner * glyph.uv_rect.zw;
+    out.color = glyph.color;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_glyphs: texture_2d<f32>;
+@group(0) @binding(1)
+var s_glyphs: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(t_glyphs, s_glyphs, in.tex_coords).r;
+    let alpha = coverage * in.color.a;
+    if alpha < 0.01 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, alpha);
+}
@@ -0,0 +1,236 @@
+use wgpu::{RenderPipeline, BindGroup, BindGroupLayout, Buffer, RenderPass};
+
+use crate::rendering::{wgpurenderer::Renderer, depth::ignored_depth_state};
+
+use super::{glyph_atlas::glyph_atlas, layout::{GlyphInstance, TextSpace}};
+
+const INITIAL_GLYPH_CAPACITY: usize = 1024;
+
+//draws the glyphs the CamOrganizer laid out. World space text goes on top of the sprites and below the fog, screen space text on top of everything
+#[derive(Debug)]
+pub(crate) struct TextLayer{
+    pipeline: RenderPipeline,
+    atlas_bind_group: BindGroup,
+    screen_bind_group: BindGroup,
+    screen_buffer: Buffer,
+    glyph_buffer: Buffer,
+    glyph_capacity: usize,
+    world_glyphs: u32,      //the world space glyphs are at the start of the buffer, the screen space ones follow
+    screen_glyphs: u32,
+}
+
+impl TextLayer{
+    pub(crate) fn new(renderer: &Renderer) -> Self{
+        let device = &renderer.device;
+        let (atlas_bind_group_layout, atlas_bind_group) = create_glyph_atlas_bind_group(device, &renderer.queue);
+
+        let screen_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Screen Buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let screen_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_entry(0), uniform_entry(1)],
+            label: Some("text_screen_bind_group_layout"),
+        });
+        let screen_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &screen_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: renderer.camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("text_screen_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("text.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&atlas_bind_group_layout, &screen_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[GlyphInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: renderer.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(ignored_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self{
+            pipeline,
+            atlas_bind_group,
+            screen_bind_group,
+            screen_buffer,
+            glyph_buffer: create_glyph_buffer(device, INITIAL_GLYPH_CAPACITY),
+            glyph_capacity: INITIAL_GLYPH_CAPACITY,
+            world_glyphs: 0,
+            screen_glyphs: 0,
+        }
+    }
+
+    //uploads the glyphs of this frame, has to be called before the render pass is started
+    pub(crate) fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: (u32, u32), glyphs: &[GlyphInstance]){
+        let world = glyphs.iter().filter(|glyph| glyph.space == TextSpace::World as u32);
+        let screen = glyphs.iter().filter(|glyph| glyph.space == TextSpace::Screen as u32);
+        let sorted: Vec<GlyphInstance> = world.chain(screen).copied().collect();
+        self.screen_glyphs = sorted.iter().filter(|glyph| glyph.space == TextSpace::Screen as u32).count() as u32;
+        self.world_glyphs = sorted.len() as u32 - self.screen_glyphs;
+        if sorted.is_empty(){
+            return;
+        }
+        if sorted.len() > self.glyph_capacity{
+            self.glyph_capacity = sorted.len().next_power_of_two();
+            self.glyph_buffer = create_glyph_buffer(device, self.glyph_capacity);
+        }
+        queue.write_buffer(&self.screen_buffer, 0, bytemuck::cast_slice(&[size.0 as f32, size.1 as f32, 0.0, 0.0]));
+        queue.write_buffer(&self.glyph_buffer, 0, bytemuck::cast_slice(&sorted));
+    }
+
+    pub(crate) fn draw_world<'a>(&'a self, render_pass: &mut RenderPass<'a>){
+        self.draw(render_pass, 0..self.world_glyphs);
+    }
+
+    pub(crate) fn draw_screen<'a>(&'a self, render_pass: &mut RenderPass<'a>){
+        self.draw(render_pass, self.world_glyphs..self.world_glyphs + self.screen_glyphs);
+    }
+
+    fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, instances: std::ops::Range<u32>){
+        if instances.is_empty(){
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.screen_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.glyph_buffer.slice(..));
+        render_pass.draw(0..6, instances);
+    }
+}
+
+fn create_glyph_buffer(device: &wgpu::Device, capacity: usize) -> Buffer{
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Glyph Buffer"),
+        size: (capacity * std::mem::size_of::<GlyphInstance>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+//uploads the glyph atlas, the text and the ui pipeline each get their own copy, it is small
+pub(crate) fn create_glyph_atlas_bind_group(device: &wgpu::Device, queue: &wgpu::Queue) -> (BindGroupLayout, BindGroup){
+    let atlas = glyph_atlas();
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("glyph atlas"),
+        size: wgpu::Extent3d { width: atlas.width, height: atlas.height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Unorm,
+        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &atlas.pixels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(atlas.width),
+            rows_per_image: Some(atlas.height),
+        },
+        wgpu::Extent3d { width: atlas.width, height: atlas.height, depth_or_array_layers: 1 },
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
+    let atlas_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("glyph_atlas_bind_group_layout"),
+    });
+    let atlas_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &atlas_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+        label: Some("glyph_atlas_bind_group"),
+    });
+    (atlas_bind_group_layout, atlas_bind_group)
+}
@@ -0,0 +1,355 @@
+use std::num::NonZeroU64;
+
+use image::{RgbaImage, imageops::FilterType, GenericImage};
+use wgpu::{util::DeviceExt, RenderPipeline, BindGroup, Buffer, RenderPass};
+
+use crate::{cam_organizer::cam_organizer::CamState, rendering::sprites::load_level_sprites::helper_functions::to_srgba};
+
+use super::{wgpurenderer::Renderer, depth::ignored_depth_state};
+
+const TILE_TEXTURE_SIZE: u32 = 64;      //every tile texture is scaled to this size in the atlas
+const TILE_CHUNK_CELLS: usize = 16;     //a tile chunk covers TILE_CHUNK_CELLS x TILE_CHUNK_CELLS cells of the map and has its own static buffers
+
+/**
+ * These need to be in the same order as the textures in load_tile_atlas
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TileTexture{
+    Grass,
+    Forest,
+    Water,
+    Rock,
+}
+
+//what the model hands to the renderer when a level with a tile map is loaded. tiles[y * width + x] is the TileTexture (as u32) of the cell (x, y), cell (0, 0) is at the origin
+#[derive(Debug)]
+pub(crate) struct TileMapData{
+    pub(crate) origin: [f32; 2],
+    pub(crate) cell_size: f32,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) tiles: Vec<u32>,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct TileVertex{
+    pub(crate) position: [f32; 2],      //world position, tile vertices are not instanced
+    pub(crate) atlas_rect: [f32; 4],    //x, y, width, height of the tile texture in the atlas
+    pub(crate) repeat_size: f32,        //in world units, after this the texture repeats itself
+}
+
+//one piece of the tile map, it is only drawn if its bounds are visible to the camera
+#[derive(Debug)]
+struct TileChunk{
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    num_indices: u32,
+    min: [f32; 2],
+    max: [f32; 2],
+}
+
+#[derive(Debug)]
+struct AtlasEntry{
+    rect: [f32; 4],
+    repeat_size: f32,
+}
+
+//the ground of a level, this is rendered below all sprites and doesn't go through the CamOrganizer at all, since it never changes while the level is running
+#[derive(Debug)]
+pub(crate) struct TileLayer{
+    pipeline: RenderPipeline,
+    atlas_bind_group: BindGroup,
+    camera_bind_group: BindGroup,
+    atlas_entries: Vec<AtlasEntry>,
+    chunks: Vec<TileChunk>,
+}
+
+impl TileVertex{
+    pub(crate) const fn desc() -> wgpu::VertexBufferLayout<'static>{
+        wgpu::VertexBufferLayout{
+            array_stride: std::mem::size_of::<TileVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute{
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute{
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute{
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ]
+        }
+    }
+}
+
+impl TileLayer{
+    pub(crate) fn new(renderer: &Renderer) -> Self{
+        let device = &renderer.device;
+        let (atlas, atlas_entries) = load_tile_atlas();
+        let dimensions = atlas.dimensions();
+        let atlas_rgba = to_srgba(atlas);
+
+        let texture_size = wgpu::Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: 1,
+        };
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("tile atlas texture"),
+            size: texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        renderer.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &atlas_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            atlas_rgba.as_slice(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * dimensions.0),
+                rows_per_image: Some(dimensions.1),
+            },
+            texture_size,
+        );
+        let atlas_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let atlas_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("tile_atlas_bind_group_layout"),
+        });
+        let atlas_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &atlas_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("tile_atlas_bind_group"),
+        });
+
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(std::mem::size_of::<CamState>() as u64),
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("tile_camera_bind_group_layout"),
+        });
+        //the tile layer uses the same camera buffer as the sprites, so both always move together
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: renderer.camera_buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("tile_camera_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("tilemap.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tile Pipeline Layout"),
+            bind_group_layouts: &[&atlas_bind_group_layout, &camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tile Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[TileVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: renderer.config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),         //the ground is always opaque
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(ignored_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self{
+            pipeline,
+            atlas_bind_group,
+            camera_bind_group,
+            atlas_entries,
+            chunks: Vec::new(),
+        }
+    }
+
+    //replaces the current tile map, None removes it (for levels without one)
+    pub(crate) fn set_map(&mut self, device: &wgpu::Device, data: Option<TileMapData>){
+        self.chunks.clear();
+        let Some(data) = data else{
+            return;
+        };
+        for chunk_y in (0..data.height).step_by(TILE_CHUNK_CELLS){
+            for chunk_x in (0..data.width).step_by(TILE_CHUNK_CELLS){
+                let mut vertices = Vec::new();
+                let mut indices: Vec<u32> = Vec::new();
+                for y in chunk_y..(chunk_y + TILE_CHUNK_CELLS).min(data.height){
+                    for x in chunk_x..(chunk_x + TILE_CHUNK_CELLS).min(data.width){
+                        let Some(entry) = self.atlas_entries.get(data.tiles[y * data.width + x] as usize) else{
+                            continue;
+                        };
+                        let left = data.origin[0] + x as f32 * data.cell_size;
+                        let bottom = data.origin[1] + y as f32 * data.cell_size;
+                        let right = left + data.cell_size;
+                        let top = bottom + data.cell_size;
+                        let start = vertices.len() as u32;
+                        //same winding as centered_rectangle, so the back face culling works the same way
+                        for position in [[right, bottom], [right, top], [left, top], [left, bottom]]{
+                            vertices.push(TileVertex{ position, atlas_rect: entry.rect, repeat_size: entry.repeat_size });
+                        }
+                        indices.extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+                    }
+                }
+                if indices.is_empty(){
+                    continue;
+                }
+                let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Tile Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Tile Index Buffer"),
+                    contents: bytemuck::cast_slice(&indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                });
+                let max_x = (chunk_x + TILE_CHUNK_CELLS).min(data.width);
+                let max_y = (chunk_y + TILE_CHUNK_CELLS).min(data.height);
+                self.chunks.push(TileChunk{
+                    vertex_buffer,
+                    index_buffer,
+                    num_indices: indices.len() as u32,
+                    min: [data.origin[0] + chunk_x as f32 * data.cell_size, data.origin[1] + chunk_y as f32 * data.cell_size],
+                    max: [data.origin[0] + max_x as f32 * data.cell_size, data.origin[1] + max_y as f32 * data.cell_size],
+                });
+            }
+        }
+    }
+
+    //only the chunks that overlap the camera are submitted
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, cam_state: &CamState){
+        if self.chunks.is_empty(){
+            return;
+        }
+        let cam_min = [cam_state.cam_pos[0] - cam_state.cam_size[0] / 2.0, cam_state.cam_pos[1] - cam_state.cam_size[1] / 2.0];
+        let cam_max = [cam_state.cam_pos[0] + cam_state.cam_size[0] / 2.0, cam_state.cam_pos[1] + cam_state.cam_size[1] / 2.0];
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+        for chunk in self.chunks.iter(){
+            if chunk.max[0] < cam_min[0] || chunk.min[0] > cam_max[0] || chunk.max[1] < cam_min[1] || chunk.min[1] > cam_max[1]{
+                continue;
+            }
+            render_pass.set_vertex_buffer(0, chunk.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(chunk.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..chunk.num_indices, 0, 0..1);
+        }
+    }
+}
+
+
+//packs all tile textures next to each other into one image. Repeatable textures are tiled in the shader, so they need no special treatment here
+fn load_tile_atlas() -> (RgbaImage, Vec<AtlasEntry>){
+    //(image, how many world units one repetition of the texture covers), in the same order as TileTexture
+    let tiles: [(&[u8], f32); 4] = [
+        (include_bytes!("../../textures/darker_green_pixel_1x1.png"), 1.0),
+        (include_bytes!("../../textures/vegetation_repeatable_texture.png"), 4.0),
+        (include_bytes!("../../textures/Solid_blue_pixel_1x1.png"), 1.0),
+        (include_bytes!("../../textures/black_pixel.png"), 1.0),
+    ];
+    let atlas_width = TILE_TEXTURE_SIZE * tiles.len() as u32;
+    let mut atlas = RgbaImage::new(atlas_width, TILE_TEXTURE_SIZE);
+    let mut entries = Vec::with_capacity(tiles.len());
+    //half a texel is left out at every border of a tile, otherwise linear filtering would mix in the neighboring tile
+    let inset = 0.5 / atlas_width as f32;
+    let inset_v = 0.5 / TILE_TEXTURE_SIZE as f32;
+    for (index, (bytes, repeat_size)) in tiles.iter().enumerate(){
+        let image = image::load_from_memory(bytes).unwrap().to_rgba8();
+        let resized = image::imageops::resize(&image, TILE_TEXTURE_SIZE, TILE_TEXTURE_SIZE, FilterType::Triangle);
+        atlas.copy_from(&resized, index as u32 * TILE_TEXTURE_SIZE, 0).unwrap();
+        entries.push(AtlasEntry{
+            rect: [index as f32 / tiles.len() as f32 + inset, inset_v, 1.0 / tiles.len() as f32 - 2.0 * inset, 1.0 - 2.0 * inset_v],
+            repeat_size: *repeat_size,
+        });
+    }
+    (atlas, entries)
+}
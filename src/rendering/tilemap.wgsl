@@ -0,0 +1,49 @@
+// Tile map shader, the vertices are already in world space, so only the camera is applied
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) atlas_rect: vec4<f32>,
+    @location(2) repeat_size: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec2<f32>,
+    @location(1) atlas_rect: vec4<f32>,
+    @location(2) repeat_size: f32,
+};
+
+struct Camera {
+    position: vec2<f32>,
+    size: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> cam: Camera;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    var semi_size = cam.size / 2.0;
+    out.clip_position = vec4<f32>((model.position - cam.position) / semi_size, 0.0, 1.0);
+    out.world_position = model.position;
+    out.atlas_rect = model.atlas_rect;
+    out.repeat_size = model.repeat_size;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_atlas: texture_2d<f32>;
+@group(0) @binding(1)
+var s_atlas: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the texture repeats every repeat_size world units, y is flipped since texture coordinates go downwards
+    var local = fract(in.world_position / in.repeat_size);
+    local.y = 1.0 - local.y;
+    let uv = in.atlas_rect.xy + local * in.atlas_rect.zw;
+    return textureSample(t_atlas, s_atlas, uv);
+}
@@ -0,0 +1,51 @@
+// UI shader, draws the quads of the screen space UI in pixels of the window. The camera doesn't affect it
+// solid rectangles sample the fully covered block of the glyph atlas, so panels and text share one pipeline and one draw call
+
+struct QuadInput {
+    @location(0) rect: vec4<f32>,      // x, y of the top left corner, width, height
+    @location(1) uv_rect: vec4<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> screen: vec4<f32>;    // only xy is used, the size of the window in pixels
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    quad: QuadInput,
+) -> VertexOutput {
+    // two triangles, the pipeline doesn't cull, so the winding doesn't matter
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(1.0, 0.0),
+    );
+    let corner = corners[index];
+    let pixel = quad.rect.xy + corner * quad.rect.zw;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(pixel.x / screen.x * 2.0 - 1.0, 1.0 - pixel.y / screen.y * 2.0, 0.0, 1.0);
+    out.tex_coords = quad.uv_rect.xy + corner * quad.uv_rect.zw;
+    out.color = quad.color;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_atlas: texture_2d<f32>;
+@group(0) @binding(1)
+var s_atlas: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(t_atlas, s_atlas, in.tex_coords).r;
+    let alpha = coverage * in.color.a;
+    if alpha < 0.01 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, alpha);
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use wgpu::{RenderPipeline, BindGroup, Buffer, RenderPass};
+
+use super::{wgpurenderer::Renderer, depth::ignored_depth_state, text::text_layer::create_glyph_atlas_bind_group};
+
+const INITIAL_QUAD_CAPACITY: usize = 1024;
+
+//the controller replaces the quads whenever it rebuilt the UI, the renderer draws the newest ones. Neither of them waits for the other
+pub(crate) type SharedUiFrame = Arc<ArcSwap<Vec<UiQuad>>>;
+
+pub(crate) fn new_shared_ui_frame() -> SharedUiFrame{
+    Arc::new(ArcSwap::from_pointee(Vec::new()))
+}
+
+//one quad of the UI pipeline in pixels of the window, has to match QuadInput in ui.wgsl. Solid rectangles use the solid block of the glyph atlas as uv_rect
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
+pub(crate) struct UiQuad{
+    pub(crate) rect: [f32; 4],      //x and y of the top left corner, width and height
+    pub(crate) uv_rect: [f32; 4],
+    pub(crate) color: [f32; 4],
+}
+
+const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+    0 => Float32x4,
+    1 => Float32x4,
+    2 => Float32x4,
+];
+
+//draws the screen space UI the controller built. It goes on top of the world and the screen space text, only the debug overlay is drawn above it
+#[derive(Debug)]
+pub(crate) struct UiLayer{
+    pipeline: RenderPipeline,
+    atlas_bind_group: BindGroup,
+    screen_bind_group: BindGroup,
+    screen_buffer: Buffer,
+    quad_buffer: Buffer,
+    quad_capacity: usize,
+    quad_count: u32,
+    frame: SharedUiFrame,
+}
+
+impl UiLayer{
+    pub(crate) fn new(renderer: &Renderer, frame: SharedUiFrame) -> Self{
+        let device = &renderer.device;
+        let (atlas_bind_group_layout, atlas_bind_group) = create_glyph_atlas_bind_group(device, &renderer.queue);
+
+        let screen_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("UI Screen Buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let screen_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("ui_screen_bind_group_layout"),
+        });
+        let screen_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &screen_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("ui_screen_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("ui.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("UI Pipeline Layout"),
+            bind_group_layouts: &[&atlas_bind_group_layout, &screen_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("UI Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout{
+                    array_stride: std::mem::size_of::<UiQuad>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &ATTRIBUTES,
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: renderer.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(ignored_depth_state()),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self{
+            pipeline,
+            atlas_bind_group,
+            screen_bind_group,
+            screen_buffer,
+            quad_buffer: create_quad_buffer(device, INITIAL_QUAD_CAPACITY),
+            quad_capacity: INITIAL_QUAD_CAPACITY,
+            quad_count: 0,
+            frame,
+        }
+    }
+
+    //uploads the newest quads of the controller, has to be called before the render pass is started
+    pub(crate) fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: (u32, u32)){
+        let quads = self.frame.load();
+        self.quad_count = quads.len() as u32;
+        if quads.is_empty(){
+            return;
+        }
+        if quads.len() > self.quad_capacity{
+            self.quad_capacity = quads.len().next_power_of_two();
+            self.quad_buffer = create_quad_buffer(device, self.quad_capacity);
+        }
+        queue.write_buffer(&self.screen_buffer, 0, bytemuck::cast_slice(&[size.0 as f32, size.1 as f32, 0.0, 0.0]));
+        queue.write_buffer(&self.quad_buffer, 0, bytemuck::cast_slice(&quads));
+    }
+
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>){
+        if self.quad_count == 0{
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.atlas_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.screen_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.quad_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.quad_count);
+    }
+}
+
+fn create_quad_buffer(device: &wgpu::Device, capacity: usize) -> Buffer{
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("UI Quad Buffer"),
+        size: (capacity * std::mem::size_of::<UiQuad>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
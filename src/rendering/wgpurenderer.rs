@@ -1,41 +1,53 @@
-use std::{sync::{atomic::AtomicBool, Arc, RwLock}, ops::DerefMut, cell::RefCell};
+use std::{sync::{atomic::AtomicBool, Arc}, ops::DerefMut, cell::RefCell, time::Duration};
 
 use async_std::task::{TaskId, Task, block_on};
-use wgpu::{util::DeviceExt, ShaderModule, RenderPipeline, BindGroup, Device, CommandBuffer, Buffer};
+use wgpu::{util::DeviceExt, ShaderModule, Device, CommandBuffer, Buffer};
 use winit::{window::Window, event::WindowEvent};
 
-use crate::{rendering::{vertex::Vertex, sprites::{sprite_mapping::Sprite, vertex_configration::{VertexConfigrationTrait, self}}}, controller::{position::Position, controller::SharablePosition}, model::model::GameObjectList, cam_organizer::cam_organizer::CamState};
+use crate::cam_organizer::{cam_organizer::CamState, frame_mailbox::FrameMailbox};
 
-use super::{sprite_instance::SpriteInstance, sprites::vertex_configration::{VertexConfigration, NUM_VERTEX_CONFIGURATIONS}};
+use super::{sprite_instance::SpriteInstance, sprites::{vertex_configration::VertexConfigHandle, load_sprites::SpritePipeline}, tilemap::{TileLayer, TileMapData}, fog_of_war::{FogLayer, FogData}, depth::create_depth_view, capture::save_screenshot, init::{create_vertex_buffer_for_config, create_initial_instance_buffer_struct}, debug_overlay::{stats::RenderStats, overlay::DebugOverlay}, text::{text_layer::TextLayer, layout::GlyphInstance}, ui_layer::UiLayer};
 
-#[allow(unused)]
-pub(crate) type VertexBuffers = [wgpu::Buffer; NUM_VERTEX_CONFIGURATIONS];   //<--Update this. This will updated other uses as well, less error prone
-
-pub(crate) type VertexBufferStructs = [VertexBufferStruct; NUM_VERTEX_CONFIGURATIONS];   //<--Update this. This will updated other uses as well, less error prone
+//instance buffers are created with room for this many instances more than needed, so they don't have to grow every time an object is added
+const INSTANCE_BUFFER_HEADROOM: u32 = 1300;
 
 const NUM_INDICES_PER_SPRITE: u32 = 6;
+//the renderer waits at most this long for the CamOrganizer, so window events are still handled if it stops
+const FRAME_WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+//what is visible where nothing is drawn
+const CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 };
 #[derive(Debug)]
 #[allow(unused)]
 pub struct Renderer {
-    pub(crate) surface: wgpu::Surface,
+    pub(crate) surface: Option<wgpu::Surface>,     //None for headless renderers, which only render into textures, see capture.rs
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) config: wgpu::SurfaceConfiguration,
     pub(crate) size: winit::dpi::PhysicalSize<u32>,
-    pub(crate) window: Window,
+    pub(crate) window: Option<Window>,
     pub(crate) running: Arc<AtomicBool>,  //<-- this is used to indicate whether the program should exit or not
     pub(crate) shader: ShaderModule,
-    pub(crate) render_receiver: Option<flume::Receiver<(Vec<RenderChunk>, CamState)>>,
+    pub(crate) frame_mailbox: Option<Arc<FrameMailbox>>,     //where the CamOrganizer leaves the newest frame
     pub(crate) index_buffer: wgpu::Buffer,
-    pub(crate) vertex_structs: VertexBufferStructs,  //<--this needs to be updated if the number of vertex_configurations changes! this is used to store the vertex buffers for the sprites, so they dont have to be recreated!
+    pub(crate) vertex_structs: Vec<Option<VertexBufferStruct>>,  //indexed by VertexConfigHandle, the buffers are created the first time a vertex configuration is rendered and then reused
     pub(crate) cam_size: [f32; 2],
     pub(crate) camera_buffer: wgpu::Buffer,
     pub(crate) to_upgrade_vec: RefCell<Vec<(usize, Vec<SpriteInstance>)>>,
+    pub(crate) tile_layer: Option<TileLayer>,
+    pub(crate) tile_map_receiver: Option<flume::Receiver<Option<TileMapData>>>,
+    pub(crate) fog_layer: Option<FogLayer>,
+    pub(crate) fog_receiver: Option<flume::Receiver<Option<FogData>>>,
+    pub(crate) depth_view: wgpu::TextureView,
+    pub(crate) screenshot_requested: bool,      //the next frame is also written to a png, see capture.rs
+    pub(crate) render_stats: RenderStats,
+    pub(crate) text_layer: Option<TextLayer>,
+    pub(crate) ui_layer: Option<UiLayer>,
+    pub(crate) debug_overlay: Option<DebugOverlay>,     //only windows have one, it is hidden until F3 is pressed
 }
 
 #[derive(Debug)]
 pub struct RenderChunk{
-    pub(crate) vertex_conf: VertexConfigration,
+    pub(crate) vertex_conf: VertexConfigHandle,
     pub(crate) instance_buffer: Vec<SpriteInstance>,
 }
 pub struct RenderChunkRaw<'a>{
@@ -72,17 +84,38 @@ impl Renderer {
 
 
 
+    //creates the vertex and instance buffer of a vertex configuration that hasn't been rendered yet
+    fn ensure_vertex_struct(&mut self, handle: VertexConfigHandle, len: usize){
+        let id = handle.0 as usize;
+        if self.vertex_structs.len() <= id{
+            self.vertex_structs.resize_with(id + 1, || None);
+        }
+        if self.vertex_structs[id].is_some(){
+            return;
+        }
+        self.render_stats.vertex_buffers_created += 1;
+        self.vertex_structs[id] = Some(VertexBufferStruct{
+            vertex_buffer: create_vertex_buffer_for_config(&self.device, handle.to_configuration()),
+            instance_state: create_initial_instance_buffer_struct(&self.device, len + INSTANCE_BUFFER_HEADROOM as usize),
+        });
+    }
+
+    #[inline(always)]
+    fn vertex_struct(&self, id: usize) -> &VertexBufferStruct{
+        self.vertex_structs[id].as_ref().expect("the buffers of a vertex configuration have to be created before it is rendered")
+    }
+
     #[inline(always)]
-    fn chunk_to_raw<'a>(&'a self, chunk: RenderChunk, current_override_len: usize) -> (RenderChunkRaw, Option<UpdateBufferStruct>){
+    fn chunk_to_raw(&self, chunk: &RenderChunk) -> (RenderChunkRaw<'_>, Option<UpdateBufferStruct>){
         let len = chunk.instance_buffer.len();
-        let id = chunk.vertex_conf as usize;
-        let max_amount_to_render = self.vertex_structs[id].instance_state.num_instance_size.borrow().clone() as usize;
+        let id = chunk.vertex_conf.0 as usize;
+        let max_amount_to_render = *self.vertex_struct(id).instance_state.num_instance_size.borrow() as usize;
         let amount_to_render = if max_amount_to_render > len {len} else {max_amount_to_render};
-        let ret = self.update_instance_buffer(chunk.instance_buffer, id);
+        let ret = self.update_instance_buffer(&chunk.instance_buffer, id);
         (RenderChunkRaw{
-            vertex_buffer: &self.vertex_structs[id].vertex_buffer,
+            vertex_buffer: &self.vertex_struct(id).vertex_buffer,
             index_buffer: &self.index_buffer,
-            instance_buffer:  &self.vertex_structs[id].instance_state.instance_buffer,
+            instance_buffer:  &self.vertex_struct(id).instance_state.instance_buffer,
             num_indices: NUM_INDICES_PER_SPRITE,
             instances_len: amount_to_render,
         }, ret)
@@ -90,7 +123,11 @@ impl Renderer {
 
 
     pub fn window(&self) -> &Window {
-        &self.window
+        self.window.as_ref().expect("a headless renderer has no window")
+    }
+
+    pub(crate) fn surface(&self) -> &wgpu::Surface {
+        self.surface.as_ref().expect("a headless renderer has no surface")
     }
 
 
@@ -103,7 +140,8 @@ impl Renderer {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            self.surface().configure(&self.device, &self.config);
+            self.depth_view = create_depth_view(&self.device, &self.config);
         }
     }
 
@@ -113,36 +151,70 @@ impl Renderer {
     }
 
     #[inline(always)]
-    pub(crate) fn render(&mut self, render_pipeline: &RenderPipeline, bind_group: &BindGroup, camera_bind_group: &BindGroup) -> Result<(), wgpu::SurfaceError> {
+    pub(crate) fn render(&mut self, sprite_pipeline: &SpritePipeline) -> Result<(), wgpu::SurfaceError> {
 
 
-        if self.render_receiver.is_none(){
+        let Some(frame_mailbox) = &self.frame_mailbox else{
+            return Ok(());
+        };
+        //if no new frame was built since the last one, there is nothing new to show
+        let Some((render_ops, glyphs, cam_state)) = frame_mailbox.take_latest(FRAME_WAIT_TIMEOUT) else{
             return Ok(());
+        };
+        self.update_tile_map();
+        self.update_fog();
+
+        if self.screenshot_requested{
+            self.screenshot_requested = false;
+            match self.render_to_image(&render_ops, &glyphs, &cam_state, sprite_pipeline){
+                Ok(image) => save_screenshot(image),
+                Err(error) => eprintln!("Could not take a screenshot: {}", error),
+            }
         }
 
-        let (render_ops, cam_state) = self.render_receiver.as_ref().unwrap().recv().unwrap();
-  
+        //the surface is the inner part of the window, the output (surfaceTexture) is the actual texture that we will render to
+        let output = self.surface().get_current_texture()?;
+        
+        //this is required to tell the code how the rendering is done
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.draw_frame(&view, &render_ops, &glyphs, &cam_state, sprite_pipeline);
+        output.present();
+        self.render_stats.frame_presented();
+    
+        Ok(())
+
 
-        self.update_camera_buffer(&cam_state);
+
+    }
+
+    //draws one frame into the given view, which is either the window or an offscreen texture. The view has to have the size and format of self.config
+    pub(crate) fn draw_frame(&mut self, view: &wgpu::TextureView, render_ops: &[RenderChunk], glyphs: &[GlyphInstance], cam_state: &CamState, sprite_pipeline: &SpritePipeline){
+
+        self.update_camera_buffer(cam_state);
+        for op in render_ops.iter(){
+            self.ensure_vertex_struct(op.vertex_conf, op.instance_buffer.len());
+        }
+        self.render_stats.instance_counts = render_ops.iter().map(|op| (op.vertex_conf, op.instance_buffer.len())).collect();
+        if let Some(text_layer) = &mut self.text_layer{
+            text_layer.prepare(&self.device, &self.queue, (self.config.width, self.config.height), glyphs);
+        }
+        if let Some(ui_layer) = &mut self.ui_layer{
+            ui_layer.prepare(&self.device, &self.queue, (self.config.width, self.config.height));
+        }
+        if let Some(debug_overlay) = &mut self.debug_overlay{
+            let dropped_frames = self.frame_mailbox.as_ref().map_or(0, |frame_mailbox| frame_mailbox.dropped_frames());
+            debug_overlay.prepare(&self.device, &self.queue, (self.config.width, self.config.height), &self.render_stats, dropped_frames);
+        }
         let mut chunk_raw_vec = Vec::with_capacity(render_ops.len());
         let mut to_update_vec = Vec::new();
-        for op in render_ops.into_iter(){
-            let (raw, to_update)  = self.chunk_to_raw(op, to_update_vec.len());
+        for op in render_ops.iter(){
+            let (raw, to_update)  = self.chunk_to_raw(op);
             chunk_raw_vec.push(raw);
             if let Some(to_update) = to_update{
                 to_update_vec.push(to_update);
             }
         }
-        
-        for u in to_update_vec.iter(){
-            println!("updating instance buffer for vertex configuration list {:?}", u.offset);
-        }
-        
-        //the surface is the inner part of the window, the output (surfaceTexture) is the actual texture that we will render to
-        let output = self.surface.get_current_texture()?;
-        
-        //this is required to tell the code how the rendering is done
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         //this encoder is able to create a command buffer, which is a list of commands that will be executed by the GPU
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -156,28 +228,34 @@ impl Renderer {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(CLEAR_COLOR),
                         store: true,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
             });
 
+            //the ground goes first, everything else is drawn on top of it
+            if let Some(tile_layer) = &self.tile_layer{
+                tile_layer.draw(&mut render_pass, cam_state);
+            }
 
             for render_op in chunk_raw_vec.iter() {
 
 
-                render_pass.set_pipeline(&render_pipeline);   //the correct pipeline tells the GPU what shaders will be used on the vertices
-                render_pass.set_bind_group(0, bind_group, &[]);  //this bind group contains the textures we loaded, if we want to switch all of the textures at once, we can do that by switching to another bind group. Might create some interesting effects
-                render_pass.set_bind_group(1, camera_bind_group, &[]);
+                render_pass.set_pipeline(&sprite_pipeline.render_pipeline);   //the correct pipeline tells the GPU what shaders will be used on the vertices
+                render_pass.set_bind_group(0, &sprite_pipeline.bind_group, &[]);  //this bind group contains the textures we loaded, if we want to switch all of the textures at once, we can do that by switching to another bind group. Might create some interesting effects
+                render_pass.set_bind_group(1, &sprite_pipeline.camera_bind_group, &[]);
                 render_pass.set_vertex_buffer(0, render_op.vertex_buffer.slice(..));
 
                 render_pass.set_vertex_buffer(1, render_op.instance_buffer.slice(..));
@@ -187,6 +265,27 @@ impl Renderer {
                 render_pass.draw_indexed(0..render_op.num_indices, 0, 0..render_op.instances_len as u32);
             }
 
+            if let Some(text_layer) = &self.text_layer{
+                text_layer.draw_world(&mut render_pass);
+            }
+
+            //the fog goes on top of everything in the world, it dims or covers whatever the player can't see
+            if let Some(fog_layer) = &self.fog_layer{
+                fog_layer.draw(&mut render_pass, &self.index_buffer);
+            }
+
+            if let Some(text_layer) = &self.text_layer{
+                text_layer.draw_screen(&mut render_pass);
+            }
+
+            if let Some(ui_layer) = &self.ui_layer{
+                ui_layer.draw(&mut render_pass);
+            }
+
+            if let Some(debug_overlay) = &self.debug_overlay{
+                debug_overlay.draw(&mut render_pass);
+            }
+
 
 
 
@@ -196,16 +295,35 @@ impl Renderer {
         
         // submit will accept anything that implements IntoIter
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
 
         self.set_instance_buffers(to_update_vec);
-    
-        Ok(())
-
+    }
 
 
+    //the model sends a new tile map whenever a level is loaded, only the latest one matters
+    pub(crate) fn update_tile_map(&mut self){
+        let Some(receiver) = &self.tile_map_receiver else{
+            return;
+        };
+        let Some(data) = receiver.try_iter().last() else{
+            return;
+        };
+        if let Some(tile_layer) = &mut self.tile_layer{
+            tile_layer.set_map(&self.device, data);
+        }
     }
 
+    pub(crate) fn update_fog(&mut self){
+        let Some(receiver) = &self.fog_receiver else{
+            return;
+        };
+        let Some(data) = receiver.try_iter().last() else{
+            return;
+        };
+        if let Some(fog_layer) = &mut self.fog_layer{
+            fog_layer.set_fog(&self.device, &self.queue, data);
+        }
+    }
 
 #[inline(always)]
     fn update_camera_buffer(&mut self, cam_state: &CamState){
@@ -218,15 +336,15 @@ impl Renderer {
     }
 
     #[inline(always)]
-    fn update_instance_buffer<'a>(&'a self, instances_buffer: Vec<SpriteInstance>, id: usize) -> Option<UpdateBufferStruct>{
+    fn update_instance_buffer(&self, instances_buffer: &[SpriteInstance], id: usize) -> Option<UpdateBufferStruct>{
         let len = instances_buffer.len();
-        let mut borrow = self.vertex_structs[id].instance_state.num_instance_size.borrow_mut();
+        let mut borrow = self.vertex_struct(id).instance_state.num_instance_size.borrow_mut();
         let size = borrow.deref_mut();
         if len < *size as usize {
-            self.queue.write_buffer(&self.vertex_structs[id].instance_state.instance_buffer, 0, bytemuck::cast_slice(&instances_buffer));
+            self.queue.write_buffer(&self.vertex_struct(id).instance_state.instance_buffer, 0, bytemuck::cast_slice(instances_buffer));
             return None;
         }
-        let new_size = len as u32 + 1300;
+        let new_size = len as u32 + INSTANCE_BUFFER_HEADROOM;
         *size = new_size;
         let buf = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Instance Buffer"),
@@ -236,7 +354,7 @@ impl Renderer {
         });
         println!("New buffer size: {}", new_size);
 
-        self.queue.write_buffer(&buf, 0, bytemuck::cast_slice(&instances_buffer));
+        self.queue.write_buffer(&buf, 0, bytemuck::cast_slice(instances_buffer));
 
         Some(UpdateBufferStruct { buffer: buf, offset: id })
 
@@ -245,8 +363,10 @@ impl Renderer {
 
     fn set_instance_buffers(&mut self, to_update_vec: Vec<UpdateBufferStruct>){
         for (u) in to_update_vec.into_iter(){
-            println!("updating instance buffer {}", u.offset);
-            self.vertex_structs[u.offset].instance_state.instance_buffer = u.buffer;
+            self.render_stats.buffer_reallocations += 1;
+            if let Some(vertex_struct) = &mut self.vertex_structs[u.offset]{
+                vertex_struct.instance_state.instance_buffer = u.buffer;
+            }
         }
     }
 
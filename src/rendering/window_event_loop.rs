@@ -1,14 +1,24 @@
 use std::{sync::{atomic::AtomicBool, Arc}, thread::JoinHandle};
 
-use async_std::task::block_on;
 use flume::Receiver;
 use winit::{event::{Event, WindowEvent}, event_loop::{ControlFlow}};
 
-use crate::{controller::{input::{ControllerInput, MouseInputType}, position::Position, controller::SharablePosition, renderer_commands::RendererCommand}, model::model::GameObjectList, cam_organizer::cam_organizer::CamState};
+use crate::{model::time_scale::TimeScale, controller::{input::{ControllerInput, MouseInputType}, renderer_commands::RendererCommand}};
 
-use super::{ wgpurenderer::{Renderer, RenderChunk}, sprites::load_sprites::load_sprites, init::init};
+use super::{ wgpurenderer::Renderer, sprites::load_sprites::{load_sprites, try_load_sprites}, init::init, tilemap::{TileLayer, TileMapData}, fog_of_war::{FogLayer, FogData}, depth::create_depth_view, hot_reload::HotReloader, capabilities::GraphicsOptions, debug_overlay::{stats::DebugStats, overlay::DebugOverlay}, text::text_layer::TextLayer, ui_layer::{UiLayer, SharedUiFrame}};
+use crate::cam_organizer::frame_mailbox::FrameMailbox;
 
 
+//what the other threads hand to the renderer: the commands of the controller, the frames of the CamOrganizer and what the model sends when a level is loaded
+pub(crate) struct RendererInputs{
+    pub(crate) controller_receiver: Receiver<RendererCommand>,
+    pub(crate) frame_mailbox: Arc<FrameMailbox>,
+    pub(crate) tile_map_receiver: Receiver<Option<TileMapData>>,
+    pub(crate) fog_receiver: Receiver<Option<FogData>>,
+    pub(crate) sprite_set_receiver: Receiver<u32>,
+    pub(crate) debug_stats: Arc<DebugStats>,
+    pub(crate) ui_frame: SharedUiFrame,
+}
 
 
 impl Renderer {
@@ -16,34 +26,58 @@ impl Renderer {
     //this is the main loop of the program, it will be called from main.rs
     //this whole file is only for putting the event loop and window handling in one easy to use place
     #[inline(always)]
-    pub(crate) async fn run(running: Arc<AtomicBool>, mut join_handles: Vec<JoinHandle<()>>, controller_sender: flume::Sender<ControllerInput>, controller_receiver: Receiver<RendererCommand>, cam_pos: SharablePosition, renderer_receiver: Receiver<(Vec<RenderChunk>, CamState)>) {
+    pub(crate) async fn run(running: Arc<AtomicBool>, mut join_handles: Vec<JoinHandle<()>>, controller_sender: flume::Sender<ControllerInput>, inputs: RendererInputs, dev_mode: bool, graphics_options: GraphicsOptions) {
+        let RendererInputs{ controller_receiver, frame_mailbox, tile_map_receiver, fog_receiver, sprite_set_receiver, debug_stats, ui_frame } = inputs;
 
 
         //this is the most important struct for the current state. Almost all infos are grouped here
-        let (mut renderer, event_loop) = init(running, cam_pos).await;  //we cannot put the event_loop into the Renderer struct, as the .run() function requires a move, which takes ownership of the values in it. And it is not possible for a data field to take ownership of the struct it is in
-        renderer.render_receiver = Some(renderer_receiver);
+        let (mut renderer, event_loop) = match init(running.clone(), &graphics_options).await{  //we cannot put the event_loop into the Renderer struct, as the .run() function requires a move, which takes ownership of the values in it. And it is not possible for a data field to take ownership of the struct it is in
+            Ok(initialized) => initialized,
+            Err(error) => {
+                //the other threads are already running, they have to be stopped before the program can end
+                eprintln!("Could not start the renderer: {}", error);
+                running.store(false, std::sync::atomic::Ordering::SeqCst);
+                let _ = controller_sender.send(ControllerInput::Exit);
+                join_handles.drain(..).for_each(|join_handle| {
+                    join_handle.join().unwrap();
+                });
+                return;
+            }
+        };
+        renderer.frame_mailbox = Some(frame_mailbox);
 
         #[allow(unused)]
-        let (mut render_pipeline, mut bind_group, mut camera_bind_group) = load_sprites(0, &renderer);
+        let mut sprite_set = 0;     //the sprite manifest of the current level, the model sends another one when a level needs it
+        let mut sprite_pipeline = load_sprites(sprite_set, &renderer);
+        renderer.tile_layer = Some(TileLayer::new(&renderer));
+        renderer.tile_map_receiver = Some(tile_map_receiver);
+        renderer.fog_layer = Some(FogLayer::new(&renderer));
+        renderer.fog_receiver = Some(fog_receiver);
+        renderer.text_layer = Some(TextLayer::new(&renderer));
+        renderer.ui_layer = Some(UiLayer::new(&renderer, ui_frame));
+        renderer.debug_overlay = Some(DebugOverlay::new(&renderer, debug_stats.clone()));
+        let mut simulation_speed = (false, TimeScale::NORMAL);     //what the window title shows
+        //the controller lays out the UI and converts clicks into it, so it has to know the size of the window from the start
+        let _ = controller_sender.send(ControllerInput::WindowResized { dimensions: (renderer.size.width, renderer.size.height) });
+        let mut hot_reloader = if dev_mode { Some(HotReloader::new(0)) } else { None };
         
         event_loop.run(move |event, _, control_flow| match event {
-            Event::RedrawRequested(window_id) if window_id == renderer.window.id() => {
+            Event::RedrawRequested(window_id) if window_id == renderer.window().id() => {
                 //we could trigger this Event by calling window.request_redraw(), for example in MainEventsCleared, but rendering right there is faster due to reduced function overhead
             }
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == renderer.window.id() => {match event {
+            } if window_id == renderer.window().id() => {match event {
                 //These Window-Events are prebaked, we only need to know which ones to respond to and how
                 WindowEvent::Resized(physical_size) => {
                     if physical_size.width > 0 && physical_size.height > 0 {
                         renderer.size = *physical_size;
                         renderer.config.width = renderer.size.width;
                         renderer.config.height = renderer.size.height;
-                        renderer.surface.configure(&renderer.device, &renderer.config);
-
-
-                        //TODO: If the window gets resized, the controller should be notified, otherwise it might calculate mouse positions, etc. wrong
+                        renderer.surface().configure(&renderer.device, &renderer.config);
+                        renderer.depth_view = create_depth_view(&renderer.device, &renderer.config);
+                        let _ = controller_sender.send(ControllerInput::WindowResized { dimensions: (physical_size.width, physical_size.height) });
                     }
                 }
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
@@ -52,12 +86,14 @@ impl Renderer {
                         renderer.size = **new_inner_size;
                         renderer.config.width = renderer.size.width;
                         renderer.config.height = renderer.size.height;
-                        renderer.surface.configure(&renderer.device, &renderer.config);
+                        renderer.surface().configure(&renderer.device, &renderer.config);
+                        renderer.depth_view = create_depth_view(&renderer.device, &renderer.config);
+                        let _ = controller_sender.send(ControllerInput::WindowResized { dimensions: (new_inner_size.width, new_inner_size.height) });
                     }
                 }
                 WindowEvent::CloseRequested
                  => {
-                    renderer.render_receiver = None;
+                    renderer.frame_mailbox = None;
                     renderer.running.store(false, std::sync::atomic::Ordering::SeqCst);
                     controller_sender.send(ControllerInput::Exit).expect("Could not send exit info to controller thread!");
                     //now we wait for the other threads to finish, before we finally close the program completely we cannot just use for handles in join_handles, because they would still exist, but be captured by the move closure, which would be a problem
@@ -79,6 +115,14 @@ impl Renderer {
                         }
                     }
                 }
+                //the typed text for the console, with the keyboard layout already applied
+                WindowEvent::ReceivedCharacter(character)
+                 => {
+                    let ret = controller_sender.send(ControllerInput::Character(*character));
+                    if ret.is_err() && renderer.running.load(std::sync::atomic::Ordering::SeqCst){
+                        println!("Could not send typed characters to controller thread!");
+                    }
+                }
                 WindowEvent::MouseInput { device_id: _, state , button: btn, .. }
                  => {
                     let ret = controller_sender.send(ControllerInput::MouseInput { action: MouseInputType::Click { button: *btn, state: *state } });
@@ -129,7 +173,37 @@ impl Renderer {
     
         }
         Event::MainEventsCleared => {
-            let res = renderer.render(&render_pipeline, &bind_group, &camera_bind_group);
+            if debug_stats.simulation_speed() != simulation_speed{
+                simulation_speed = debug_stats.simulation_speed();
+                renderer.window().set_title(&window_title(simulation_speed));
+            }
+            if let Some(new_sprite_set) = sprite_set_receiver.try_iter().last().filter(|new_sprite_set| *new_sprite_set != sprite_set){
+                match try_load_sprites(new_sprite_set, &renderer){
+                    Ok(new_sprite_pipeline) => {
+                        sprite_pipeline = new_sprite_pipeline;
+                        sprite_set = new_sprite_set;
+                    }
+                    //the sprites are in the same order in every manifest, so the level can still be drawn with the old ones
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+            if let Some(reloader) = &mut hot_reloader{
+                if reloader.poll(){
+                    match renderer.hot_reload(&reloader.shader_path.clone(), sprite_set){
+                        Ok(new_sprite_pipeline) => {
+                            println!("Reloaded shader and sprites");
+                            sprite_pipeline = new_sprite_pipeline;
+                            renderer.window().set_title(&window_title(simulation_speed));
+                        }
+                        Err(error) => {
+                            //the old pipeline keeps running, so the error can be fixed without restarting
+                            eprintln!("Hot reload failed: {}", error);
+                            renderer.window().set_title(&format!("spawg - reload failed: {}", error.lines().next().unwrap_or_default()));
+                        }
+                    }
+                }
+            }
+            let res = renderer.render(&sprite_pipeline);
             if let Err(e) = res {
                 eprintln!("Error during rendering: {:?}", e);
             }
@@ -137,20 +211,26 @@ impl Renderer {
             while let Ok(command) = controller_receiver.try_recv(){
                 match command {
                     RendererCommand::PLACEHOLDER => println!("Placeholder command received!"),
-                    RendererCommand::TOGGLE_FULLSCREEN => {let fullscreen = renderer.window.fullscreen().is_some();
+                    RendererCommand::TOGGLE_FULLSCREEN => {let fullscreen = renderer.window().fullscreen().is_some();
                         println!("Toggling fullscreen: {}", !fullscreen);
                         if let Some(displ) = renderer.window().current_monitor(){
                             if fullscreen {
-                                renderer.window.set_fullscreen(None);
+                                renderer.window().set_fullscreen(None);
                             } else {
-                                renderer.window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(Some(displ))));
+                                renderer.window().set_fullscreen(Some(winit::window::Fullscreen::Borderless(Some(displ))));
                             }
                         }
                     },
+                    RendererCommand::Screenshot => renderer.screenshot_requested = true,
+                    RendererCommand::TOGGLE_DEBUG_OVERLAY => {
+                        if let Some(debug_overlay) = &mut renderer.debug_overlay{
+                            debug_overlay.visible = !debug_overlay.visible;
+                        }
+                    },
                     RendererCommand::SHUTDOWN => {
 
                         //same logic as in CloseRequested
-                        renderer.render_receiver = None;
+                        renderer.frame_mailbox = None;
                         renderer.running.store(false, std::sync::atomic::Ordering::SeqCst);
                         controller_sender.send(ControllerInput::Exit).expect("Could not send exit info to controller thread!");
                         //now we wait for the other threads to finish, before we finally close the program completely we cannot just use for handles in join_handles, because they would still exist, but be captured by the move closure, which would be a problem
@@ -167,4 +247,14 @@ impl Renderer {
         });
     }
     
-}
\ No newline at end of file
+}
+
+//the name of the game, followed by the speed of the simulation if it isn't running normally
+fn window_title((paused, time_scale): (bool, TimeScale)) -> String{
+    match (paused, time_scale == TimeScale::NORMAL){
+        (false, true) => "spawg".to_string(),
+        (false, false) => format!("spawg - {}", time_scale),
+        (true, true) => "spawg - paused".to_string(),
+        (true, false) => format!("spawg - paused ({})", time_scale),
+    }
+}
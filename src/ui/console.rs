@@ -0,0 +1,137 @@
+use crate::{controller::console_commands::CONSOLE_COMMANDS, rendering::text::layout::measure_text};
+
+use super::{context::Ui, widgets::{label, panel, PADDING, TEXT_SIZE}};
+
+const VISIBLE_LINES: usize = 12;     //the output lines shown above the input line
+const MAX_LINES: usize = 200;       //older output is dropped, it is still in the log
+const MAX_HISTORY: usize = 100;
+
+//the drop-down console at the top of the window, toggled with the key left of 1. It only edits and shows text, the controller parses and runs the lines
+pub(crate) struct Console{
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    history_index: Option<usize>,       //the entry of the history that is shown in the input while going through it with up and down
+    output: Vec<String>,
+}
+
+impl Console{
+    pub(crate) fn new() -> Self{
+        Self{
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            output: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_open(&self) -> bool{
+        self.open
+    }
+
+    pub(crate) fn toggle(&mut self){
+        self.open = !self.open;
+    }
+
+    pub(crate) fn close(&mut self){
+        self.open = false;
+    }
+
+    //control characters like return or backspace arrive here as well, they are handled through their keys instead
+    pub(crate) fn type_character(&mut self, character: char){
+        if !self.open || character.is_control() || character == '`'{
+            return;
+        }
+        self.input.push(character);
+    }
+
+    pub(crate) fn delete_character(&mut self){
+        self.input.pop();
+    }
+
+    //the output is printed as well, so everything that happened in the console is also in the log
+    pub(crate) fn print(&mut self, line: String){
+        println!("{}", line);
+        self.output.push(line);
+        if self.output.len() > MAX_LINES{
+            self.output.drain(..self.output.len() - MAX_LINES);
+        }
+    }
+
+    pub(crate) fn clear(&mut self){
+        self.output.clear();
+    }
+
+    //takes the typed line to run it, the line is echoed and remembered in the history
+    pub(crate) fn submit(&mut self) -> Option<String>{
+        let line = std::mem::take(&mut self.input).trim().to_string();
+        self.history_index = None;
+        if line.is_empty(){
+            return None;
+        }
+        self.print(format!("> {}", line));
+        if self.history.last() != Some(&line){
+            self.history.push(line.clone());
+            if self.history.len() > MAX_HISTORY{
+                self.history.remove(0);
+            }
+        }
+        Some(line)
+    }
+
+    pub(crate) fn history_previous(&mut self){
+        if self.history.is_empty(){
+            return;
+        }
+        let index = self.history_index.map_or(self.history.len() - 1, |index| index.saturating_sub(1));
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    //going past the newest entry empties the input again
+    pub(crate) fn history_next(&mut self){
+        let Some(index) = self.history_index else{
+            return;
+        };
+        if index + 1 < self.history.len(){
+            self.history_index = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        }else{
+            self.history_index = None;
+            self.input.clear();
+        }
+    }
+
+    //completes the command name, if there are several candidates they are listed and the input is extended to their common start
+    pub(crate) fn complete(&mut self){
+        if self.input.contains(' '){
+            return;
+        }
+        let candidates: Vec<&str> = CONSOLE_COMMANDS.iter().map(|(name, _)| *name).filter(|name| name.starts_with(self.input.as_str())).collect();
+        match candidates.as_slice(){
+            [] => {},
+            [name] => self.input = format!("{} ", name),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |common, name| first.bytes().zip(name.bytes()).take(common).take_while(|(a, b)| a == b).count());
+                self.input = first[..common].to_string();
+                self.print(candidates.join("  "));
+            }
+        }
+    }
+
+    pub(crate) fn build(&self, ui: &mut Ui){
+        if !self.open{
+            return;
+        }
+        let line_height = measure_text("", TEXT_SIZE)[1];
+        let [screen_width, _] = ui.screen_size();
+        let height = (VISIBLE_LINES + 1) as f32 * line_height + 2.0 * PADDING;
+        panel(ui, [0.0, 0.0, screen_width, height]);
+        let visible = &self.output[self.output.len().saturating_sub(VISIBLE_LINES)..];
+        for (index, line) in visible.iter().enumerate(){
+            label(ui, [PADDING, PADDING + index as f32 * line_height], line);
+        }
+        label(ui, [PADDING, PADDING + VISIBLE_LINES as f32 * line_height], &format!("> {}_", self.input));
+    }
+}
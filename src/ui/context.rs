@@ -0,0 +1,126 @@
+use winit::event::{ElementState, MouseButton};
+
+use crate::{controller::input::MouseInputType, rendering::{ui_layer::UiQuad, text::{glyph_atlas::glyph_atlas, layout::place_glyphs}}};
+
+//x and y of the top left corner, width and height, in pixels of the window
+pub(crate) type Rect = [f32; 4];
+
+pub(crate) fn contains(rect: &Rect, point: [f32; 2]) -> bool{
+    point[0] >= rect[0] && point[0] < rect[0] + rect[2] && point[1] >= rect[1] && point[1] < rect[1] + rect[3]
+}
+
+//immediate mode UI: the controller builds all widgets again after every input, a widget is only a function call that adds quads and reports clicks
+//mouse input is routed through here before it reaches the world, everything over a panel or a button of the last built frame belongs to the UI
+pub(crate) struct Ui{
+    screen_size: [f32; 2],
+    cursor: Option<[f32; 2]>,       //None while the cursor is outside of the window
+    press_position: Option<[f32; 2]>,       //where the left button went down, only if that was on the UI
+    release_position: Option<[f32; 2]>,     //where it went up again, the click is handed to the widgets in the next frame
+    quads: Vec<UiQuad>,
+    blocking_rects: Vec<Rect>,      //the rects of this frame that take mouse input
+    previous_blocking_rects: Vec<Rect>,     //the ones of the last finished frame, input is checked against these
+}
+
+impl Ui{
+    pub(crate) fn new() -> Self{
+        Self{
+            screen_size: [1.0, 1.0],
+            cursor: None,
+            press_position: None,
+            release_position: None,
+            quads: Vec::new(),
+            blocking_rects: Vec::new(),
+            previous_blocking_rects: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_screen_size(&mut self, dimensions: (u32, u32)){
+        self.screen_size = [dimensions.0 as f32, dimensions.1 as f32];
+    }
+
+    pub(crate) fn screen_size(&self) -> [f32; 2]{
+        self.screen_size
+    }
+
+    //returns true if the UI used the input, then it must not be handled by the world anymore
+    pub(crate) fn handle_mouse_input(&mut self, input: &MouseInputType) -> bool{
+        match input{
+            MouseInputType::Move(x, y) => {
+                self.cursor = Some([*x, *y]);
+                false
+            }
+            MouseInputType::LeftWindow => {
+                self.cursor = None;
+                false
+            }
+            MouseInputType::EnteredWindow => false,
+            MouseInputType::Click { button: MouseButton::Left, state: ElementState::Pressed } => {
+                self.press_position = self.cursor.filter(|_| self.is_over_ui());
+                self.release_position = None;
+                self.press_position.is_some()
+            }
+            //a press that started on the UI also owns its release, even if the cursor left the widget in between
+            MouseInputType::Click { button: MouseButton::Left, state: ElementState::Released } => {
+                if self.press_position.is_none(){
+                    return false;
+                }
+                self.release_position = self.cursor;
+                true
+            }
+            MouseInputType::Click { .. } | MouseInputType::Scroll { .. } => self.is_over_ui(),
+        }
+    }
+
+    pub(crate) fn is_over_ui(&self) -> bool{
+        self.cursor.is_some_and(|cursor| self.previous_blocking_rects.iter().any(|rect| contains(rect, cursor)))
+    }
+
+    pub(crate) fn begin_frame(&mut self){
+        self.quads.clear();
+        self.blocking_rects.clear();
+    }
+
+    //returns the quads for the renderer. A finished click has been seen by the widgets now, so it is forgotten
+    pub(crate) fn end_frame(&mut self) -> Vec<UiQuad>{
+        std::mem::swap(&mut self.blocking_rects, &mut self.previous_blocking_rects);
+        if self.release_position.is_some() || self.cursor.is_none(){
+            self.press_position = None;
+            self.release_position = None;
+        }
+        std::mem::take(&mut self.quads)
+    }
+
+    pub(crate) fn is_hovered(&self, rect: &Rect) -> bool{
+        self.cursor.is_some_and(|cursor| contains(rect, cursor))
+    }
+
+    //the left button went down on the rect and is still held
+    pub(crate) fn is_held(&self, rect: &Rect) -> bool{
+        self.release_position.is_none() && self.press_position.is_some_and(|position| contains(rect, position))
+    }
+
+    //the left button went down and up again on the rect
+    pub(crate) fn was_clicked(&self, rect: &Rect) -> bool{
+        match (self.press_position, self.release_position){
+            (Some(press), Some(release)) => contains(rect, press) && contains(rect, release),
+            _ => false,
+        }
+    }
+
+    //clicks on the rect don't reach the world anymore, from the next frame on
+    pub(crate) fn block_input(&mut self, rect: Rect){
+        self.blocking_rects.push(rect);
+    }
+
+    pub(crate) fn fill_rect(&mut self, rect: Rect, color: [f32; 4]){
+        self.quads.push(UiQuad{ rect, uv_rect: glyph_atlas().solid_uv_rect, color });
+    }
+
+    //position is the top left corner of the first line
+    pub(crate) fn draw_text(&mut self, text: &str, position: [f32; 2], size: f32, color: [f32; 4]){
+        let quads = &mut self.quads;
+        place_glyphs(text, size, |rect, glyph| {
+            quads.push(UiQuad{ rect: [position[0] + rect[0], position[1] + rect[1], rect[2], rect[3]], uv_rect: glyph.uv_rect, color });
+        });
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{controller::{controller_commands::{ControllerCommand, LevelCommand}, input::BaseProduction}, game_objects::buildings::start_obj::UnitType, model::{load_level_functions::Level, strategy_test::faction::PLAYER_FACTION}};
+
+use super::{context::Ui, widgets::{panel, panel_height, Column, PADDING}};
+
+const HUD_WIDTH: f32 = 200.0;
+const HUD_MARGIN: f32 = 16.0;
+
+//the in-game controls of the current level (None for levels from files), returns the commands of the buttons that were clicked
+pub(crate) fn build_hud(ui: &mut Ui, level: Option<Level>, production: &BaseProduction) -> Vec<ControllerCommand>{
+    let mut commands = Vec::new();
+    if level != Some(Level::StrategyTest){
+        return commands;
+    }
+    //bottom right corner, out of the way of the screen space text in the top left
+    let [screen_width, screen_height] = ui.screen_size();
+    let height = panel_height(1, 3);
+    let (x, y) = (screen_width - HUD_WIDTH - HUD_MARGIN, screen_height - height - HUD_MARGIN);
+    panel(ui, [x, y, HUD_WIDTH, height]);
+    let mut column = Column::new(x + PADDING, y + PADDING, HUD_WIDTH - 2.0 * PADDING);
+    column.label(ui, "Base");
+    let text = match production.queued{
+        0 => "Idle".to_string(),
+        queued => format!("Worker {}% ({} queued)", (production.progress * 100.0) as u32, queued),
+    };
+    column.progress_bar(ui, production.progress, &text);
+    if column.button(ui, "Queue worker (Q)"){
+        commands.push(ControllerCommand::Level(LevelCommand::QueueUnit { faction: PLAYER_FACTION, base_index: 0, unit: UnitType::Worker }));
+    }
+    if column.button(ui, "Cancel (X)"){
+        commands.push(ControllerCommand::Level(LevelCommand::CancelUnit { faction: PLAYER_FACTION, base_index: 0 }));
+    }
+    commands
+}
@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use crate::model::{load_level_functions::{Level, ALL_LEVELS}, level_file::{list_level_files, level_file_title}};
+
+use super::{context::Ui, widgets::{backdrop, panel, panel_height, Column, PADDING}};
+
+const MENU_WIDTH: f32 = 280.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MenuScreen{
+    MainMenu,
+    LevelSelect,
+    Pause,
+    Settings,
+}
+
+//what the controller has to do because of a click in the menu
+pub(crate) enum MenuAction{
+    LoadLevel(Level),
+    LoadLevelFile(PathBuf),
+    ToggleFullscreen,
+    ToggleDebugOverlay,
+    Quit,
+}
+
+//the menus are a stack: the top one is shown and Back returns to the one below it. While the stack is empty the game is played
+pub(crate) struct Menu{
+    screens: Vec<MenuScreen>,
+    level_files: Vec<PathBuf>,      //read again whenever the level select is opened, so new files show up without a restart
+}
+
+impl Menu{
+    pub(crate) fn new() -> Self{
+        let mut menu = Self{
+            screens: Vec::new(),
+            level_files: Vec::new(),
+        };
+        menu.open(MenuScreen::MainMenu);
+        menu
+    }
+
+    pub(crate) fn is_open(&self) -> bool{
+        !self.screens.is_empty()
+    }
+
+    pub(crate) fn current(&self) -> Option<MenuScreen>{
+        self.screens.last().copied()
+    }
+
+    pub(crate) fn open(&mut self, screen: MenuScreen){
+        if screen == MenuScreen::LevelSelect{
+            self.level_files = list_level_files();
+        }
+        self.screens.push(screen);
+    }
+
+    pub(crate) fn back(&mut self){
+        self.screens.pop();
+    }
+
+    pub(crate) fn close(&mut self){
+        self.screens.clear();
+    }
+
+    //escape pauses the game, goes back one menu or resumes from the pause menu. The main menu can only be left through its buttons
+    pub(crate) fn escape(&mut self){
+        match self.current(){
+            None => self.open(MenuScreen::Pause),
+            Some(MenuScreen::MainMenu) if self.screens.len() == 1 => {},
+            Some(_) => self.back(),
+        }
+    }
+
+    pub(crate) fn build(&mut self, ui: &mut Ui) -> Vec<MenuAction>{
+        let mut actions = Vec::new();
+        let Some(screen) = self.current() else{
+            return actions;
+        };
+        backdrop(ui);
+        match screen{
+            MenuScreen::MainMenu => {
+                let mut column = centered_column(ui, 1, 3);
+                column.label(ui, "spawg");
+                if column.button(ui, "Play"){
+                    self.open(MenuScreen::LevelSelect);
+                }
+                if column.button(ui, "Settings"){
+                    self.open(MenuScreen::Settings);
+                }
+                if column.button(ui, "Quit"){
+                    actions.push(MenuAction::Quit);
+                }
+            }
+            MenuScreen::LevelSelect => {
+                let mut column = centered_column(ui, 1, ALL_LEVELS.len() + self.level_files.len() + 1);
+                column.label(ui, "Select a level");
+                for level in ALL_LEVELS{
+                    if column.button(ui, level.title()){
+                        actions.push(MenuAction::LoadLevel(level));
+                    }
+                }
+                for path in self.level_files.iter(){
+                    if column.button(ui, &level_file_title(path)){
+                        actions.push(MenuAction::LoadLevelFile(path.clone()));
+                    }
+                }
+                if column.button(ui, "Back"){
+                    self.back();
+                }
+                if !actions.is_empty(){
+                    self.close();
+                }
+            }
+            MenuScreen::Pause => {
+                let mut column = centered_column(ui, 1, 5);
+                column.label(ui, "Paused");
+                if column.button(ui, "Resume"){
+                    self.close();
+                }
+                if column.button(ui, "Level select"){
+                    self.open(MenuScreen::LevelSelect);
+                }
+                if column.button(ui, "Settings"){
+                    self.open(MenuScreen::Settings);
+                }
+                if column.button(ui, "Main menu"){
+                    self.close();
+                    self.open(MenuScreen::MainMenu);
+                }
+                if column.button(ui, "Quit"){
+                    actions.push(MenuAction::Quit);
+                }
+            }
+            MenuScreen::Settings => {
+                let mut column = centered_column(ui, 1, 3);
+                column.label(ui, "Settings");
+                if column.button(ui, "Fullscreen (Alt+Enter)"){
+                    actions.push(MenuAction::ToggleFullscreen);
+                }
+                if column.button(ui, "Debug overlay (F3)"){
+                    actions.push(MenuAction::ToggleDebugOverlay);
+                }
+                if column.button(ui, "Back"){
+                    self.back();
+                }
+            }
+        }
+        actions
+    }
+}
+
+//draws the panel of a menu in the middle of the window and returns the column for its widgets
+fn centered_column(ui: &mut Ui, labels: usize, buttons: usize) -> Column{
+    let [screen_width, screen_height] = ui.screen_size();
+    let height = panel_height(labels, buttons);
+    let (x, y) = ((screen_width - MENU_WIDTH) / 2.0, ((screen_height - height) / 2.0).max(0.0));
+    panel(ui, [x, y, MENU_WIDTH, height]);
+    Column::new(x + PADDING, y + PADDING, MENU_WIDTH - 2.0 * PADDING)
+}
@@ -0,0 +1,5 @@
+pub(crate) mod context;
+pub(crate) mod widgets;
+pub(crate) mod hud;
+pub(crate) mod menu;
+pub(crate) mod console;
@@ -0,0 +1,104 @@
+use crate::rendering::text::layout::measure_text;
+
+use super::context::{Ui, Rect};
+
+pub(crate) const TEXT_SIZE: f32 = 16.0;
+pub(crate) const PADDING: f32 = 8.0;
+pub(crate) const SPACING: f32 = 6.0;
+pub(crate) const BUTTON_HEIGHT: f32 = 28.0;
+
+//the colors are linear, the surface converts them to srgb, which makes them look a lot lighter
+const BACKDROP_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+const PANEL_COLOR: [f32; 4] = [0.005, 0.008, 0.012, 0.85];
+const TEXT_COLOR: [f32; 4] = [0.95, 0.95, 0.95, 1.0];
+const BUTTON_COLOR: [f32; 4] = [0.03, 0.06, 0.1, 1.0];
+const BUTTON_HOVERED_COLOR: [f32; 4] = [0.06, 0.11, 0.18, 1.0];
+const BUTTON_HELD_COLOR: [f32; 4] = [0.015, 0.03, 0.05, 1.0];
+const PROGRESS_BACKGROUND_COLOR: [f32; 4] = [0.015, 0.015, 0.015, 1.0];
+const PROGRESS_COLOR: [f32; 4] = [0.03, 0.3, 0.05, 1.0];
+
+//a background for other widgets, the world doesn't get clicks on it
+pub(crate) fn panel(ui: &mut Ui, rect: Rect){
+    ui.fill_rect(rect, PANEL_COLOR);
+    ui.block_input(rect);
+}
+
+//dims the whole window behind a menu, nothing below it gets mouse input
+pub(crate) fn backdrop(ui: &mut Ui){
+    let [width, height] = ui.screen_size();
+    ui.fill_rect([0.0, 0.0, width, height], BACKDROP_COLOR);
+    ui.block_input([0.0, 0.0, width, height]);
+}
+
+pub(crate) fn label(ui: &mut Ui, position: [f32; 2], text: &str){
+    ui.draw_text(text, position, TEXT_SIZE, TEXT_COLOR);
+}
+
+//returns true in the frame after the button was clicked
+pub(crate) fn button(ui: &mut Ui, rect: Rect, text: &str) -> bool{
+    let color = if ui.is_held(&rect){
+        BUTTON_HELD_COLOR
+    }else if ui.is_hovered(&rect){
+        BUTTON_HOVERED_COLOR
+    }else{
+        BUTTON_COLOR
+    };
+    ui.fill_rect(rect, color);
+    ui.draw_text(text, centered_text_position(&rect, text), TEXT_SIZE, TEXT_COLOR);
+    ui.block_input(rect);
+    ui.was_clicked(&rect)
+}
+
+//fraction goes from 0.0 to 1.0, the text is drawn centered on top of the bar
+pub(crate) fn progress_bar(ui: &mut Ui, rect: Rect, fraction: f32, text: &str){
+    ui.fill_rect(rect, PROGRESS_BACKGROUND_COLOR);
+    ui.fill_rect([rect[0], rect[1], rect[2] * fraction.clamp(0.0, 1.0), rect[3]], PROGRESS_COLOR);
+    ui.draw_text(text, centered_text_position(&rect, text), TEXT_SIZE, TEXT_COLOR);
+}
+
+fn centered_text_position(rect: &Rect, text: &str) -> [f32; 2]{
+    let [width, height] = measure_text(text, TEXT_SIZE);
+    [rect[0] + (rect[2] - width) / 2.0, rect[1] + (rect[3] - height) / 2.0]
+}
+
+//stacks widgets of the same width from top to bottom, so the callers don't have to add up the heights themselves
+pub(crate) struct Column{
+    x: f32,
+    y: f32,
+    width: f32,
+}
+
+impl Column{
+    pub(crate) fn new(x: f32, y: f32, width: f32) -> Self{
+        Self{ x, y, width }
+    }
+
+    //the rect of the next widget with the given height
+    pub(crate) fn next(&mut self, height: f32) -> Rect{
+        let rect = [self.x, self.y, self.width, height];
+        self.y += height + SPACING;
+        rect
+    }
+
+    pub(crate) fn label(&mut self, ui: &mut Ui, text: &str){
+        let rect = self.next(measure_text(text, TEXT_SIZE)[1]);
+        label(ui, [rect[0], rect[1]], text);
+    }
+
+    pub(crate) fn button(&mut self, ui: &mut Ui, text: &str) -> bool{
+        let rect = self.next(BUTTON_HEIGHT);
+        button(ui, rect, text)
+    }
+
+    pub(crate) fn progress_bar(&mut self, ui: &mut Ui, fraction: f32, text: &str){
+        let rect = self.next(BUTTON_HEIGHT);
+        progress_bar(ui, rect, fraction, text);
+    }
+}
+
+//the height a column of these widgets needs, including the padding of its panel
+pub(crate) fn panel_height(labels: usize, buttons: usize) -> f32{
+    let line_height = measure_text("", TEXT_SIZE)[1];
+    let widgets = labels + buttons;
+    labels as f32 * line_height + buttons as f32 * BUTTON_HEIGHT + widgets.saturating_sub(1) as f32 * SPACING + 2.0 * PADDING
+}